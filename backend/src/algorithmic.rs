@@ -1,22 +1,33 @@
-use std::{
-    collections::{hash_map::Entry, HashMap},
-    hash::Hash,
-};
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
 
 use instant::Instant;
 
 use crate::{
     cube::MoveSequence,
     group::{InverseSemigroup, Magma},
+    intuitive::{ProgressEvent, ProgressPhase},
     permute::CubePermutation3,
     utils::log,
 };
 
 /// S is a 'signature' of the current cube state (see [crate::intuitive::SequenceGraph] for more info).
 /// Query this object to get optimal move sequences for solving a cube into a specific (pre-determined) signature.
-#[derive(Debug)]
 pub struct AlgorithmicSolver<S> {
-    node_info: HashMap<S, MoveSequence>,
+    /// The move sequences that solve each signature, sorted so that the best is first.
+    node_info: HashMap<S, Vec<MoveSequence>>,
+    /// The signature function that the solver was created with.
+    signature: Box<dyn Fn(CubePermutation3) -> S + Send + Sync>,
+}
+
+impl<S> Debug for AlgorithmicSolver<S>
+where
+    S: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlgorithmicSolver")
+            .field("node_info", &self.node_info)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<S> AlgorithmicSolver<S>
@@ -26,66 +37,117 @@ where
     /// Create a new sequence graph from the given generating set.
     /// For each generated move sequence, we generate the signature of the resulting cube permutation.
     /// The signature function should generate the signature of a cube permutation.
-    /// The set of post moves (typically AUF moves) are used for matching signatures, but are elided in the move sequences generated.
+    ///
+    /// Each algorithm is tried after every combination of powers of the pre moves, and before every combination of powers of the post moves.
+    /// The move sequences in `pre_moves` and `post_moves` are therefore not alternatives to each other:
+    /// given the pre moves `U` and `y`, every product of a power of `U` and a power of `y`, such as `U2 y'`, is tried.
+    /// The pre moves (typically AUF moves) are used for matching signatures, but are elided in the move sequences generated.
+    /// Pre moves may also include rotations such as `y`, so that cases are recognised from any angle.
+    /// Rotations are not elided: the generated move sequences end by undoing them, so that the cube is left the right way round.
+    /// Algorithms that turn layers a 3x3x3 cube does not have are skipped.
+    /// Rotations must commute with the other pre moves, as `y` commutes with `U`,
+    /// and the signature should include the position of a centre,
+    /// so that the rotations in the pre and post moves are only matched when they cancel out.
     pub fn new(
         graph_name: &'static str,
         alg_set: Vec<MoveSequence>,
         pre_moves: Vec<MoveSequence>,
         post_moves: Vec<MoveSequence>,
-        signature: impl Fn(CubePermutation3) -> S,
+        signature: impl Fn(CubePermutation3) -> S + Send + Sync + 'static,
         metric: impl Fn(&MoveSequence) -> u64,
+    ) -> Self {
+        Self::new_with_progress(
+            graph_name,
+            alg_set,
+            pre_moves,
+            post_moves,
+            signature,
+            metric,
+            |_| {},
+        )
+    }
+
+    /// Creates a new solver as in [AlgorithmicSolver::new], reporting its progress to the given callback.
+    /// Progress is reported after each algorithm.
+    pub fn new_with_progress(
+        graph_name: &'static str,
+        alg_set: Vec<MoveSequence>,
+        pre_moves: Vec<MoveSequence>,
+        post_moves: Vec<MoveSequence>,
+        signature: impl Fn(CubePermutation3) -> S + Send + Sync + 'static,
+        metric: impl Fn(&MoveSequence) -> u64,
+        mut progress: impl FnMut(ProgressEvent),
     ) -> Self {
         let start_time = Instant::now();
 
         let mut this = Self {
             node_info: HashMap::new(),
+            signature: Box::new(signature),
         };
 
-        let mut real_pre_moves = pre_moves
-            .iter()
-            .map(|mv| vec![mv.inverse(), mv.clone().op(mv.clone()), mv.clone()])
-            .flatten()
-            .map(|mv| mv.canonicalise())
-            .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
-            .collect::<Vec<_>>();
-        real_pre_moves.sort();
-        real_pre_moves.dedup();
-
-        let mut real_post_moves = post_moves
-            .iter()
-            .map(|mv| vec![mv.inverse(), mv.clone().op(mv.clone()), mv.clone()])
-            .flatten()
-            .map(|mv| mv.canonicalise())
-            .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
+        let real_pre_moves = powers(&pre_moves);
+        let real_post_moves = powers(&post_moves)
+            .into_iter()
+            .map(|(post_move, _)| post_move)
             .collect::<Vec<_>>();
-        real_post_moves.sort();
-        real_post_moves.dedup();
 
-        for alg in alg_set {
-            for pre_move in &real_pre_moves {
+        // For each signature, the index of each algorithm that solves it,
+        // together with the best move sequence using that algorithm and its metric.
+        let mut candidates = HashMap::<S, Vec<(usize, u64, MoveSequence)>>::new();
+
+        let alg_count = alg_set.len();
+        for (i, alg) in alg_set.into_iter().enumerate() {
+            progress(ProgressEvent {
+                name: graph_name,
+                phase: ProgressPhase::GeneratingAlgorithms,
+                nodes_expanded: i,
+                queue_size: alg_count - i,
+                finished: false,
+            });
+            for (pre_move, pre_rotation) in &real_pre_moves {
                 for post_move in &real_post_moves {
                     let moves_no_pre = post_move.clone().op(alg.clone());
-                    let moves_no_pre_inverse = moves_no_pre.inverse();
+                    let moves_no_pre_inverse = pre_rotation.inverse().op(moves_no_pre.inverse());
                     let moves = moves_no_pre.op(pre_move.clone());
-                    let sig = signature(CubePermutation3::from_move_sequence(moves));
-                    match this.node_info.entry(sig) {
-                        Entry::Occupied(mut entry) => {
-                            // If two move sequences gave the same result, shorter is better.
-                            let new_metric = metric(&moves_no_pre_inverse);
-                            let previous_metric = metric(entry.get());
-                            if new_metric < previous_metric {
-                                // Replace with the new entry.
-                                entry.insert(moves_no_pre_inverse);
-                            }
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(moves_no_pre_inverse);
+                    let permutation = match CubePermutation3::from_move_sequence(moves) {
+                        Some(permutation) => permutation,
+                        None => continue,
+                    };
+                    let sig = (this.signature)(permutation);
+                    let new_metric = metric(&moves_no_pre_inverse);
+
+                    let entries = candidates.entry(sig).or_default();
+                    if let Some(entry) = entries.iter_mut().find(|(alg, _, _)| *alg == i) {
+                        // Variants of the same algorithm with different pre and post moves are not alternatives.
+                        // If two move sequences gave the same result, shorter is better.
+                        if new_metric < entry.1 {
+                            *entry = (i, new_metric, moves_no_pre_inverse);
                         }
+                    } else if entries
+                        .iter()
+                        .all(|(_, _, seq)| *seq != moves_no_pre_inverse)
+                    {
+                        entries.push((i, new_metric, moves_no_pre_inverse));
                     }
                 }
             }
         }
 
+        for (sig, mut entries) in candidates {
+            // The sort is stable, so earlier algorithms are preferred over later algorithms with the same metric.
+            entries.sort_by_key(|(_, metric, _)| *metric);
+            this.node_info
+                .insert(sig, entries.into_iter().map(|(_, _, seq)| seq).collect());
+        }
+
+        progress(ProgressEvent {
+            name: graph_name,
+            phase: ProgressPhase::GeneratingAlgorithms,
+            nodes_expanded: alg_count,
+            queue_size: 0,
+            finished: true,
+        });
+
         let end_time = Instant::now();
         let duration = end_time - start_time;
         log!(
@@ -98,7 +160,227 @@ where
         this
     }
 
+    /// Gives the best move sequence to solve the given signature.
+    /// The cube is solved up to the elided pre moves.
     pub fn solve(&self, signature: &S) -> Option<&MoveSequence> {
-        self.node_info.get(signature)
+        self.solve_all(signature).first()
+    }
+
+    /// Gives every move sequence that solves the given signature, using a different algorithm each,
+    /// from best to worst.
+    pub fn solve_all(&self, signature: &S) -> &[MoveSequence] {
+        self.node_info.get(signature).map_or(&[], Vec::as_slice)
+    }
+
+    /// The signature of the given cube permutation, as computed by the signature function that the solver was created with.
+    pub fn signature(&self, permutation: CubePermutation3) -> S {
+        (self.signature)(permutation)
+    }
+}
+
+/// Two algorithmic solvers used one after the other, such as OLL then PLL for a two-look last layer.
+/// Create one with [AlgorithmicSolver::then].
+#[derive(Debug)]
+pub struct ChainedSolver<S1, S2> {
+    first: AlgorithmicSolver<S1>,
+    second: AlgorithmicSolver<S2>,
+}
+
+impl<S> AlgorithmicSolver<S> {
+    /// Chains this solver with `next`, which is used on the cube that this solver leaves behind.
+    pub fn then<S2>(self, next: AlgorithmicSolver<S2>) -> ChainedSolver<S, S2> {
+        ChainedSolver {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+impl<S1, S2> ChainedSolver<S1, S2> {
+    /// The solver that is used first.
+    pub fn first(&self) -> &AlgorithmicSolver<S1> {
+        &self.first
+    }
+
+    /// The solver that is used on the cube that the first solver leaves behind.
+    pub fn second(&self) -> &AlgorithmicSolver<S2> {
+        &self.second
+    }
+}
+
+impl<S1, S2> ChainedSolver<S1, S2>
+where
+    S1: Eq + Hash,
+    S2: Eq + Hash,
+{
+    /// Solves the cube with the first solver, then solves the resulting cube with the second,
+    /// giving both move sequences one after the other.
+    /// As with [AlgorithmicSolver::solve], the cube is solved up to the elided pre moves of the second solver.
+    pub fn solve(&self, permutation: CubePermutation3) -> Option<MoveSequence> {
+        let first = self.first.solve(&self.first.signature(permutation))?;
+        let permutation = CubePermutation3::from_move_sequence(first.clone())?.op(permutation);
+        let second = self.second.solve(&self.second.signature(permutation))?;
+        Some(second.clone().op(first.clone()))
+    }
+}
+
+/// Every product of one power of each of the given move sequences, including the empty product.
+/// Each product is paired with the product of just the powers of rotations.
+fn powers(moves: &[MoveSequence]) -> Vec<(MoveSequence, MoveSequence)> {
+    let empty = MoveSequence { moves: Vec::new() };
+    let mut products = vec![(empty.clone(), empty.clone())];
+    for mv in moves {
+        let rotation = mv.moves.iter().all(|mv| mv.is_rotation());
+        products = products
+            .into_iter()
+            .flat_map(|(product, rotations)| {
+                [
+                    empty.clone(),
+                    mv.clone(),
+                    mv.clone().op(mv.clone()),
+                    mv.inverse(),
+                ]
+                .into_iter()
+                .map(move |power| {
+                    let rotations = if rotation {
+                        power.clone().op(rotations.clone())
+                    } else {
+                        rotations.clone()
+                    };
+                    (
+                        power.op(product.clone()).canonicalise(),
+                        rotations.canonicalise(),
+                    )
+                })
+            })
+            .collect();
+    }
+    products.sort();
+    products.dedup();
+    products
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cfop::{oll_signature, pll_signature, OLL_ALGORITHMS, PLL_ALGORITHMS, U_CORNERS, U_EDGES},
+        cube::FaceType,
+        group::{CyclicGroup, GroupAction, Unital},
+        permute::{CentreCubelet, CornerCubelet, EdgeCubelet},
+    };
+
+    use super::*;
+
+    fn signature(cube: CubePermutation3) -> ([EdgeCubelet; 4], [CornerCubelet; 4], CentreCubelet) {
+        (
+            U_EDGES.map(|edge| {
+                cube.edges()
+                    .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                    .0
+            }),
+            U_CORNERS.map(|corner| {
+                cube.corners()
+                    .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+                    .0
+            }),
+            cube.centres().act(&CentreCubelet(FaceType::F)),
+        )
+    }
+
+    #[test]
+    fn rotations() {
+        let algs = [
+            "R U R' U' R' F R2 U' R' U' R U R' F'",
+            "R U' R U R U R U' R' U' R2",
+        ];
+        let parse = |seq: &str| seq.parse::<MoveSequence>().unwrap();
+        let solver = AlgorithmicSolver::new(
+            "pll",
+            algs.iter().map(|alg| parse(alg)).collect(),
+            vec![parse("y")],
+            vec![parse("U"), parse("y")],
+            signature,
+            crate::metric::htm,
+        );
+
+        // Perform each algorithm from each angle, followed by each AUF.
+        let powers = ["", "", "2", "'"];
+        for alg in algs {
+            for angle in 1..4 {
+                for auf in 0..4 {
+                    let mut scramble = format!("y{} {} y{}", powers[angle], alg, powers[4 - angle]);
+                    if auf > 0 {
+                        scramble += &format!(" U{}", powers[auf]);
+                    }
+                    let permutation =
+                        CubePermutation3::from_move_sequence(parse(&scramble)).unwrap();
+                    let solution = solver.solve(&solver.signature(permutation)).unwrap();
+                    assert_eq!(
+                        CubePermutation3::from_move_sequence(solution.clone())
+                            .unwrap()
+                            .op(permutation),
+                        CubePermutation3::identity(),
+                        "{}: {}",
+                        scramble,
+                        solution
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_look_last_layer() {
+        let parse = |seq: &str| seq.parse::<MoveSequence>().unwrap();
+        let skip = MoveSequence { moves: Vec::new() };
+        let oll = AlgorithmicSolver::new(
+            "two_look_oll",
+            OLL_ALGORITHMS
+                .into_iter()
+                .map(parse)
+                .chain(std::iter::once(skip.clone()))
+                .collect(),
+            vec![parse("U")],
+            vec![parse("U")],
+            oll_signature,
+            crate::metric::etm,
+        );
+        let pll = AlgorithmicSolver::new(
+            "two_look_pll",
+            PLL_ALGORITHMS
+                .into_iter()
+                .map(|(_, alg)| parse(alg))
+                .chain(std::iter::once(skip))
+                .collect(),
+            vec![parse("U")],
+            vec![parse("U")],
+            pll_signature,
+            crate::metric::etm,
+        );
+        let last_layer = oll.then(pll);
+
+        for scramble in [
+            "R U R' U R U2 R'",
+            "R U R' U R U2 R' U' R U R' U' R' F R2 U' R' U' R U R' F'",
+            "F R U R' U' F' U2 R U R' U' R' F R2 U' R' U' R U R' F'",
+            "r U R' U' r' F R F' U R U2 R' U' R U' R' U2",
+        ] {
+            let permutation = CubePermutation3::from_move_sequence(parse(scramble)).unwrap();
+            let solution = last_layer.solve(permutation).unwrap();
+            let permutation = CubePermutation3::from_move_sequence(solution.clone())
+                .unwrap()
+                .op(permutation);
+            // The last layer is solved up to a final turn of the U face.
+            assert!(
+                ["U", "U2", "U'"]
+                    .into_iter()
+                    .map(|auf| CubePermutation3::from_move_sequence(parse(auf)).unwrap())
+                    .chain(std::iter::once(CubePermutation3::identity()))
+                    .any(|auf| auf.op(permutation) == CubePermutation3::identity()),
+                "{}: {}",
+                scramble,
+                solution
+            );
+        }
     }
 }
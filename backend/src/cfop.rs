@@ -0,0 +1,529 @@
+use crate::{
+    algorithmic::{AlgorithmicSolver, ChainedSolver},
+    cube::{
+        CornerType, EdgeType, MoveSequence,
+        {CornerType::*, EdgeType::*},
+    },
+    group::{CyclicGroup, GroupAction, InverseSemigroup, Magma, Unital},
+    intuitive::{SequenceGraph, SequenceSolver},
+    metric,
+    permute::{CornerCubelet, CubePermutation3, EdgeCubelet},
+    recognition::PllCase,
+    solve::{move_sequence_to_intuitive_action, run_method, Action, Method, Step},
+};
+
+type CfopCrossSignature = [(EdgeCubelet, CyclicGroup<2>); 2];
+type CfopPairSignature = (
+    (EdgeCubelet, CyclicGroup<2>),
+    (CornerCubelet, CyclicGroup<3>),
+);
+pub(crate) type CfopOllSignature = ([CyclicGroup<2>; 4], [CyclicGroup<3>; 4]);
+pub(crate) type CfopPllSignature = ([EdgeCubelet; 4], [CornerCubelet; 4]);
+
+/// The U layer edges and corners, in the order used by the last layer signatures.
+pub(crate) const U_EDGES: [EdgeType; 4] = [UF, UR, UB, UL];
+pub(crate) const U_CORNERS: [CornerType; 4] = [FUL, FUR, BUR, BUL];
+
+/// The inserts that take the edge and corner of an F2L slot into the U layer, turn U, and put them back.
+/// These do not affect the cross, or any other F2L slot.
+pub(crate) fn slot_inserts(faces: [&str; 2]) -> Vec<String> {
+    let mut inserts = Vec::new();
+    for face in faces {
+        let inverse = match face.strip_suffix('\'') {
+            Some(face) => face.to_string(),
+            None => format!("{}'", face),
+        };
+        for turn in ["U", "U2", "U'"] {
+            inserts.push(format!("{} {} {}", face, turn, inverse));
+        }
+    }
+    inserts
+}
+
+pub(crate) fn edges_signature(cube: CubePermutation3, edges: [EdgeType; 2]) -> CfopCrossSignature {
+    edges.map(|edge| {
+        cube.edges()
+            .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+    })
+}
+
+pub(crate) fn pair_signature(
+    cube: CubePermutation3,
+    edge: EdgeType,
+    corner: CornerType,
+) -> CfopPairSignature {
+    (
+        cube.edges()
+            .act(&(EdgeCubelet(edge), CyclicGroup::identity())),
+        cube.corners()
+            .act(&(CornerCubelet(corner), CyclicGroup::identity())),
+    )
+}
+
+/// One algorithm for each OLL case, in the standard numbering, so OLL `n` is solved by `OLL_ALGORITHMS[n - 1]`.
+pub(crate) const OLL_ALGORITHMS: [&str; 57] = [
+    "R U2 R2 F R F' U2 R' F R F'",
+    "F R U R' U' F' f R U R' U' f'",
+    "f R U R' U' f' U' F R U R' U' F'",
+    "f R U R' U' f' U F R U R' U' F'",
+    "r' U2 R U R' U r",
+    "r U2 R' U' R U' r'",
+    "r U R' U R U2 r'",
+    "l' U' L U' L' U2 l",
+    "R U R' U' R' F R2 U R' U' F'",
+    "R U R' U R' F R F' R U2 R'",
+    "r U R' U R' F R F' R U2 r'",
+    "M' R' U' R U' R' U2 R U' R r'",
+    "F U R U' R2 F' R U R U' R'",
+    "R' F R U R' F' R F U' F'",
+    "r' U' r R' U' R U r' U r",
+    "r U r' R U R' U' r U' r'",
+    "F R' F' R2 r' U R U' R' U' M'",
+    "r U R' U R U2 r2 U' R U' R' U2 r",
+    "r' R U R U R' U' M' R' F R F'",
+    "r U R' U' M2 U R U' R' U' M'",
+    "R U2 R' U' R U R' U' R U' R'",
+    "R U2 R2 U' R2 U' R2 U2 R",
+    "R2 D' R U2 R' D R U2 R",
+    "r U R' U' r' F R F'",
+    "F' r U R' U' r' F R",
+    "R U2 R' U' R U' R'",
+    "R U R' U R U2 R'",
+    "r U R' U' M U R U' R'",
+    "R U R' U' R U' R' F' U' F R U R'",
+    "F R' F R2 U' R' U' R U R' F2",
+    "R' U' F U R U' R' F' R",
+    "L U F' U' L' U L F L'",
+    "R U R' U' R' F R F'",
+    "R U R2 U' R' F R U R U' F'",
+    "R U2 R2 F R F' R U2 R'",
+    "L' U' L U' L' U L U L F' L' F",
+    "F R' F' R U R U' R'",
+    "R U R' U R U' R' U' R' F R F'",
+    "L F' L' U' L U F U' L'",
+    "R' F R U R' U' F' U R",
+    "R U R' U R U2 R' F R U R' U' F'",
+    "R' U' R U' R' U2 R F R U R' U' F'",
+    "F' U' L' U L F",
+    "F U R U' R' F'",
+    "F R U R' U' F'",
+    "R' U' R' F R F' U R",
+    "R' U' R' F R F' R' F R F' U R",
+    "F R U R' U' R U R' U' F'",
+    "r U' r2 U r2 U r2 U' r",
+    "r' U r2 U' r2 U' r2 U r'",
+    "F U R U' R' U R U' R' F'",
+    "R U R' U R U' B U' B' R'",
+    "l' U2 L U L' U' L U L' U l",
+    "r U2 R' U' R U R' U' R U' r'",
+    "R' F R U R U' R2 F' R2 U' R' U R U R'",
+    "r' U' r U' R' U R U' R' U R r' U r",
+    "R U R' U' M' U R U' r'",
+];
+
+/// One algorithm for each PLL case.
+pub(crate) const PLL_ALGORITHMS: [(PllCase, &str); 21] = [
+    // Edges only
+    (PllCase::Ua, "M2 U M U2 M' U M2"),
+    (PllCase::Ub, "M2 U' M U2 M' U' M2"),
+    (PllCase::H, "M2 U M2 U2 M2 U M2"),
+    (PllCase::Z, "M' U M2 U M2 U M' U2 M2"),
+    // Corners only
+    (PllCase::Aa, "x R' U R' D2 R U' R' D2 R2 x'"),
+    (PllCase::Ab, "x R2 D2 R U R' D2 R U' R x'"),
+    (PllCase::E, "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+    // Adjacent corner swap
+    (PllCase::T, "R U R' U' R' F R2 U' R' U' R U R' F'"),
+    (PllCase::F, "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+    (PllCase::Ja, "x R2 F R F' R U2 r' U r U2 x'"),
+    (PllCase::Jb, "R U R' F' R U R' U' R' F R2 U' R'"),
+    (PllCase::Ra, "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+    (PllCase::Rb, "R2 F R U R U' R' F' R U2 R' U2 R"),
+    (PllCase::Ga, "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+    (PllCase::Gb, "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+    (PllCase::Gc, "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+    (PllCase::Gd, "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+    // Diagonal corner swap
+    (PllCase::Y, "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+    (PllCase::V, "R' U R' U' R D' R' D R' U D' R2 U' R2 D R2"),
+    (
+        PllCase::Na,
+        "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+    ),
+    (PllCase::Nb, "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+];
+
+pub(crate) fn oll_signature(cube: CubePermutation3) -> CfopOllSignature {
+    (
+        // Unact is used to get orientation: we don't care which piece is in this position,
+        // just how it is oriented relative to where the piece should belong.
+        U_EDGES.map(|edge| {
+            cube.edges()
+                .unact(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .1
+        }),
+        U_CORNERS.map(|corner| {
+            cube.corners()
+                .unact(&(CornerCubelet(corner), CyclicGroup::identity()))
+                .1
+        }),
+    )
+}
+
+pub(crate) fn pll_signature(cube: CubePermutation3) -> CfopPllSignature {
+    (
+        U_EDGES.map(|edge| {
+            cube.edges()
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .0
+        }),
+        U_CORNERS.map(|corner| {
+            cube.corners()
+                .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+                .0
+        }),
+    )
+}
+
+fn pair_solver(
+    graph_name: &'static str,
+    slots: &[[&str; 2]],
+    edge: EdgeType,
+    corner: CornerType,
+) -> SequenceSolver<CfopPairSignature> {
+    // Only the inserts of this slot and the unsolved slots are used, so the solved slots are preserved.
+    let gen_set = std::iter::once("U".to_string())
+        .chain(slots.iter().flat_map(|&faces| slot_inserts(faces)))
+        .map(|x| x.parse::<MoveSequence>().unwrap())
+        .collect::<Vec<_>>();
+
+    let graph = SequenceGraph::new(graph_name, gen_set, |cube| {
+        pair_signature(cube, edge, corner)
+    });
+    graph.search(
+        (
+            (EdgeCubelet(edge), CyclicGroup::identity()),
+            (CornerCubelet(corner), CyclicGroup::identity()),
+        ),
+        metric::etm,
+    )
+}
+
+// The faces whose inserts affect each F2L slot.
+// The slots are solved in this order.
+pub(crate) const FR_SLOT: [&str; 2] = ["R", "F'"];
+pub(crate) const FL_SLOT: [&str; 2] = ["L'", "F"];
+pub(crate) const BR_SLOT: [&str; 2] = ["R'", "B"];
+pub(crate) const BL_SLOT: [&str; 2] = ["L", "B'"];
+
+lazy_static::lazy_static! {
+    /// Solving all four cross edges at once needs a very large table,
+    /// so the cross is solved two edges at a time.
+    static ref CFOP_CROSS_FIRST_EDGES: SequenceSolver<CfopCrossSignature> = {
+        let gen_set = vec!["F", "R", "U", "B", "L", "D"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("cfop_cross1", gen_set, |cube| {
+            edges_signature(cube, [DF, DR])
+        });
+        graph.search(edges_signature(CubePermutation3::identity(), [DF, DR]), metric::etm)
+    };
+
+    /// The F and R faces are only turned in inserts, which put the DF and DR edges back.
+    static ref CFOP_CROSS_LAST_EDGES: SequenceSolver<CfopCrossSignature> = {
+        let gen_set = ["U", "B", "L"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .chain(slot_inserts(["R", "R'"]))
+            .chain(slot_inserts(["F", "F'"]))
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("cfop_cross2", gen_set, |cube| {
+            edges_signature(cube, [DB, DL])
+        });
+        graph.search(edges_signature(CubePermutation3::identity(), [DB, DL]), metric::etm)
+    };
+
+    static ref CFOP_FIRST_PAIR: SequenceSolver<CfopPairSignature> =
+        pair_solver("cfop1p", &[FR_SLOT, FL_SLOT, BR_SLOT, BL_SLOT], FR, FDR);
+    static ref CFOP_SECOND_PAIR: SequenceSolver<CfopPairSignature> =
+        pair_solver("cfop2p", &[FL_SLOT, BR_SLOT, BL_SLOT], FL, FDL);
+    static ref CFOP_THIRD_PAIR: SequenceSolver<CfopPairSignature> =
+        pair_solver("cfop3p", &[BR_SLOT, BL_SLOT], BR, BDR);
+    static ref CFOP_FOURTH_PAIR: SequenceSolver<CfopPairSignature> =
+        pair_solver("cfop4p", &[BL_SLOT], BL, BDL);
+
+    /// OLL then PLL, which together solve the last layer up to a final turn of the U layer.
+    static ref LAST_LAYER: ChainedSolver<CfopOllSignature, CfopPllSignature> = {
+        // Inverting an algorithm gives an algorithm for another case,
+        // so together with their inverses these cover every case.
+        let alg_set = OLL_ALGORITHMS
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .flat_map(|alg| [alg.inverse(), alg])
+            // OLL skip: the last layer might already be oriented.
+            .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
+            .collect::<Vec<_>>();
+
+        let pre_moves = vec!["U".parse::<MoveSequence>().unwrap()];
+        let post_moves = vec!["U".parse::<MoveSequence>().unwrap()];
+
+        let oll = AlgorithmicSolver::new("cfop_oll", alg_set, pre_moves, post_moves, oll_signature, metric::etm);
+
+        let alg_set = PLL_ALGORITHMS
+            .into_iter()
+            .map(|(_, alg)| alg)
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            // PLL skip: the last layer might already be solved, up to AUF.
+            .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
+            .collect::<Vec<_>>();
+
+        let pre_moves = vec!["U".parse::<MoveSequence>().unwrap()];
+        let post_moves = vec!["U".parse::<MoveSequence>().unwrap()];
+
+        let pll = AlgorithmicSolver::new("cfop_pll", alg_set, pre_moves, post_moves, pll_signature, metric::etm);
+
+        oll.then(pll)
+    };
+}
+
+/// Solves the DF, DR, DB, and DL edges.
+pub fn cross(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let first = CFOP_CROSS_FIRST_EDGES.solve(&edges_signature(permutation, [DF, DR]))?;
+    let permutation = CubePermutation3::from_move_sequence(first.clone())?.op(permutation);
+    let last = CFOP_CROSS_LAST_EDGES.solve(&edges_signature(permutation, [DB, DL]))?;
+    Some(last.clone().op(first.clone()))
+}
+
+pub fn cross_action(permutation: CubePermutation3) -> Option<Action> {
+    cross(permutation).map(|seq| move_sequence_to_intuitive_action("Cross", seq))
+}
+
+pub fn first_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    CFOP_FIRST_PAIR.solve(&pair_signature(permutation, FR, FDR))
+}
+
+pub fn first_pair_action(permutation: CubePermutation3) -> Option<Action> {
+    first_pair(permutation).map(|seq| move_sequence_to_intuitive_action("First pair", seq.clone()))
+}
+
+pub fn second_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    CFOP_SECOND_PAIR.solve(&pair_signature(permutation, FL, FDL))
+}
+
+pub fn second_pair_action(permutation: CubePermutation3) -> Option<Action> {
+    second_pair(permutation)
+        .map(|seq| move_sequence_to_intuitive_action("Second pair", seq.clone()))
+}
+
+pub fn third_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    CFOP_THIRD_PAIR.solve(&pair_signature(permutation, BR, BDR))
+}
+
+pub fn third_pair_action(permutation: CubePermutation3) -> Option<Action> {
+    third_pair(permutation).map(|seq| move_sequence_to_intuitive_action("Third pair", seq.clone()))
+}
+
+pub fn fourth_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    CFOP_FOURTH_PAIR.solve(&pair_signature(permutation, BL, BDL))
+}
+
+pub fn fourth_pair_action(permutation: CubePermutation3) -> Option<Action> {
+    fourth_pair(permutation)
+        .map(|seq| move_sequence_to_intuitive_action("Fourth pair", seq.clone()))
+}
+
+pub fn oll(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    let oll = LAST_LAYER.first();
+    oll.solve(&oll.signature(permutation))
+}
+
+pub fn oll_action(permutation: CubePermutation3) -> Option<Action> {
+    oll(permutation).map(|seq| move_sequence_to_intuitive_action("OLL", seq.clone()))
+}
+
+/// Permutes the last layer, including the final U layer turn.
+pub fn pll(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let pll = LAST_LAYER.second();
+    let pll = pll.solve(&pll.signature(permutation))?;
+    with_auf(pll.clone(), permutation)
+}
+
+/// Solves the last layer with OLL then PLL, including the final U layer turn.
+pub fn last_layer(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let last_layer = LAST_LAYER.solve(permutation)?;
+    with_auf(last_layer, permutation)
+}
+
+/// Follows the given move sequence with the turn of the U layer that solves the cube after it, if there is one.
+fn with_auf(seq: MoveSequence, permutation: CubePermutation3) -> Option<MoveSequence> {
+    let permutation = CubePermutation3::from_move_sequence(seq.clone())?.op(permutation);
+    ["U", "U2", "U'"]
+        .into_iter()
+        .map(|auf| auf.parse::<MoveSequence>().unwrap())
+        .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
+        .find(|auf| {
+            CubePermutation3::from_move_sequence(auf.clone()).map(|auf| auf.op(permutation))
+                == Some(CubePermutation3::identity())
+        })
+        .map(|auf| auf.op(seq.clone()))
+}
+
+pub fn pll_action(permutation: CubePermutation3) -> Option<Action> {
+    pll(permutation).map(|seq| move_sequence_to_intuitive_action("PLL", seq))
+}
+
+/// The steps of the CFOP method, in the order they are performed.
+const STEPS: [Step; 7] = [
+    ("Cross", cross_action, None),
+    ("First pair", first_pair_action, None),
+    ("Second pair", second_pair_action, None),
+    ("Third pair", third_pair_action, None),
+    ("Fourth pair", fourth_pair_action, None),
+    ("OLL", oll_action, None),
+    ("PLL", pll_action, None),
+];
+
+/// The CFOP method: a cross on the bottom, then the first two layers pair by pair, then the last layer in two looks.
+pub struct Cfop;
+
+impl Method for Cfop {
+    fn name(&self) -> &'static str {
+        "CFOP method"
+    }
+
+    fn steps(&self) -> &'static [Step] {
+        &STEPS
+    }
+}
+
+pub fn solve(permutation: CubePermutation3) -> Option<Action> {
+    run_method(&Cfop, permutation)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        group::Enumerable,
+        permute::{CentrePermutation, CornerPermutation, EdgePermutation},
+    };
+
+    use super::*;
+
+    /// A cube with the first two layers solved, and the given last layer.
+    /// Each U layer piece is moved to the position at the given index, and given the given orientation.
+    fn last_layer(edges: [(usize, u8); 4], corners: [(usize, u8); 4]) -> CubePermutation3 {
+        let mut edge_map = EdgeCubelet::enumerate().map(|e| (e, CyclicGroup::identity()));
+        for (i, (target, orientation)) in edges.into_iter().enumerate() {
+            edge_map[U_EDGES[i] as usize] =
+                (EdgeCubelet(U_EDGES[target]), CyclicGroup::new(orientation));
+        }
+        let mut corner_map = CornerCubelet::enumerate().map(|c| (c, CyclicGroup::identity()));
+        for (i, (target, orientation)) in corners.into_iter().enumerate() {
+            corner_map[U_CORNERS[i] as usize] = (
+                CornerCubelet(U_CORNERS[target]),
+                CyclicGroup::new(orientation),
+            );
+        }
+        CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            EdgePermutation::new_unchecked(edge_map),
+            CornerPermutation::new_unchecked(corner_map),
+        )
+    }
+
+    /// Every arrangement of four pieces, together with whether it is an odd permutation.
+    fn arrangements() -> Vec<([usize; 4], bool)> {
+        let mut result = Vec::new();
+        for a in 0..4 {
+            for b in 0..4 {
+                for c in 0..4 {
+                    if a == b || b == c || a == c {
+                        continue;
+                    }
+                    let arrangement = [a, b, c, 6 - a - b - c];
+                    let inversions = (0..4)
+                        .flat_map(|i| (i + 1..4).map(move |j| (i, j)))
+                        .filter(|&(i, j)| arrangement[i] > arrangement[j])
+                        .count();
+                    result.push((arrangement, inversions % 2 == 1));
+                }
+            }
+        }
+        result
+    }
+
+    fn solve_last_layer(permutation: CubePermutation3) -> CubePermutation3 {
+        let solution = super::last_layer(permutation)
+            .unwrap_or_else(|| panic!("no last layer solution for {}", permutation));
+        CubePermutation3::from_move_sequence(solution)
+            .unwrap()
+            .op(permutation)
+    }
+
+    #[test]
+    fn last_layer_cases() {
+        // Every orientation of the last layer can be solved.
+        for flips in 0..8 {
+            let flips = [flips % 2, flips / 2 % 2, flips / 4];
+            let last_flip = flips.iter().sum::<u8>() % 2;
+            for twists in 0..27 {
+                let twists = [twists % 3, twists / 3 % 3, twists / 9];
+                let last_twist = (6 - twists.iter().sum::<u8>()) % 3;
+                let permutation = last_layer(
+                    [0, 1, 2, 3].map(|i| (i, *flips.get(i).unwrap_or(&last_flip))),
+                    [0, 1, 2, 3].map(|i| (i, *twists.get(i).unwrap_or(&last_twist))),
+                );
+                assert_eq!(
+                    solve_last_layer(permutation),
+                    CubePermutation3::identity(),
+                    "{}",
+                    permutation
+                );
+            }
+        }
+
+        // Every permutation of the last layer can be solved.
+        let mut cases = 0;
+        for (edges, edges_odd) in arrangements() {
+            for (corners, corners_odd) in arrangements() {
+                if edges_odd != corners_odd {
+                    continue;
+                }
+                let permutation = last_layer(edges.map(|e| (e, 0)), corners.map(|c| (c, 0)));
+                assert_eq!(
+                    solve_last_layer(permutation),
+                    CubePermutation3::identity(),
+                    "{}",
+                    permutation
+                );
+                cases += 1;
+            }
+        }
+        assert_eq!(cases, 288);
+    }
+
+    #[test]
+    fn cfop_solve() {
+        let mut rng = StdRng::seed_from_u64(13579);
+        for _ in 0..5 {
+            let permutation = CubePermutation3::random_solvable(&mut rng);
+            let solution = solve(permutation).unwrap();
+            let final_permutation =
+                CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                    .unwrap()
+                    .op(permutation);
+            assert_eq!(
+                final_permutation,
+                CubePermutation3::identity(),
+                "{}",
+                permutation
+            );
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Finds commutators that solve 3-cycles of pieces, as used in fewest moves solving and blindfolded solving.
+
+use std::collections::HashMap;
+
+use crate::{
+    cube::{CornerType, EdgeType, Move, MoveSequence},
+    group::{CyclicGroup, Enumerable, GroupAction, InverseSemigroup, Magma, Unital},
+    permute::{CentrePermutation, CornerCubelet, CubePermutation3, EdgeCubelet},
+};
+
+type CycleSignature = (
+    [(EdgeCubelet, CyclicGroup<2>); 12],
+    [(CornerCubelet, CyclicGroup<3>); 8],
+);
+
+/// Where each edge and corner goes. The centres are never moved by a commutator of face and slice turns,
+/// so this determines the permutation.
+fn cycle_signature(permutation: CubePermutation3) -> CycleSignature {
+    (
+        EdgeType::enumerate().map(|edge| {
+            permutation
+                .edges()
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+        }),
+        CornerType::enumerate().map(|corner| {
+            permutation
+                .corners()
+                .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+        }),
+    )
+}
+
+/// Whether the permutation cycles three edges or three corners, and moves nothing else.
+/// The pieces may be reoriented as they move, but a piece that is only twisted or flipped in place is not part of a cycle.
+fn is_pure_three_cycle(permutation: CubePermutation3) -> bool {
+    if *permutation.centres() != CentrePermutation::identity() {
+        return false;
+    }
+    let (edges, corners) = cycle_signature(permutation);
+    let moved_edges = EdgeType::enumerate()
+        .into_iter()
+        .zip(edges)
+        .filter(|&(edge, target)| target != (EdgeCubelet(edge), CyclicGroup::identity()))
+        .collect::<Vec<_>>();
+    let moved_corners = CornerType::enumerate()
+        .into_iter()
+        .zip(corners)
+        .filter(|&(corner, target)| target != (CornerCubelet(corner), CyclicGroup::identity()))
+        .collect::<Vec<_>>();
+    // Three moved pieces that all change position must form a single cycle.
+    match (moved_edges.len(), moved_corners.len()) {
+        (3, 0) => moved_edges
+            .iter()
+            .all(|&(edge, (target, _))| target != EdgeCubelet(edge)),
+        (0, 3) => moved_corners
+            .iter()
+            .all(|&(corner, (target, _))| target != CornerCubelet(corner)),
+        _ => false,
+    }
+}
+
+/// The face and slice turns that commutators are built from.
+fn generators() -> Vec<Move> {
+    ["F", "R", "U", "B", "L", "D", "M", "E", "S"]
+        .into_iter()
+        .flat_map(|face| {
+            ["", "2", "'"]
+                .into_iter()
+                .map(move |suffix| format!("{}{}", face, suffix).parse::<Move>().unwrap())
+        })
+        .collect()
+}
+
+/// Every sequence of up to `length` moves, not counting sequences that turn the same axis twice in a row.
+fn sequences(length: usize) -> Vec<MoveSequence> {
+    let generators = generators();
+    let mut all = vec![MoveSequence { moves: Vec::new() }];
+    let mut previous = all.clone();
+    for _ in 0..length {
+        previous = previous
+            .iter()
+            .flat_map(|seq| {
+                generators
+                    .iter()
+                    .filter(
+                        move |mv| !matches!(seq.moves.last(), Some(last) if last.axis == mv.axis),
+                    )
+                    .map(move |&mv| seq.clone() + MoveSequence { moves: vec![mv] })
+            })
+            .collect();
+        all.extend(previous.iter().cloned());
+    }
+    all
+}
+
+lazy_static::lazy_static! {
+    /// The shortest commutator `[A, B]` that performs each pure 3-cycle,
+    /// where `A` is an insertion of up to three moves and `B` is a single interchange move.
+    static ref COMMUTATORS: HashMap<CycleSignature, MoveSequence> = {
+        let interchanges = generators()
+            .into_iter()
+            .map(|mv| (mv, CubePermutation3::from_move(mv)))
+            .collect::<Vec<_>>();
+
+        let mut commutators = HashMap::<CycleSignature, MoveSequence>::new();
+        for insertion in sequences(3).into_iter().skip(1) {
+            let a = CubePermutation3::from_move_sequence(insertion.clone())
+                .expect("generators turn layers of a 3x3x3 cube");
+            for &(interchange, b) in &interchanges {
+                // `a` is performed first, then `b`, then their inverses.
+                let commutator = b.inverse().op(a.inverse()).op(b).op(a);
+                if !is_pure_three_cycle(commutator) {
+                    continue;
+                }
+                let interchange = MoveSequence { moves: vec![interchange] };
+                let seq = insertion.clone()
+                    + interchange.clone()
+                    + insertion.inverse()
+                    + interchange.inverse();
+                let shortest = commutators
+                    .entry(cycle_signature(commutator))
+                    .or_insert_with(|| seq.clone());
+                if seq.len() < shortest.len() {
+                    *shortest = seq;
+                }
+            }
+        }
+        commutators
+    };
+
+    /// Setup moves that bring the pieces of a 3-cycle to where a stored commutator can cycle them,
+    /// grouped by the number of moves.
+    static ref SETUPS: Vec<Vec<(MoveSequence, CubePermutation3)>> = {
+        let mut setups = vec![Vec::new(); 4];
+        for seq in sequences(3) {
+            let permutation = CubePermutation3::from_move_sequence(seq.clone())
+                .expect("generators turn layers of a 3x3x3 cube");
+            setups[seq.len()].push((seq, permutation));
+        }
+        setups
+    };
+}
+
+/// Finds a short commutator, possibly conjugated by up to three setup moves, that solves the given 3-cycle.
+/// Fewer setup moves are preferred.
+/// The returned sequence `seq` satisfies `CubePermutation3::from_move_sequence(seq) == permutation.inverse()`.
+///
+/// Returns `None` if the permutation is not a pure 3-cycle of edges or of corners.
+pub fn find_commutator(permutation: &CubePermutation3) -> Option<MoveSequence> {
+    if !is_pure_three_cycle(*permutation) {
+        return None;
+    }
+    let target = permutation.inverse();
+    SETUPS.iter().find_map(|setups| {
+        setups
+            .iter()
+            .filter_map(|(setup, setup_permutation)| {
+                // Performing the setup, then the commutator, then undoing the setup gives the target.
+                let commutator = setup_permutation.op(target).op(setup_permutation.inverse());
+                let commutator = COMMUTATORS.get(&cycle_signature(commutator))?;
+                Some(setup.clone() + commutator.clone() + setup.inverse())
+            })
+            .min_by_key(|seq| seq.len())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::permute::{CornerPermutation, EdgePermutation};
+
+    use super::*;
+
+    fn permutation(alg: &str) -> CubePermutation3 {
+        CubePermutation3::from_move_sequence(alg.parse().unwrap()).unwrap()
+    }
+
+    fn assert_solves(cycle: CubePermutation3) {
+        let commutator = find_commutator(&cycle).unwrap();
+        assert_eq!(
+            CubePermutation3::from_move_sequence(commutator.clone())
+                .unwrap()
+                .op(cycle),
+            CubePermutation3::identity(),
+            "{}",
+            commutator
+        );
+    }
+
+    #[test]
+    fn three_cycles() {
+        // The edges of the U permutation and the corners of the A permutation are 3-cycles.
+        assert_solves(permutation("M2 U M U2 M' U M2"));
+        assert_solves(permutation("x R' U R' D2 R U' R' D2 R2 x'"));
+        // The pieces may be reoriented as they are cycled.
+        assert_solves(permutation("R U R' D R U' R' D'"));
+        assert_solves(permutation("R' F R S' R' F' R S"));
+
+        assert_eq!(find_commutator(&CubePermutation3::identity()), None);
+        for alg in ["R", "R U R' U'", "R U R' U R U2 R'", "M2 U2 M2 U2", "x"] {
+            assert_eq!(find_commutator(&permutation(alg)), None, "{}", alg);
+        }
+    }
+
+    #[test]
+    fn every_three_cycle() {
+        // Cycle the pieces at `a`, `b`, and `c` in each direction, without reorienting them.
+        let mut cycles = Vec::new();
+        for a in 0..12 {
+            for b in a + 1..12 {
+                for c in a + 1..12 {
+                    if b == c {
+                        continue;
+                    }
+                    let mut edges =
+                        EdgeCubelet::enumerate().map(|edge| (edge, CyclicGroup::identity()));
+                    edges[a].0 = EdgeCubelet::enumerate()[b];
+                    edges[b].0 = EdgeCubelet::enumerate()[c];
+                    edges[c].0 = EdgeCubelet::enumerate()[a];
+                    cycles.push(CubePermutation3::new_unchecked(
+                        CentrePermutation::identity(),
+                        EdgePermutation::new_unchecked(edges),
+                        CornerPermutation::identity(),
+                    ));
+                }
+            }
+        }
+        for a in 0..8 {
+            for b in a + 1..8 {
+                for c in a + 1..8 {
+                    if b == c {
+                        continue;
+                    }
+                    let mut corners =
+                        CornerCubelet::enumerate().map(|corner| (corner, CyclicGroup::identity()));
+                    corners[a].0 = CornerCubelet::enumerate()[b];
+                    corners[b].0 = CornerCubelet::enumerate()[c];
+                    corners[c].0 = CornerCubelet::enumerate()[a];
+                    cycles.push(CubePermutation3::new_unchecked(
+                        CentrePermutation::identity(),
+                        EdgePermutation::identity(),
+                        CornerPermutation::new_unchecked(corners),
+                    ));
+                }
+            }
+        }
+        assert_eq!(cycles.len(), 12 * 11 * 10 / 3 + 8 * 7 * 6 / 3);
+
+        for cycle in cycles {
+            assert!(is_pure_three_cycle(cycle));
+            assert_solves(cycle);
+        }
+    }
+}
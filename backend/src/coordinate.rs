@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use crate::{
+    cube::{
+        FaceType::{B, F},
+        Move,
+    },
+    group::{CyclicGroup, Enumerable, GroupAction, Magma, Unital},
+    permute::{CornerCubelet, CubePermutation3, EdgeCubelet},
+};
+
+/// A coordinate summarises part of a cube state as a number in `0..size`.
+/// The effect of a move on a coordinate depends only on the value of the coordinate,
+/// so searches can update coordinates with this lookup table instead of recomputing them.
+pub(crate) struct CoordinateTable {
+    /// The value of the coordinate on a solved cube.
+    pub solved: usize,
+    /// `transitions[c][i]` is the value of the coordinate after performing the `i`th move on a cube with coordinate `c`.
+    pub transitions: Vec<Vec<usize>>,
+    /// The minimum number of moves required to bring each coordinate to its solved value.
+    /// This is a lower bound on the number of moves required to solve the whole cube.
+    pub distance: Vec<u8>,
+}
+
+impl CoordinateTable {
+    /// Fills in the table by a breadth first search from the solved state, using only the given moves.
+    /// Each coordinate value is reached through some cube state, and that state is used to compute its transitions.
+    /// Every value in `0..size` must be reachable.
+    pub fn new(
+        size: usize,
+        moves: &[CubePermutation3],
+        coordinate: fn(&CubePermutation3) -> usize,
+    ) -> Self {
+        let mut transitions = vec![Vec::new(); size];
+        let mut distance = vec![u8::MAX; size];
+
+        let solved = CubePermutation3::identity();
+        distance[coordinate(&solved)] = 0;
+        let mut queue = VecDeque::from([solved]);
+        while let Some(permutation) = queue.pop_front() {
+            let c = coordinate(&permutation);
+            transitions[c] = moves
+                .iter()
+                .map(|mv| {
+                    let next = mv.op(permutation);
+                    let d = coordinate(&next);
+                    if distance[d] == u8::MAX {
+                        distance[d] = distance[c] + 1;
+                        queue.push_back(next);
+                    }
+                    d
+                })
+                .collect();
+        }
+
+        debug_assert!(distance.iter().all(|&d| d != u8::MAX));
+        Self {
+            solved: coordinate(&solved),
+            transitions,
+            distance,
+        }
+    }
+}
+
+/// The minimum number of moves required to bring a pair of coordinates to their solved values at the same time.
+/// The pair `(a, b)` is stored at index `a * b_size + b`, where `b_size` is the number of values of `b`.
+pub(crate) fn pair_distances(a: &CoordinateTable, b: &CoordinateTable) -> Vec<u8> {
+    let b_size = b.transitions.len();
+    let mut distance = vec![u8::MAX; a.transitions.len() * b_size];
+
+    distance[a.solved * b_size + b.solved] = 0;
+    let mut queue = VecDeque::from([(a.solved, b.solved)]);
+    while let Some((x, y)) = queue.pop_front() {
+        let current = distance[x * b_size + y];
+        for (&x2, &y2) in a.transitions[x].iter().zip(&b.transitions[y]) {
+            let next = &mut distance[x2 * b_size + y2];
+            if *next == u8::MAX {
+                *next = current + 1;
+                queue.push_back((x2, y2));
+            }
+        }
+    }
+    distance
+}
+
+/// Returns true if `mv` should not be searched immediately after `previous`.
+/// Turning the same face twice in a row is never optimal,
+/// and turns of opposite faces commute, so searches only need to try them in one order.
+pub(crate) fn is_redundant_after(previous: Move, mv: Move) -> bool {
+    previous.axis == mv.axis && previous.start_depth >= mv.start_depth
+}
+
+/// The orientation of the corner in each position, as a number in base 3.
+/// The orientation of the last corner is determined by the others, so it is omitted.
+pub(crate) fn corner_orientation(permutation: &CubePermutation3) -> usize {
+    let mut orientations = [0; CornerCubelet::N];
+    for corner in CornerCubelet::enumerate() {
+        let (position, orientation) = permutation
+            .corners()
+            .act(&(corner, CyclicGroup::identity()));
+        orientations[position.index()] = orientation.get_value() as usize;
+    }
+    orientations[..CornerCubelet::N - 1]
+        .iter()
+        .rev()
+        .fold(0, |coordinate, &orientation| coordinate * 3 + orientation)
+}
+
+/// The orientation of the edge in each position, as a number in base 2.
+/// The orientation of the last edge is determined by the others, so it is omitted.
+pub(crate) fn edge_orientation(permutation: &CubePermutation3) -> usize {
+    let mut orientations = [0; EdgeCubelet::N];
+    for edge in EdgeCubelet::enumerate() {
+        let (position, orientation) = permutation.edges().act(&(edge, CyclicGroup::identity()));
+        orientations[position.index()] = orientation.get_value() as usize;
+    }
+    orientations[..EdgeCubelet::N - 1]
+        .iter()
+        .rev()
+        .fold(0, |coordinate, &orientation| coordinate * 2 + orientation)
+}
+
+/// Returns true if the edge belongs in the slice between U and D.
+fn is_ud_slice_edge(edge: EdgeCubelet) -> bool {
+    matches!(edge.0.faces()[0], F | B)
+}
+
+fn edge_position(permutation: &CubePermutation3, edge: EdgeCubelet) -> usize {
+    permutation
+        .edges()
+        .act(&(edge, CyclicGroup::identity()))
+        .0
+        .index()
+}
+
+/// Which four positions contain the edges that belong in the slice between U and D,
+/// as a number in `0..495` given by the combinatorial number system.
+pub(crate) fn ud_slice(permutation: &CubePermutation3) -> usize {
+    let mut positions = EdgeCubelet::enumerate()
+        .into_iter()
+        .filter(|&edge| is_ud_slice_edge(edge))
+        .map(|edge| edge_position(permutation, edge))
+        .collect::<Vec<_>>();
+    positions.sort_unstable();
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(k, position)| binomial(position, k + 1))
+        .sum()
+}
+
+/// The positions of the corners, as a number in `0..40320`.
+pub(crate) fn corner_permutation(permutation: &CubePermutation3) -> usize {
+    let positions = CornerCubelet::enumerate().map(|corner| {
+        permutation
+            .corners()
+            .act(&(corner, CyclicGroup::identity()))
+            .0
+            .index()
+    });
+    permutation_index(&positions)
+}
+
+/// The positions of the eight edges that belong in the U and D faces, as a number in `0..40320`.
+/// This is only meaningful if those edges are all in the U and D faces.
+pub(crate) fn ud_edge_permutation(permutation: &CubePermutation3) -> usize {
+    let positions = EdgeCubelet::enumerate()
+        .into_iter()
+        .filter(|&edge| !is_ud_slice_edge(edge))
+        .map(|edge| edge_position(permutation, edge))
+        .collect::<Vec<_>>();
+    permutation_index(&positions)
+}
+
+/// The positions of the four edges that belong in the slice between U and D, as a number in `0..24`.
+/// This is only meaningful if those edges are all in that slice.
+pub(crate) fn ud_slice_permutation(permutation: &CubePermutation3) -> usize {
+    let positions = EdgeCubelet::enumerate()
+        .into_iter()
+        .filter(|&edge| is_ud_slice_edge(edge))
+        .map(|edge| edge_position(permutation, edge))
+        .collect::<Vec<_>>();
+    permutation_index(&positions)
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        0
+    } else {
+        (0..k).fold(1, |result, i| result * (n - i) / (i + 1))
+    }
+}
+
+/// Ranks the ordering of the given distinct values among all orderings of the same values, using the Lehmer code.
+fn permutation_index(values: &[usize]) -> usize {
+    values.iter().enumerate().fold(0, |index, (i, &value)| {
+        let smaller_later = values[i + 1..].iter().filter(|&&v| v < value).count();
+        index * (values.len() - i) + smaller_later
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coordinates() {
+        let solved = CubePermutation3::identity();
+        assert_eq!(corner_orientation(&solved), 0);
+        assert_eq!(edge_orientation(&solved), 0);
+        assert_eq!(corner_permutation(&solved), 0);
+        assert_eq!(ud_edge_permutation(&solved), 0);
+        assert_eq!(ud_slice_permutation(&solved), 0);
+
+        assert_eq!(permutation_index(&[0, 1, 2]), 0);
+        assert_eq!(permutation_index(&[2, 1, 0]), 5);
+        assert_eq!(permutation_index(&[7, 3, 9]), 2);
+        let mut indices = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ]
+        .map(|values| permutation_index(&values));
+        indices.sort_unstable();
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5]);
+    }
+}
@@ -1,17 +1,32 @@
-use std::{collections::HashMap, fmt::Display, ops::Index, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    ops::{Add, Index, Mul},
+    str::FromStr,
+};
 use wasm_bindgen::{prelude::*, JsCast};
 
 /// Represents a *valid* (i.e. has all of the required pieces, not necessarily solvable) NxN cube.
 /// Not `Copy` primarily as a lint.
 #[derive(Debug, Clone)]
 pub struct Cube<const N: usize> {
-    /// Faces of the cube, ordered F R U B L D.
-    faces: [Face<N>; 6],
+    /// Stickers are stored in the default colour scheme, and only recoloured when displayed.
+    faces: FaceMap<Face<N>>,
+    scheme: ColourScheme,
 }
 
+/// Cubes are equal if their stickers are, even if they are displayed with different colour schemes.
+impl<const N: usize> PartialEq for Cube<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.faces == other.faces
+    }
+}
+
+impl<const N: usize> Eq for Cube<N> {}
+
 /// A face of an NxN cube.
 /// Not `Copy` primarily as a lint.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Face<const N: usize> {
     rows: [[Colour; N]; N],
 }
@@ -42,11 +57,127 @@ impl Colour {
             Colour::Yellow => 'y',
         }
     }
+
+    /// Gets a hex code for this colour, matching the stickers of the 3D cube in the web UI.
+    pub fn hex(self) -> &'static str {
+        match self {
+            Colour::Green => "#22cc55",
+            Colour::Red => "#dd4444",
+            Colour::White => "#dddddd",
+            Colour::Blue => "#2244dd",
+            Colour::Orange => "#ee8822",
+            Colour::Yellow => "#dddd22",
+        }
+    }
+
+    /// Gets the ANSI escape code parameters that set the terminal background to this colour.
+    /// Orange has no standard code, so it uses the 256-colour palette.
+    pub fn ansi_background(self) -> &'static str {
+        match self {
+            Colour::Green => "42",
+            Colour::Red => "41",
+            Colour::White => "47",
+            Colour::Blue => "44",
+            Colour::Orange => "48;5;208",
+            Colour::Yellow => "43",
+        }
+    }
+
+    /// Parses a colour from its letter name, as given by `letter`.
+    /// Upper case face letters are also accepted, and yield the colour of that face on a solved cube.
+    /// Letters are case sensitive, so `b` is always blue and `B` is always the back face,
+    /// even though the two happen to coincide in the default colour scheme.
+    pub fn from_letter(c: char) -> Option<Colour> {
+        match c {
+            'g' => Some(Colour::Green),
+            'r' => Some(Colour::Red),
+            'w' => Some(Colour::White),
+            'b' => Some(Colour::Blue),
+            'o' => Some(Colour::Orange),
+            'y' => Some(Colour::Yellow),
+            _ => FaceType::from_str(&c.to_string()).ok().map(Colour::from),
+        }
+    }
+}
+
+/// The reason that a colour could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColourParseError {
+    /// The string was not a single character.
+    NotOneLetter(String),
+    /// The character is not the letter of a colour or of a face.
+    UnknownLetter(char),
+}
+
+impl Display for ColourParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColourParseError::NotOneLetter(s) => {
+                write!(f, "expected a single letter, but found \"{}\"", s)
+            }
+            ColourParseError::UnknownLetter(c) => write!(f, "'{}' is not a colour or face", c),
+        }
+    }
+}
+
+impl std::error::Error for ColourParseError {}
+
+impl FromStr for Colour {
+    type Err = ColourParseError;
+
+    /// Parses a single letter using `Colour::from_letter`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Colour::from_letter(c).ok_or(ColourParseError::UnknownLetter(c)),
+            _ => Err(ColourParseError::NotOneLetter(s.to_string())),
+        }
+    }
+}
+
+/// An assignment of a colour to the centre of each face of a cube.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ColourScheme {
+    /// The colour of each face, ordered F R U B L D.
+    colours: [Colour; 6],
+}
+
+impl ColourScheme {
+    /// Creates a colour scheme from the colours of each face, ordered F R U B L D.
+    /// Returns None if a colour is used on more than one face.
+    pub fn new(colours: [Colour; 6]) -> Option<Self> {
+        for i in 0..colours.len() {
+            if colours[..i].contains(&colours[i]) {
+                return None;
+            }
+        }
+        Some(Self { colours })
+    }
+
+    /// The colour of the given face.
+    pub fn colour(&self, face: FaceType) -> Colour {
+        self.colours[face as usize]
+    }
+
+    /// Converts a colour in the default scheme into the corresponding colour in this scheme.
+    pub fn recolour(&self, colour: Colour) -> Colour {
+        self.colour(colour.into())
+    }
+}
+
+/// The default scheme has white on top and green in front.
+impl Default for ColourScheme {
+    fn default() -> Self {
+        Self {
+            colours: FaceType::enumerate().map(Colour::from),
+        }
+    }
 }
 
 /// A face on a cube.
 /// Represented in Singmaster notation.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum FaceType {
     F,
@@ -103,10 +234,89 @@ impl Enumerable for FaceType {
     }
 }
 
+impl FaceType {
+    /// The face on the other side of the cube.
+    pub fn opposite(self) -> FaceType {
+        match self {
+            F => B,
+            R => L,
+            U => D,
+            B => F,
+            L => R,
+            D => U,
+        }
+    }
+
+    /// The axis that passes through the centre of this face.
+    pub fn axis(self) -> Axis {
+        match self {
+            F | B => Axis::FB,
+            R | L => Axis::RL,
+            U | D => Axis::UD,
+        }
+    }
+
+    /// The four faces that share an edge with this face,
+    /// in clockwise order as viewed from outside the cube looking at this face.
+    /// The U and D faces start with F, and the other faces start with U.
+    pub fn adjacent(self) -> [FaceType; 4] {
+        match self {
+            F => [U, R, D, L],
+            R => [U, B, D, F],
+            U => [F, L, B, R],
+            B => [U, L, D, R],
+            L => [U, F, D, B],
+            D => [F, R, B, L],
+        }
+    }
+}
+
+/// Faces are numbered in the order of [FaceType::enumerate].
+impl From<FaceType> for usize {
+    fn from(face: FaceType) -> usize {
+        face.index()
+    }
+}
+
+/// A value for each face of the cube, indexed by [FaceType].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FaceMap<T>([T; 6]);
+
+impl<T> FaceMap<T> {
+    /// Creates a map from the value for each face, given in the order of [FaceType::enumerate]: F R U B L D.
+    pub fn new(values: [T; 6]) -> Self {
+        Self(values)
+    }
+
+    /// Creates a map by calling `f` on each face.
+    pub fn from_fn(f: impl FnMut(FaceType) -> T) -> Self {
+        Self(FaceType::enumerate().map(f))
+    }
+
+    pub fn get(&self, face: FaceType) -> &T {
+        &self.0[usize::from(face)]
+    }
+
+    pub fn get_mut(&mut self, face: FaceType) -> &mut T {
+        &mut self.0[usize::from(face)]
+    }
+
+    /// Applies `f` to the value for each face.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> FaceMap<U> {
+        FaceMap(self.0.map(f))
+    }
+
+    /// Each face together with its value, in the order of [FaceType::enumerate].
+    pub fn iter(&self) -> impl Iterator<Item = (FaceType, &T)> {
+        FaceType::enumerate().into_iter().zip(&self.0)
+    }
+}
+
 /// One of twelve edge types on a cube.
 /// Edge names are derived from 2-axis (RL, UD) edge orientation.
 /// The "key sticker" is written first.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[rustfmt::skip]
 pub enum EdgeType {
@@ -194,6 +404,24 @@ impl EdgeType {
         }
     }
 
+    /// The two faces this edge lies on, with the face of the key sticker first.
+    pub fn faces(self) -> [FaceType; 2] {
+        match self {
+            UR => [U, R],
+            UF => [U, F],
+            UL => [U, L],
+            UB => [U, B],
+            DR => [D, R],
+            DF => [D, F],
+            DL => [D, L],
+            DB => [D, B],
+            FR => [F, R],
+            FL => [F, L],
+            BR => [B, R],
+            BL => [B, L],
+        }
+    }
+
     /// Yields the edge formed from the intersection of the two faces, along with
     /// the parity of the given edge. The parity is reversed if the input faces are reversed.
     pub fn from_faces(f1: FaceType, f2: FaceType) -> Option<(EdgeType, CyclicGroup<2>)> {
@@ -208,6 +436,7 @@ impl EdgeType {
 /// One of twelve corner types on a cube.
 /// Corner types are named according to the member of each axis: FB, UD, RL.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 #[rustfmt::skip]
 pub enum CornerType {
@@ -280,6 +509,55 @@ impl CornerType {
             _ => None,
         }
     }
+
+    /// The three faces this corner lies on, starting with the U or D face,
+    /// and then proceeding clockwise around the corner as viewed from outside the cube.
+    pub fn faces_clockwise(self) -> [FaceType; 3] {
+        match self {
+            FUR => [U, R, F],
+            FUL => [U, F, L],
+            FDR => [D, F, R],
+            FDL => [D, L, F],
+            BUR => [U, B, R],
+            BUL => [U, L, B],
+            BDR => [D, R, B],
+            BDL => [D, B, L],
+        }
+    }
+
+    /// The three faces this corner lies on, in the order they appear in its name:
+    /// the face on the FB axis, then the UD axis, then the RL axis.
+    pub fn faces(self) -> [FaceType; 3] {
+        match self {
+            FUR => [F, U, R],
+            FUL => [F, U, L],
+            FDR => [F, D, R],
+            FDL => [F, D, L],
+            BUR => [B, U, R],
+            BUL => [B, U, L],
+            BDR => [B, D, R],
+            BDL => [B, D, L],
+        }
+    }
+
+    /// Yields the corner formed from the intersection of the three faces, along with its orientation.
+    /// The faces `f1`, `f2` and `f3` must be given clockwise around the corner as viewed from outside the cube,
+    /// or None will be returned.
+    /// The orientation is the number of clockwise turns taken by the U or D face,
+    /// relative to the order in which the faces were given.
+    pub fn from_faces(
+        f1: FaceType,
+        f2: FaceType,
+        f3: FaceType,
+    ) -> Option<(CornerType, CyclicGroup<3>)> {
+        let faces = [f1, f2, f3];
+        let orientation = faces.iter().position(|&face| face == U || face == D)?;
+        let faces = [0, 1, 2].map(|i| faces[(i + orientation) % 3]);
+        CornerType::enumerate()
+            .into_iter()
+            .find(|corner| corner.faces_clockwise() == faces)
+            .map(|corner| (corner, CyclicGroup::new(orientation as u8)))
+    }
 }
 
 /// An axis on a cube.
@@ -306,6 +584,17 @@ impl FromStr for Axis {
     }
 }
 
+impl Axis {
+    /// The face turned by a move on this axis with depth zero.
+    pub fn face(self) -> FaceType {
+        match self {
+            FB => F,
+            RL => R,
+            UD => U,
+        }
+    }
+}
+
 /// These impls are safe since colour and face type are `repr(u8)` and have the same possible discriminants.
 impl From<FaceType> for Colour {
     fn from(face: FaceType) -> Self {
@@ -365,6 +654,41 @@ impl Display for RotationType {
     }
 }
 
+/// A rotation type that also records the direction of a double turn.
+/// `U2` and `U2'` have the same effect on the cube, so `RotationType` does not distinguish them,
+/// but reconstructions and animations may want to turn in the direction that was written.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SignedRotationType {
+    Normal,
+    DoubleClockwise,
+    DoubleAnticlockwise,
+    Inverse,
+}
+
+impl SignedRotationType {
+    /// The number of clockwise quarter turns, which is negative for anticlockwise turns.
+    pub fn rotations(self) -> i32 {
+        match self {
+            SignedRotationType::Normal => 1,
+            SignedRotationType::DoubleClockwise => 2,
+            SignedRotationType::DoubleAnticlockwise => -2,
+            SignedRotationType::Inverse => -1,
+        }
+    }
+}
+
+impl From<SignedRotationType> for RotationType {
+    fn from(rotation_type: SignedRotationType) -> Self {
+        match rotation_type {
+            SignedRotationType::Normal => RotationType::Normal,
+            SignedRotationType::DoubleClockwise | SignedRotationType::DoubleAnticlockwise => {
+                RotationType::Double
+            }
+            SignedRotationType::Inverse => RotationType::Inverse,
+        }
+    }
+}
+
 /// Gives the inverse of a RotationType.
 #[wasm_bindgen(js_name = inverse)]
 #[doc(hidden)]
@@ -388,45 +712,198 @@ pub struct Move {
     pub start_depth: usize,
     #[wasm_bindgen(js_name = endDepth)]
     pub end_depth: usize,
+    // Whether a double turn was written with a prime, as in `U2'`, so turns anticlockwise
+    // as seen from the face it was written with. This never changes the permutation of the cube,
+    // and is always false for quarter turns. Use `Move::key` to compare moves by their effect.
+    #[wasm_bindgen(js_name = doubleAnticlockwise)]
+    pub double_anticlockwise: bool,
+}
+
+/// The reason that a move could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveParseError {
+    /// There were no characters to parse, for example between two adjacent spaces in a move sequence.
+    EmptyToken,
+    /// The first character did not name a face or a slice.
+    UnknownFace(char),
+    /// A character after the face was not a valid modifier.
+    InvalidModifier(char),
+    /// The move was followed by extra characters, for example a repeated or out-of-order modifier.
+    /// Modifiers must be written in the order `w`, `2`, `'`.
+    TrailingGarbage(String),
+    /// The move refers to layers that do not exist on a cube of the given size,
+    /// or a layer count was given for a move that does not accept one.
+    LayerOutOfRange { layers: usize, size: usize },
+    /// A bracket or separator appeared where it was not expected,
+    /// for example a closing bracket with no matching opening bracket.
+    UnexpectedSymbol(char),
+    /// The sequence ended before a bracket was closed.
+    UnclosedBracket,
+    /// The cube has no layers that the move could turn,
+    /// for example a slice move on a 2x2x2 cube, which has no inner slices.
+    CubeTooSmall { size: usize },
+    /// The sequence would expand to more than the given number of moves,
+    /// for example because a group is repeated too many times.
+    TooManyMoves { limit: usize },
+    /// Brackets were nested inside each other more than the given number of times.
+    TooDeeplyNested { limit: usize },
+}
+
+impl Display for MoveParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveParseError::EmptyToken => write!(f, "expected a move, but found nothing"),
+            MoveParseError::UnknownFace(c) => write!(f, "'{}' is not a face or slice", c),
+            MoveParseError::InvalidModifier(c) => write!(f, "'{}' is not a valid modifier", c),
+            MoveParseError::TrailingGarbage(rest) => {
+                write!(f, "unexpected \"{}\" at the end of the move", rest)
+            }
+            MoveParseError::LayerOutOfRange { layers, size } => write!(
+                f,
+                "cannot turn {} layers on a {}x{}x{} cube",
+                layers, size, size, size
+            ),
+            MoveParseError::UnexpectedSymbol(c) => write!(f, "unexpected '{}'", c),
+            MoveParseError::UnclosedBracket => write!(f, "a bracket was never closed"),
+            MoveParseError::CubeTooSmall { size } => write!(
+                f,
+                "a {}x{}x{} cube has no layers that this move could turn",
+                size, size, size
+            ),
+            MoveParseError::TooManyMoves { limit } => {
+                write!(f, "the sequence has more than {} moves", limit)
+            }
+            MoveParseError::TooDeeplyNested { limit } => {
+                write!(f, "brackets are nested more than {} deep", limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MoveParseError {}
+
+/// The reason that a move sequence could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveSequenceParseError {
+    /// The index of the move that could not be parsed, counting from zero.
+    /// If the error was in the brackets around the moves, this is the number of moves before the error.
+    pub index: usize,
+    pub error: MoveParseError,
+}
+
+impl Display for MoveSequenceParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not parse move {}: {}", self.index + 1, self.error)
+    }
 }
 
+impl std::error::Error for MoveSequenceParseError {}
+
 impl FromStr for Move {
-    type Err = ();
+    type Err = MoveParseError;
 
+    /// Parses a move on a 3x3x3 cube. See [Move::from_str_sized].
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        const N: usize = 3;
-        let mut chars = s.chars();
-        let face_char = chars.next().ok_or(())?;
+        Self::from_str_sized(s, 3)
+    }
+}
+
+impl Move {
+    /// Parses a move on an NxNxN cube, where `n` is the number of layers.
+    ///
+    /// Face turns may be prefixed with a layer count:
+    /// - `3R` turns only the third layer from the R face,
+    /// - `3Rw` (or `3r`) turns the outer three layers from the R face.
+    ///
+    /// Without a prefix, `Rw` and `r` turn the outer two layers.
+    /// Slice moves `M`, `E`, `S` turn every inner slice, and rotations `x`, `y`, `z` turn the whole cube.
+    pub fn from_str_sized(s: &str, n: usize) -> Result<Self, MoveParseError> {
+        let prefix_len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let layers = if prefix_len == 0 {
+            None
+        } else {
+            // If the prefix is too large to even fit in a `usize`, it's certainly too large for the cube.
+            Some(s[..prefix_len].parse::<usize>().unwrap_or(usize::MAX))
+        };
+        let s = &s[prefix_len..];
+
+        let face_char = s.chars().next().ok_or(MoveParseError::EmptyToken)?;
         let turn_direction = match face_char {
             'M' => 'L',
             'E' => 'D',
             'S' => 'F',
+            'x' => 'R',
+            'y' => 'U',
+            'z' => 'F',
             x => x,
         };
-        let face: FaceType = turn_direction.to_uppercase().collect::<String>().parse()?;
-        let mut end_depth = if face_char.is_lowercase() { 2 } else { 1 };
-        let mut start_depth = match face_char {
-            'M' | 'E' | 'S' => {
-                end_depth = 2;
-                1
-            }
-            _ => 0,
-        };
+        let face: FaceType = turn_direction
+            .to_uppercase()
+            .collect::<String>()
+            .parse()
+            .map_err(|_| MoveParseError::UnknownFace(face_char))?;
+        let is_slice_or_rotation = matches!(face_char, 'M' | 'E' | 'S' | 'x' | 'y' | 'z');
+
         let mut rotation_type = RotationType::Normal;
-        for modification in chars {
-            match modification {
-                'w' => end_depth = 2,
-                '2' => rotation_type = RotationType::Double,
-                '\'' => {
-                    // Sometimes, algorithms have things like U2', but we don't care
-                    // about the direction of double turns.
-                    if rotation_type != RotationType::Double {
-                        rotation_type = RotationType::Inverse
-                    }
-                }
-                _ => return Err(()),
+        let mut double_anticlockwise = false;
+        let mut wide = face_char.is_lowercase() && !is_slice_or_rotation;
+
+        // Modifiers are read in the order `w`, `2`, `'`.
+        let mut modifiers = &s[face_char.len_utf8()..];
+        if let Some(rest) = modifiers.strip_prefix('w') {
+            // Slices and rotations already have a fixed width.
+            if is_slice_or_rotation {
+                return Err(MoveParseError::InvalidModifier('w'));
+            }
+            wide = true;
+            modifiers = rest;
+        }
+        if let Some(rest) = modifiers.strip_prefix('2') {
+            rotation_type = RotationType::Double;
+            modifiers = rest;
+        }
+        if let Some(rest) = modifiers.strip_prefix('\'') {
+            // Sometimes, algorithms have things like U2'. This has the same effect as U2,
+            // but the direction is kept in case the move is animated.
+            if rotation_type == RotationType::Double {
+                double_anticlockwise = true;
+            } else {
+                rotation_type = RotationType::Inverse
+            }
+            modifiers = rest;
+        }
+        if let Some(c) = modifiers.chars().next() {
+            return Err(match c {
+                'w' | '2' | '\'' => MoveParseError::TrailingGarbage(modifiers.to_string()),
+                c => MoveParseError::InvalidModifier(c),
+            });
+        }
+
+        let (mut start_depth, mut end_depth) = match (face_char, layers) {
+            // A slice move needs a slice between the two outer faces, and any other move needs at least one layer.
+            ('M' | 'E' | 'S', None) if n < 3 => {
+                return Err(MoveParseError::CubeTooSmall { size: n })
+            }
+            _ if n == 0 => return Err(MoveParseError::CubeTooSmall { size: n }),
+            // Slice moves turn everything between the two outer faces.
+            ('M' | 'E' | 'S', None) => (1, n - 1),
+            // Cube rotations turn every slice.
+            ('x' | 'y' | 'z', None) => (0, n),
+            (_, None) if wide => (0, 2),
+            (_, None) => (0, 1),
+            (_, Some(layers)) if layers == 0 || layers > n || is_slice_or_rotation => {
+                return Err(MoveParseError::LayerOutOfRange { layers, size: n })
             }
+            (_, Some(layers)) if wide => (0, layers),
+            (_, Some(layers)) => (layers - 1, layers),
+        };
+        if end_depth > n {
+            return Err(MoveParseError::LayerOutOfRange {
+                layers: end_depth,
+                size: n,
+            });
         }
+
         let axis = match face {
             F => FB,
             R => RL,
@@ -434,22 +911,22 @@ impl FromStr for Move {
             B => {
                 rotation_type = rotation_type.inverse();
                 let d = start_depth;
-                start_depth = N - end_depth;
-                end_depth = N - d;
+                start_depth = n - end_depth;
+                end_depth = n - d;
                 FB
             }
             L => {
                 rotation_type = rotation_type.inverse();
                 let d = start_depth;
-                start_depth = N - end_depth;
-                end_depth = N - d;
+                start_depth = n - end_depth;
+                end_depth = n - d;
                 RL
             }
             D => {
                 rotation_type = rotation_type.inverse();
                 let d = start_depth;
-                start_depth = N - end_depth;
-                end_depth = N - d;
+                start_depth = n - end_depth;
+                end_depth = n - d;
                 UD
             }
         };
@@ -458,8 +935,34 @@ impl FromStr for Move {
             rotation_type,
             start_depth,
             end_depth,
+            double_anticlockwise,
         })
     }
+
+    /// Reflects this move on an NxNxN cube across the plane between the R and L faces.
+    /// The R and L sides are swapped, and every move turns in the opposite direction as seen from its own face,
+    /// so `R` becomes `L'`, `U` becomes `U'`, `U2` becomes `U2'`, and `M` and `x` are unchanged.
+    /// Returns `None` if the move turns layers that do not exist on an NxNxN cube.
+    pub fn mirror_lr(self, n: usize) -> Option<Self> {
+        let double_anticlockwise =
+            self.rotation_type == RotationType::Double && !self.double_anticlockwise;
+        match self.axis {
+            // About the RL axis itself, the reflection preserves the direction of the turn,
+            // but the layers are counted from the other side.
+            RL => Some(Self {
+                start_depth: n.checked_sub(self.end_depth)?,
+                end_depth: n.checked_sub(self.start_depth)?,
+                double_anticlockwise,
+                ..self
+            }),
+            FB | UD if self.end_depth <= n => Some(Self {
+                rotation_type: self.rotation_type.inverse(),
+                double_anticlockwise,
+                ..self
+            }),
+            FB | UD => None,
+        }
+    }
 }
 
 impl Display for Move {
@@ -480,6 +983,21 @@ impl Display for Move {
                 RL => write!(f, "L{}", self.rotation_type.inverse()),
                 UD => write!(f, "D{}", self.rotation_type.inverse()),
             },
+            (0, 2) => match self.axis {
+                FB => write!(f, "Fw{}", self.rotation_type),
+                RL => write!(f, "Rw{}", self.rotation_type),
+                UD => write!(f, "Uw{}", self.rotation_type),
+            },
+            (1, 3) => match self.axis {
+                FB => write!(f, "Bw{}", self.rotation_type.inverse()),
+                RL => write!(f, "Lw{}", self.rotation_type.inverse()),
+                UD => write!(f, "Dw{}", self.rotation_type.inverse()),
+            },
+            (0, 3) => match self.axis {
+                FB => write!(f, "z{}", self.rotation_type),
+                RL => write!(f, "x{}", self.rotation_type),
+                UD => write!(f, "y{}", self.rotation_type),
+            },
             _ => {
                 // Fallback if we don't know how else to display the move:
                 write!(
@@ -488,7 +1006,11 @@ impl Display for Move {
                     self.axis, self.start_depth, self.end_depth, self.rotation_type
                 )
             }
+        }?;
+        if self.double_anticlockwise {
+            write!(f, "'")?;
         }
+        Ok(())
     }
 }
 
@@ -505,9 +1027,12 @@ impl Move {
             rotation_type,
             start_depth,
             end_depth,
+            double_anticlockwise: false,
         }
     }
 
+    /// Double turns are their own inverses, and keep the direction they were written with.
+    /// See [Move::inverse_signed] to reverse their direction too.
     pub fn inverse(self) -> Self {
         Self {
             rotation_type: self.rotation_type.inverse(),
@@ -520,6 +1045,134 @@ impl Move {
     }
 }
 
+impl Move {
+    /// The inverse of this move, where double turns are inverted to double turns in the opposite direction,
+    /// so `U2` becomes `U2'`.
+    pub fn inverse_signed(self) -> Self {
+        Self {
+            double_anticlockwise: self.rotation_type == RotationType::Double
+                && !self.double_anticlockwise,
+            ..self.inverse()
+        }
+    }
+
+    /// The fields of the move that determine its effect on the cube, ignoring the direction of a double turn.
+    /// Two moves have the same effect exactly when their keys are equal.
+    pub fn key(self) -> (Axis, RotationType, usize, usize) {
+        (
+            self.axis,
+            self.rotation_type,
+            self.start_depth,
+            self.end_depth,
+        )
+    }
+
+    /// The rotation type of this move, distinguishing between clockwise and anticlockwise double turns.
+    pub fn signed_rotation_type(self) -> SignedRotationType {
+        match self.rotation_type {
+            RotationType::Normal => SignedRotationType::Normal,
+            RotationType::Double if self.double_anticlockwise => {
+                SignedRotationType::DoubleAnticlockwise
+            }
+            RotationType::Double => SignedRotationType::DoubleClockwise,
+            RotationType::Inverse => SignedRotationType::Inverse,
+        }
+    }
+
+    /// Whether this move turns the whole of a 3x3x3 cube.
+    pub fn is_rotation(self) -> bool {
+        self.start_depth == 0 && self.end_depth == 3
+    }
+
+    /// Splits a move that turns several layers into one move per layer, outermost first.
+    /// For instance, `Rw` becomes `R M'`. Single-layer moves are returned unchanged.
+    pub fn expand_wide(self) -> MoveSequence {
+        MoveSequence {
+            moves: (self.start_depth..self.end_depth)
+                .map(|depth| Move {
+                    start_depth: depth,
+                    end_depth: depth + 1,
+                    ..self
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Tracks the orientation of a 3x3x3 cube through a sequence of rotations.
+/// The entry for each face is the face of the original cube that is now in its position.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Orientation([FaceType; 6]);
+
+impl Orientation {
+    fn new() -> Self {
+        Self([F, R, U, B, L, D])
+    }
+
+    fn rotate(&mut self, rotation: Move) {
+        let adjacent = rotation.axis.face().adjacent();
+        for _ in 0..rotation.rotation_type.rotations().rem_euclid(4) {
+            let previous = self.0;
+            for i in 0..4 {
+                self.0[adjacent[(i + 1) % 4] as usize] = previous[adjacent[i] as usize];
+            }
+        }
+    }
+
+    /// Converts a move on the rotated cube into the move that turns the same layers of the original cube.
+    /// A double turn keeps its direction as seen from the same physical face.
+    fn relabel(&self, mv: Move) -> Move {
+        let face = self.0[mv.axis.face() as usize];
+        if face == face.axis().face() {
+            Move {
+                axis: face.axis(),
+                ..mv
+            }
+        } else {
+            // Slice moves and rotations are written with a fixed face of their axis, which is now on the other side
+            // of the cube, so the direction of a double turn is reversed. Other moves are written with the face
+            // they turn, which stays in place.
+            let written_with_opposite_face = mv.start_depth + mv.end_depth == 3;
+            Move {
+                axis: face.axis(),
+                rotation_type: mv.rotation_type.inverse(),
+                start_depth: 3 - mv.end_depth,
+                end_depth: 3 - mv.start_depth,
+                double_anticlockwise: mv.rotation_type == RotationType::Double
+                    && mv.double_anticlockwise != written_with_opposite_face,
+            }
+        }
+    }
+
+    /// At most two rotations that bring a solved cube into this orientation.
+    fn rotations(&self) -> Vec<Move> {
+        let single = [FB, RL, UD].iter().flat_map(|&axis| {
+            [
+                RotationType::Normal,
+                RotationType::Double,
+                RotationType::Inverse,
+            ]
+            .iter()
+            .map(move |&rotation_type| Move::new(axis, rotation_type, 0, 3))
+        });
+        std::iter::once(Vec::new())
+            .chain(single.clone().map(|mv| vec![mv]))
+            .chain(
+                single
+                    .clone()
+                    .flat_map(|first| single.clone().map(move |second| vec![first, second])),
+            )
+            .find(|rotations| {
+                let mut orientation = Orientation::new();
+                for &rotation in rotations {
+                    orientation.rotate(rotation);
+                }
+                orientation == *self
+            })
+            .expect("every orientation is at most two rotations away")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MoveSequence {
     pub moves: Vec<Move>,
@@ -536,6 +1189,11 @@ impl Magma for MoveSequence {
 
 impl Semigroup for MoveSequence {}
 
+/// The inverse of a move sequence performs the inverse of each move in reverse order.
+/// Double turns stay as double turns.
+///
+/// This is compatible with cube permutations: `CubePermutation3::from_move_sequence(seq.inverse())`
+/// is the inverse of `CubePermutation3::from_move_sequence(seq)`.
 impl InverseSemigroup for MoveSequence {
     fn inverse(&self) -> Self {
         Self {
@@ -545,11 +1203,145 @@ impl InverseSemigroup for MoveSequence {
 }
 
 impl MoveSequence {
+    /// The inverse of this sequence, where each double turn is reversed in direction as in [Move::inverse_signed].
+    pub fn inverse_signed(&self) -> Self {
+        Self {
+            moves: self
+                .moves
+                .iter()
+                .rev()
+                .map(|mv| mv.inverse_signed())
+                .collect(),
+        }
+    }
+}
+
+/// Performs the first sequence, then the second.
+/// Note that this is the reverse of [Magma::op], which performs its right operand first.
+impl Add for MoveSequence {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            moves: self.moves.into_iter().chain(other.moves).collect(),
+        }
+    }
+}
+
+/// Performs the sequence the given number of times. See [MoveSequence::repeat].
+impl Mul<usize> for MoveSequence {
+    type Output = Self;
+
+    fn mul(self, n: usize) -> Self {
+        self.repeat(n)
+    }
+}
+
+impl IntoIterator for MoveSequence {
+    type Item = Move;
+    type IntoIter = std::vec::IntoIter<Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveSequence {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.moves.iter()
+    }
+}
+
+impl FromIterator<Move> for MoveSequence {
+    fn from_iter<I: IntoIterator<Item = Move>>(iter: I) -> Self {
+        Self {
+            moves: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Index<usize> for MoveSequence {
+    type Output = Move;
+
+    fn index(&self, index: usize) -> &Move {
+        &self.moves[index]
+    }
+}
+
+impl MoveSequence {
+    /// The number of moves in this sequence. Each wide move or rotation counts as one move.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Whether this sequence has no moves.
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Performs this sequence `n` times in a row. Repeating a sequence zero times gives the empty sequence.
+    pub fn repeat(&self, n: usize) -> Self {
+        Self {
+            moves: self.moves.repeat(n),
+        }
+    }
+
+    /// Writes out each move in Singmaster notation, separated by the given string.
+    /// The [Display] implementation uses a single space as the separator.
+    pub fn to_string_with_sep(&self, sep: &str) -> String {
+        self.moves
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<_>>()
+            .join(sep)
+    }
+
+    /// Reflects each move on an NxNxN cube with [Move::mirror_lr].
+    /// Returns `None` if any move turns layers that do not exist on an NxNxN cube.
+    pub fn mirror_lr(&self, n: usize) -> Option<Self> {
+        Some(Self {
+            moves: self
+                .moves
+                .iter()
+                .map(|mv| mv.mirror_lr(n))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// The permutation of a 3x3x3 cube that this sequence performs,
+    /// or `None` if some move turns layers that a 3x3x3 cube does not have.
+    /// This is shorthand for [CubePermutation3::from_move_sequence].
+    pub fn permutation(&self) -> Option<CubePermutation3> {
+        CubePermutation3::from_move_sequence(self.clone())
+    }
+
+    /// Whether this sequence has the same effect as `other` on a 3x3x3 cube,
+    /// even if they are written differently. For instance, `R R` is equivalent to `R2`.
+    /// A sequence that turns layers a 3x3x3 cube does not have is not equivalent to anything.
+    pub fn is_equivalent(&self, other: &MoveSequence) -> bool {
+        match (self.permutation(), other.permutation()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Merges consecutive moves on the same axis into as few moves as possible.
+    ///
+    /// Moves on the same axis commute, so every run of consecutive moves on one axis is collapsed
+    /// into the net number of turns applied to each slice. For instance, `R R` becomes `R2`,
+    /// `U U U` becomes `U'`, and `R R'` disappears completely. In particular, opposite faces
+    /// are merged through each other: `R L R` becomes `R2 L`.
+    /// Adjacent slices turning by the same amount are combined into a single wide move,
+    /// and moves on different axes are never reordered.
     pub fn canonicalise(self) -> Self {
         if self.moves.is_empty() {
             return self;
         }
 
+        let original_len = self.moves.len();
         let mut moves = Vec::new();
 
         // If two consecutive moves have the same axis, try to collapse them into the same real move.
@@ -558,12 +1350,16 @@ impl MoveSequence {
 
         let mut process_axis = |current_axis: Axis, current_axis_moves: Vec<Move>| {
             // Canonicalise the list of current axis moves, since they all must commute.
-            let mut turns_by_slice = HashMap::<usize, i32>::new();
+            // The slices must be visited in order to find continuous blocks of slices below.
+            let mut turns_by_slice = BTreeMap::<usize, i32>::new();
             for mv in current_axis_moves {
                 for slice in mv.start_depth..mv.end_depth {
                     *turns_by_slice.entry(slice).or_default() += mv.rotation_type.rotations();
                 }
             }
+            if turns_by_slice.is_empty() {
+                return;
+            }
             // Convert this back into a move sequence.
             // For each continuous block of slices rotating the same amount, convert it into a move.
             let mut new_moves = Vec::new();
@@ -580,12 +1376,12 @@ impl MoveSequence {
                 } else {
                     // Finish this wide move.
                     if let Some(rotation_type) = RotationType::from_rotations(expected_rotations) {
-                        new_moves.push(Move {
-                            axis: current_axis,
+                        new_moves.push(Move::new(
+                            current_axis,
                             rotation_type,
-                            start_depth: current_start_slice,
-                            end_depth: expected_slice,
-                        });
+                            current_start_slice,
+                            expected_slice,
+                        ));
                     }
 
                     // Set up the current start slice, and expected next slice and rotations.
@@ -596,12 +1392,12 @@ impl MoveSequence {
             }
 
             if let Some(rotation_type) = RotationType::from_rotations(expected_rotations) {
-                new_moves.push(Move {
-                    axis: current_axis,
+                new_moves.push(Move::new(
+                    current_axis,
                     rotation_type,
-                    start_depth: current_start_slice,
-                    end_depth: expected_slice,
-                });
+                    current_start_slice,
+                    expected_slice,
+                ));
             }
 
             moves.extend(new_moves);
@@ -617,8 +1413,71 @@ impl MoveSequence {
         process_axis(current_axis, std::mem::take(&mut current_axis_moves));
 
         moves.extend(current_axis_moves);
-        Self { moves }
-    }
+        let result = Self { moves };
+
+        // Cancelling out an entire axis may bring more moves on the same axis together,
+        // such as in `R U U' R'`, so repeat until nothing more can be merged.
+        if result.moves.len() < original_len {
+            result.canonicalise()
+        } else {
+            result
+        }
+    }
+
+    /// Moves every cube rotation in a 3x3x3 move sequence to the end.
+    ///
+    /// Each move after a rotation is relabelled to turn the same layer of the unrotated cube,
+    /// so `y R` becomes `B y`. The net rotation is written as at most two rotations at the end,
+    /// so the result performs exactly the same transformation as the original sequence.
+    pub fn absorb_rotations(&self) -> Self {
+        let mut orientation = Orientation::new();
+        let mut moves = Vec::new();
+        for &mv in &self.moves {
+            if mv.is_rotation() {
+                orientation.rotate(mv);
+            } else {
+                moves.push(orientation.relabel(mv));
+            }
+        }
+        moves.extend(orientation.rotations());
+        Self { moves }
+    }
+
+    /// Merges consecutive moves that turn adjacent layers on the same axis in the same direction,
+    /// so `R M'` becomes `Rw`. This undoes [Move::expand_wide].
+    /// Unlike [MoveSequence::canonicalise], moves are only ever joined together, never cancelled.
+    pub fn contract_to_wide(&self) -> Self {
+        let mut moves = Vec::<Move>::new();
+        for &mv in &self.moves {
+            match moves.last_mut() {
+                Some(last)
+                    if last.axis == mv.axis
+                        && last.rotation_type == mv.rotation_type
+                        && (last.end_depth == mv.start_depth
+                            || mv.end_depth == last.start_depth) =>
+                {
+                    last.start_depth = last.start_depth.min(mv.start_depth);
+                    last.end_depth = last.end_depth.max(mv.end_depth);
+                }
+                _ => moves.push(mv),
+            }
+        }
+        Self { moves }
+    }
+
+    /// Like [MoveSequence::absorb_rotations], but drops the trailing rotations.
+    /// The result moves every piece to the same place relative to the centres,
+    /// but may leave the cube in a different orientation.
+    pub fn without_rotations(&self) -> Self {
+        Self {
+            moves: self
+                .absorb_rotations()
+                .moves
+                .into_iter()
+                .filter(|mv| !mv.is_rotation())
+                .collect(),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -638,45 +1497,352 @@ impl From<MoveSequence> for MoveSequenceConv {
 }
 
 impl FromStr for MoveSequence {
-    type Err = ();
-
+    type Err = MoveSequenceParseError;
+
+    /// Parses moves separated by any amount of whitespace, including line breaks.
+    /// Everything from `//` or `#` to the end of a line is a comment, and is ignored.
+    ///
+    /// Commutators `[A, B]` expand to `A B A' B'`, and conjugates `[A: B]` expand to `A B A'`.
+    /// These may be nested and surrounded by other moves, as in `F [R: [U, M']] F'`.
+    /// Groups `(A)n` repeat `A` a total of `n` times, and `(A)'` performs the inverse of `A`.
+    /// As with moves, a count may be followed by a prime, as in `(R U)2'`.
+    ///
+    /// Any amount of whitespace may be written next to a bracket or separator.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut result = Self { moves: Vec::new() };
-        for value in s.split(' ') {
-            result.moves.push(value.parse()?);
+        let tokens = tokenise(s)?;
+        let mut position = 0;
+        let result = parse_tokens(&tokens, &mut position, 0)?;
+        match tokens.get(position) {
+            None => Ok(result),
+            Some(&(index, Token::Symbol(c))) => Err(MoveSequenceParseError {
+                index,
+                error: MoveParseError::UnexpectedSymbol(c),
+            }),
+            Some(_) => unreachable!("parse_tokens stops only at symbols"),
         }
-        Ok(result)
     }
 }
 
-impl Display for MoveSequence {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (i, mv) in self.moves.iter().enumerate() {
-            if i != 0 {
-                write!(f, " ")?;
+/// The characters with special meaning in a move sequence, other than moves and whitespace.
+const SYMBOLS: [char; 6] = ['[', ']', ',', ':', '(', ')'];
+
+/// The most moves that a parsed move sequence may contain after expanding groups and commutators,
+/// so that a short input such as `((R U)999)999` cannot take up all of the available memory.
+const MAX_SEQUENCE_LENGTH: usize = 10_000;
+
+/// The most brackets that may be nested inside each other,
+/// so that a long run of opening brackets cannot overflow the stack while parsing.
+const MAX_NESTING_DEPTH: usize = 64;
+
+enum Token {
+    Move(Move),
+    Symbol(char),
+    /// The modifier after a closing parenthesis.
+    Repeat {
+        count: usize,
+        inverse: bool,
+    },
+}
+
+/// Removes the comment, if any, from the end of each line.
+fn strip_comments(s: &str) -> String {
+    s.lines()
+        .map(|line| {
+            let end = [line.find("//"), line.find('#')]
+                .into_iter()
+                .flatten()
+                .min()
+                .unwrap_or(line.len());
+            &line[..end]
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a move sequence into moves and symbols, ignoring comments.
+/// Each token is paired with the number of moves before it.
+fn tokenise(s: &str) -> Result<Vec<(usize, Token)>, MoveSequenceParseError> {
+    // First, split the input into runs of whitespace, symbols, and the text of each move.
+    let s = strip_comments(s);
+    let mut pieces = Vec::new();
+    let mut rest = s.as_str();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_whitespace() {
+            rest.find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len())
+        } else if SYMBOLS.contains(&c) {
+            c.len_utf8()
+        } else {
+            rest.find(|c: char| c.is_whitespace() || SYMBOLS.contains(&c))
+                .unwrap_or(rest.len())
+        };
+        pieces.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+
+    let is_symbol = |piece: &str| piece.starts_with(|c| SYMBOLS.contains(&c));
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    for (i, piece) in pieces.iter().enumerate() {
+        if piece.starts_with(char::is_whitespace) {
+            continue;
+        } else if is_symbol(piece) {
+            tokens.push((index, Token::Symbol(piece.chars().next().unwrap())));
+        } else if i > 0 && pieces[i - 1] == ")" {
+            // A group may be followed by a repeat count, then a prime to invert it.
+            let (count, inverse) = match piece.strip_suffix('\'') {
+                Some(count) => (count, true),
+                None => (*piece, false),
+            };
+            let count = if count.is_empty() {
+                1
+            } else if count.bytes().all(|b| b.is_ascii_digit()) {
+                // If the count is too large to even fit in a `usize`, it's certainly too large.
+                count.parse().unwrap_or(usize::MAX)
+            } else {
+                return Err(MoveSequenceParseError {
+                    index,
+                    error: MoveParseError::InvalidModifier(piece.chars().next().unwrap()),
+                });
+            };
+            if count > MAX_SEQUENCE_LENGTH {
+                return Err(MoveSequenceParseError {
+                    index,
+                    error: MoveParseError::TooManyMoves {
+                        limit: MAX_SEQUENCE_LENGTH,
+                    },
+                });
             }
-            write!(f, "{}", mv)?;
+            tokens.push((index, Token::Repeat { count, inverse }));
+        } else {
+            let mv = piece
+                .parse()
+                .map_err(|error| MoveSequenceParseError { index, error })?;
+            tokens.push((index, Token::Move(mv)));
+            index += 1;
         }
+    }
+
+    if tokens.is_empty() {
+        Err(MoveSequenceParseError {
+            index: 0,
+            error: MoveParseError::EmptyToken,
+        })
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Reads moves and bracketed expressions, stopping at the first symbol that does not open a bracket.
+/// `depth` is the number of brackets that enclose these tokens.
+fn parse_tokens(
+    tokens: &[(usize, Token)],
+    position: &mut usize,
+    depth: usize,
+) -> Result<MoveSequence, MoveSequenceParseError> {
+    let mut moves = Vec::new();
+    while let Some(&(index, ref token)) = tokens.get(*position) {
+        let too_many_moves = MoveSequenceParseError {
+            index,
+            error: MoveParseError::TooManyMoves {
+                limit: MAX_SEQUENCE_LENGTH,
+            },
+        };
+        if matches!(token, Token::Symbol('[' | '(')) && depth >= MAX_NESTING_DEPTH {
+            return Err(MoveSequenceParseError {
+                index,
+                error: MoveParseError::TooDeeplyNested {
+                    limit: MAX_NESTING_DEPTH,
+                },
+            });
+        }
+        match token {
+            Token::Move(mv) => {
+                moves.push(*mv);
+                *position += 1;
+            }
+            Token::Symbol('[') => {
+                *position += 1;
+                let a = parse_tokens(tokens, position, depth + 1)?;
+                let separator = expect_symbol(tokens, position, &[',', ':'])?;
+                let b = parse_tokens(tokens, position, depth + 1)?;
+                expect_symbol(tokens, position, &[']'])?;
+
+                moves.extend(a.moves.iter().copied());
+                moves.extend(b.moves.iter().copied());
+                moves.extend(a.inverse().moves);
+                if separator == ',' {
+                    moves.extend(b.inverse().moves);
+                }
+            }
+            Token::Symbol('(') => {
+                *position += 1;
+                let mut group = parse_tokens(tokens, position, depth + 1)?;
+                expect_symbol(tokens, position, &[')'])?;
+
+                let mut count = 1;
+                if let Some(&(_, Token::Repeat { count: n, inverse })) = tokens.get(*position) {
+                    *position += 1;
+                    count = n;
+                    if inverse {
+                        group = group.inverse();
+                    }
+                }
+                // Both the group and the count are at most the maximum length, so this cannot overflow.
+                if moves.len() + group.moves.len() * count > MAX_SEQUENCE_LENGTH {
+                    return Err(too_many_moves);
+                }
+                for _ in 0..count {
+                    moves.extend(group.moves.iter().copied());
+                }
+            }
+            Token::Symbol(_) => break,
+            Token::Repeat { .. } => unreachable!("repeats are read with their group"),
+        }
+        if moves.len() > MAX_SEQUENCE_LENGTH {
+            return Err(too_many_moves);
+        }
+    }
+    Ok(MoveSequence { moves })
+}
+
+/// Consumes the next token, which must be one of the given symbols.
+fn expect_symbol(
+    tokens: &[(usize, Token)],
+    position: &mut usize,
+    expected: &[char],
+) -> Result<char, MoveSequenceParseError> {
+    match tokens.get(*position) {
+        Some(&(_, Token::Symbol(c))) if expected.contains(&c) => {
+            *position += 1;
+            Ok(c)
+        }
+        Some(&(index, Token::Symbol(c))) => Err(MoveSequenceParseError {
+            index,
+            error: MoveParseError::UnexpectedSymbol(c),
+        }),
+        Some(_) => unreachable!("parse_tokens stops only at symbols"),
+        None => Err(MoveSequenceParseError {
+            index: tokens.last().map_or(0, |(index, token)| match token {
+                Token::Move(_) => index + 1,
+                _ => *index,
+            }),
+            error: MoveParseError::UnclosedBracket,
+        }),
+    }
+}
+
+impl Display for MoveSequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_with_sep(" "))
+    }
+}
+
+/// Moves are serialized in Singmaster notation, as a string such as `"Rw2'"`.
+/// Only moves on a 3x3x3 cube can be written this way, so serializing a move that turns deeper layers is an error.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Move {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        check_serializable(self)?;
+        serializer.collect_str(self)
+    }
+}
+
+/// Returns an error if the move does not turn a range of layers of a 3x3x3 cube,
+/// since it would not be read back as the same move.
+#[cfg(feature = "serde")]
+fn check_serializable<E: serde::ser::Error>(mv: &Move) -> Result<(), E> {
+    if mv.start_depth < mv.end_depth && mv.end_depth <= 3 {
         Ok(())
+    } else {
+        Err(E::custom(format!(
+            "the move {} is not a move on a 3x3x3 cube, so it cannot be serialized",
+            mv
+        )))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Move {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Move sequences are serialized as a single string of moves separated by spaces, such as `"R U R' U'"`.
+/// The empty sequence is serialized as the empty string.
+/// As with [Move], every move must be a move on a 3x3x3 cube.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MoveSequence {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        for mv in &self.moves {
+            check_serializable(mv)?;
+        }
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MoveSequence {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if s.is_empty() {
+            Ok(Self { moves: Vec::new() })
+        } else {
+            s.parse().map_err(serde::de::Error::custom)
+        }
     }
 }
 
 impl<const N: usize> Cube<N> {
     pub fn new() -> Self {
         Self {
-            faces: [
-                Face::new(F),
-                Face::new(R),
-                Face::new(U),
-                Face::new(B),
-                Face::new(L),
-                Face::new(D),
-            ],
+            faces: FaceMap::from_fn(Face::new),
+            scheme: ColourScheme::default(),
+        }
+    }
+
+    /// Creates a cube from the colour of each sticker, given by its face, row and column.
+    /// The stickers are not checked to form a valid arrangement of pieces.
+    pub(crate) fn from_stickers(sticker: impl Fn(FaceType, usize, usize) -> Colour) -> Self {
+        let mut cube = Self::new();
+        for face in FaceType::enumerate() {
+            for row in 0..N {
+                for col in 0..N {
+                    cube.faces.get_mut(face).rows[row][col] = sticker(face, row, col);
+                }
+            }
+        }
+        cube
+    }
+
+    /// Creates a solved cube that will be displayed using the given colour scheme.
+    pub fn with_scheme(scheme: ColourScheme) -> Self {
+        Self {
+            scheme,
+            ..Self::new()
         }
     }
 
+    pub fn scheme(&self) -> ColourScheme {
+        self.scheme
+    }
+
+    /// The colours of the given face are those of the default colour scheme.
+    /// Use `ColourScheme::recolour` to convert them into the colours of this cube's scheme.
     pub fn face(&self, ty: FaceType) -> &Face<N> {
-        &self.faces[ty as usize]
+        self.faces.get(ty)
+    }
+
+    /// Performs the given move, or returns an error if the move turns slices that do not exist on this cube.
+    pub fn try_perform(self, mv: Move) -> Result<Self, Error> {
+        if mv.end_depth > N {
+            Err(Error::DepthOutOfRange { mv, size: N })
+        } else {
+            Ok(self.perform(mv))
+        }
     }
 
     pub fn perform(self, mv: Move) -> Self {
@@ -749,13 +1915,15 @@ impl<const N: usize> Cube<N> {
         }
 
         Self {
-            faces: match mv {
+            // The new faces are listed in the order F R U B L D.
+            faces: FaceMap::new(match mv {
                 // FB turns
                 Move {
                     axis: FB,
                     rotation_type: RotationType::Normal,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     // Read this:
                     // "F is clockwise, but only if the front face is modified"
@@ -764,8 +1932,10 @@ impl<const N: usize> Cube<N> {
                     // (the left part of R's face is copied from the bottom part of U's face)
                     (R Left U Bottom)
                     (U Bottom L Right)
-                    // "B is clockwise, but only if the back face is modified" (back face signalled by the `b` character)
-                    (B b cw)
+                    // "B is anticlockwise, but only if the back face is modified" (back face signalled by the `b` character)
+                    // The back face turns in the same direction as the front face,
+                    // which is anticlockwise when looking at the back face itself.
+                    (B b ccw)
                     (L Right D Top)
                     (D Top R Left)
                 ),
@@ -774,6 +1944,7 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Double,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F 2)
                     (R Left L Right)
@@ -787,11 +1958,12 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Inverse,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F ccw)
                     (R Left D Top)
                     (U Bottom R Left)
-                    (B b ccw)
+                    (B b cw)
                     (L Right U Bottom)
                     (D Top L Right)
                 ),
@@ -801,12 +1973,13 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Normal,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Right D Right)
                     (R cw)
                     (U Right F Right)
                     (B Left U Right)
-                    (L b cw)
+                    (L b ccw)
                     (D Right B Left)
                 ),
                 Move {
@@ -814,6 +1987,7 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Double,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Right B Left)
                     (R 2)
@@ -827,12 +2001,13 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Inverse,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Right U Right)
                     (R ccw)
                     (U Right B Left)
                     (B Left D Right)
-                    (L b ccw)
+                    (L b cw)
                     (D Right F Right)
                 ),
                 // UD turns
@@ -841,19 +2016,21 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Normal,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Top R Top)
                     (R Top B Top)
                     (U cw)
                     (B Top L Top)
                     (L Top F Top)
-                    (D b cw)
+                    (D b ccw)
                 ),
                 Move {
                     axis: UD,
                     rotation_type: RotationType::Double,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Top B Top)
                     (R Top L Top)
@@ -867,21 +2044,125 @@ impl<const N: usize> Cube<N> {
                     rotation_type: RotationType::Inverse,
                     start_depth,
                     end_depth,
+                    ..
                 } => perform!(start_depth, end_depth,
                     (F Top L Top)
                     (R Top F Top)
                     (U ccw)
                     (B Top R Top)
                     (L Top B Top)
-                    (D b ccw)
+                    (D b cw)
                 ),
-            },
+            }),
+            scheme: self.scheme,
         }
     }
-}
 
-impl<const N: usize> Display for Cube<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Performs each move in the sequence in reading order, so the first move in the sequence is performed first.
+    /// This is the order in which a scramble is applied to a physical cube.
+    pub fn perform_sequence(self, seq: &MoveSequence) -> Self {
+        seq.moves.iter().fold(self, |cube, &mv| cube.perform(mv))
+    }
+
+    /// Performs each move in the sequence in turn, as in [Cube::perform_sequence],
+    /// yielding the state of the cube after each move.
+    /// Moves are only performed as the iterator is advanced.
+    pub fn play(self, seq: &MoveSequence) -> impl Iterator<Item = Self> + '_ {
+        seq.moves.iter().scan(self, |cube, &mv| {
+            *cube = cube.clone().perform(mv);
+            Some(cube.clone())
+        })
+    }
+
+    /// Returns true if every face of the cube is a single colour.
+    /// A solved cube that has been rotated as a whole is still solved.
+    pub fn is_solved(&self) -> bool {
+        self.faces.iter().all(|(_, face)| {
+            face.rows
+                .iter()
+                .flatten()
+                .all(|&colour| colour == face.rows[0][0])
+        })
+    }
+
+    /// Renders the cube as an SVG image of its net, laid out as in the `Display` impl.
+    /// Each sticker is drawn as a separate `rect`, coloured using the cube's colour scheme.
+    pub fn to_svg(&self) -> String {
+        use std::fmt::Write;
+
+        const STICKER_SIZE: usize = 20;
+        const BORDER_WIDTH: usize = 1;
+        let face_size = STICKER_SIZE * N;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = 4 * face_size,
+            h = 3 * face_size
+        )
+        .unwrap();
+        // The position of the top left corner of each face in the net, measured in faces.
+        for (face, face_x, face_y) in [
+            (U, 1, 0),
+            (L, 0, 1),
+            (F, 1, 1),
+            (R, 2, 1),
+            (B, 3, 1),
+            (D, 1, 2),
+        ] {
+            for i in 0..N {
+                for j in 0..N {
+                    writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{size}" height="{size}" fill="{}" stroke="black" stroke-width="{}"/>"#,
+                        face_x * face_size + j * STICKER_SIZE,
+                        face_y * face_size + i * STICKER_SIZE,
+                        self.scheme.recolour(self.face(face)[(i, j)]).hex(),
+                        BORDER_WIDTH,
+                        size = STICKER_SIZE,
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Renders the net of the cube as in the `Display` impl,
+    /// but with each sticker given a background colour using ANSI escape codes.
+    pub fn to_ansi(&self) -> String {
+        self.to_terminal_string(true)
+    }
+
+    /// Renders the net of the cube for a terminal.
+    /// If `colour` is false, for instance when the output is not a terminal,
+    /// no escape codes are emitted and the output is the same as the `Display` impl.
+    pub fn to_terminal_string(&self, colour: bool) -> String {
+        let mut result = String::new();
+        if colour {
+            self.write_net(&mut result, |colour| {
+                format!(
+                    "\x1b[30;{}m{} \x1b[0m",
+                    colour.ansi_background(),
+                    colour.letter()
+                )
+            })
+        } else {
+            self.write_net(&mut result, |colour| format!("{} ", colour.letter()))
+        }
+        .unwrap();
+        result
+    }
+
+    /// Writes the net of the cube, with the U face above the L, F, R, B faces, and the D face below.
+    /// Each sticker must be written with the width of two characters.
+    fn write_net(
+        &self,
+        f: &mut impl std::fmt::Write,
+        sticker: impl Fn(Colour) -> String,
+    ) -> std::fmt::Result {
         // Write the U face.
         for i in 0..N {
             // Write each row.
@@ -891,7 +2172,7 @@ impl<const N: usize> Display for Cube<N> {
             }
             // Display the row.
             for j in 0..N {
-                write!(f, "{} ", self.face(U)[(i, j)].letter())?;
+                write!(f, "{}", sticker(self.scheme.recolour(self.face(U)[(i, j)])))?;
             }
             writeln!(f)?;
         }
@@ -900,7 +2181,11 @@ impl<const N: usize> Display for Cube<N> {
         for i in 0..N {
             for face in [L, F, R, B] {
                 for j in 0..N {
-                    write!(f, "{} ", self.face(face)[(i, j)].letter())?;
+                    write!(
+                        f,
+                        "{}",
+                        sticker(self.scheme.recolour(self.face(face)[(i, j)]))
+                    )?;
                 }
             }
             writeln!(f)?;
@@ -915,7 +2200,7 @@ impl<const N: usize> Display for Cube<N> {
             }
             // Display the row.
             for j in 0..N {
-                write!(f, "{} ", self.face(D)[(i, j)].letter())?;
+                write!(f, "{}", sticker(self.scheme.recolour(self.face(D)[(i, j)])))?;
             }
             writeln!(f)?;
         }
@@ -924,6 +2209,270 @@ impl<const N: usize> Display for Cube<N> {
     }
 }
 
+/// The reason that a facelet string could not be read as a cube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FaceletError {
+    /// The string did not contain exactly 54 facelets.
+    WrongLength(usize),
+    /// A facelet was not one of the letters `U R F D L B`.
+    UnknownColour(char),
+    /// A colour did not appear on exactly nine facelets.
+    WrongColourCount { colour: FaceType, count: usize },
+    /// More than one face had a centre of the given colour.
+    DuplicateCentre(FaceType),
+}
+
+impl Display for FaceletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaceletError::WrongLength(len) => write!(f, "expected 54 facelets, but found {}", len),
+            FaceletError::UnknownColour(c) => write!(f, "'{}' is not a facelet colour", c),
+            FaceletError::WrongColourCount { colour, count } => write!(
+                f,
+                "expected 9 facelets of colour {}, but found {}",
+                colour, count
+            ),
+            FaceletError::DuplicateCentre(colour) => {
+                write!(f, "more than one centre has colour {}", colour)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FaceletError {}
+
+impl Cube<3> {
+    /// The order in which faces are written in a facelet string.
+    const FACELET_ORDER: [FaceType; 6] = [U, R, F, D, L, B];
+
+    /// Reads a cube from a facelet string, as used by Kociemba's solver and many other tools.
+    ///
+    /// The string contains the nine stickers of each face in the order U R F D L B.
+    /// Each face is read row by row, oriented as in the `Display` impl for `Cube`.
+    /// Each sticker is written as the letter of the face whose centre has that colour on a solved cube.
+    /// The solved cube is `UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB`.
+    ///
+    /// This only checks that the stickers have the right colours in the right quantities,
+    /// not that they form a valid arrangement of pieces.
+    pub fn from_facelets(s: &str) -> Result<Self, FaceletError> {
+        let facelets = s
+            .chars()
+            .map(|c| {
+                c.to_string()
+                    .parse::<FaceType>()
+                    .map_err(|_| FaceletError::UnknownColour(c))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if facelets.len() != 54 {
+            return Err(FaceletError::WrongLength(facelets.len()));
+        }
+
+        for colour in Self::FACELET_ORDER {
+            let count = facelets
+                .iter()
+                .filter(|&&facelet| facelet == colour)
+                .count();
+            if count != 9 {
+                return Err(FaceletError::WrongColourCount { colour, count });
+            }
+        }
+
+        let mut cube = Self::new();
+        let mut centres = Vec::new();
+        for (face, stickers) in Self::FACELET_ORDER.into_iter().zip(facelets.chunks(9)) {
+            for (i, &sticker) in stickers.iter().enumerate() {
+                cube.faces.get_mut(face).rows[i / 3][i % 3] = sticker.into();
+            }
+            if centres.contains(&stickers[4]) {
+                return Err(FaceletError::DuplicateCentre(stickers[4]));
+            }
+            centres.push(stickers[4]);
+        }
+        Ok(cube)
+    }
+
+    /// Writes this cube as a facelet string.
+    /// See `from_facelets` for the format.
+    pub fn to_facelets(&self) -> String {
+        self.facelet_faces().map(|face| face.to_string()).collect()
+    }
+
+    /// The face whose colour each sticker has, in the order of a facelet string.
+    pub fn facelet_faces(&self) -> impl Iterator<Item = FaceType> + '_ {
+        Self::FACELET_ORDER
+            .into_iter()
+            .flat_map(|face| self.face(face).rows.into_iter().flatten())
+            .map(FaceType::from)
+    }
+
+    /// The row and column of the sticker on the given face that belongs to the piece lying on all of the given faces.
+    /// Faces not adjacent to `face`, including `face` itself, are ignored.
+    fn sticker_position(face: FaceType, piece: &[FaceType]) -> (usize, usize) {
+        let (mut row, mut col) = (1, 1);
+        for &other in piece {
+            match face.segment_towards(other) {
+                Some(Top) => row = 0,
+                Some(Right) => col = 2,
+                Some(Bottom) => row = 2,
+                Some(Left) => col = 0,
+                None => {}
+            }
+        }
+        (row, col)
+    }
+
+    fn sticker(&self, face: FaceType, piece: &[FaceType]) -> FaceType {
+        let (row, col) = Self::sticker_position(face, piece);
+        self.face(face)[(row, col)].into()
+    }
+
+    fn set_sticker(&mut self, face: FaceType, piece: &[FaceType], colour: FaceType) {
+        let (row, col) = Self::sticker_position(face, piece);
+        self.faces.get_mut(face).rows[row][col] = colour.into();
+    }
+
+    /// Renders the given permutation onto the stickers of a cube.
+    /// Use `CubePermutation3::to_cube` instead.
+    pub(crate) fn from_permutation(permutation: CubePermutation3) -> Self {
+        let mut cube = Self::new();
+
+        for centre in CentreCubelet::enumerate() {
+            let target = permutation.centres().act(&centre);
+            cube.set_sticker(target.0, &[target.0], centre.0);
+        }
+
+        for edge in EdgeCubelet::enumerate() {
+            let (target, orientation) = permutation.edges().act(&(edge, CyclicGroup::identity()));
+            let faces = edge.0.faces();
+            let target_faces = target.0.faces();
+            for i in 0..2 {
+                let face = target_faces[(i + orientation.get_value() as usize) % 2];
+                cube.set_sticker(face, &target_faces, faces[i]);
+            }
+        }
+
+        for corner in CornerCubelet::enumerate() {
+            let (target, orientation) = permutation
+                .corners()
+                .act(&(corner, CyclicGroup::identity()));
+            let faces = corner.0.faces_clockwise();
+            let target_faces = target.0.faces_clockwise();
+            for i in 0..3 {
+                let face = target_faces[(i + orientation.get_value() as usize) % 3];
+                cube.set_sticker(face, &target_faces, faces[i]);
+            }
+        }
+
+        cube
+    }
+
+    /// Reads the position and orientation of each piece from the stickers of this cube.
+    /// Returns `None` if the stickers do not form a valid arrangement of pieces,
+    /// for instance if two stickers of the same piece have been swapped.
+    /// The resulting permutation may still be unreachable from the solved state.
+    pub fn to_permutation(&self) -> Option<CubePermutation3> {
+        // The value at index `i` is the image of the `i`th piece, once we have found it.
+        let mut centres = [None; CentreCubelet::N];
+        for position in FaceType::enumerate() {
+            let centre = self.sticker(position, &[position]);
+            if centres[centre.index()].is_some() {
+                return None;
+            }
+            centres[centre.index()] = Some(CentreCubelet(position));
+        }
+
+        let mut edges = [None; EdgeCubelet::N];
+        for position in EdgeType::enumerate() {
+            let faces = position.faces();
+            let (edge, orientation) = EdgeType::from_faces(
+                self.sticker(faces[0], &faces),
+                self.sticker(faces[1], &faces),
+            )?;
+            if edges[edge.index()].is_some() {
+                return None;
+            }
+            edges[edge.index()] = Some((EdgeCubelet(position), orientation));
+        }
+
+        let mut corners = [None; CornerCubelet::N];
+        for position in CornerType::enumerate() {
+            let faces = position.faces_clockwise();
+            let (corner, orientation) = CornerType::from_faces(
+                self.sticker(faces[0], &faces),
+                self.sticker(faces[1], &faces),
+                self.sticker(faces[2], &faces),
+            )?;
+            if corners[corner.index()].is_some() {
+                return None;
+            }
+            corners[corner.index()] = Some((CornerCubelet(position), orientation));
+        }
+
+        // Each piece was found exactly once, so every entry has been filled.
+        Some(CubePermutation3::new_unchecked(
+            CentrePermutation::new_unchecked(centres.map(Option::unwrap)),
+            EdgePermutation::new_unchecked(edges.map(Option::unwrap)),
+            CornerPermutation::new_unchecked(corners.map(Option::unwrap)),
+        ))
+    }
+}
+
+impl<const N: usize> Display for Cube<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_net(f, |colour| format!("{} ", colour.letter()))
+    }
+}
+
+/// A 3x3x3 cube that can be turned from JavaScript, for example by an interactive 3D cube.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmCube {
+    cube: Cube<3>,
+}
+
+#[wasm_bindgen]
+impl WasmCube {
+    /// Creates a solved cube.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { cube: Cube::new() }
+    }
+
+    /// Performs a move, or throws an error if it turns slices that a 3x3x3 cube does not have.
+    pub fn apply(&mut self, mv: Move) -> Result<(), JsValue> {
+        self.cube = self
+            .cube
+            .clone()
+            .try_perform(mv)
+            .map_err(|error| js_sys::Error::new(&error.to_string()))?;
+        Ok(())
+    }
+
+    /// Performs a sequence of moves written in Singmaster notation, such as `"R U R' U'"`.
+    /// If the sequence cannot be parsed, an error is thrown and the cube is unchanged.
+    #[wasm_bindgen(js_name = applyStr)]
+    pub fn apply_str(&mut self, s: &str) -> Result<(), JsValue> {
+        let seq = s
+            .parse::<MoveSequence>()
+            .map_err(|error| js_sys::Error::new(&error.to_string()))?;
+        self.cube = self.cube.clone().perform_sequence(&seq);
+        Ok(())
+    }
+
+    /// The colour of each sticker, in the order of a facelet string.
+    /// Each colour is given by the face it belongs to on a solved cube, numbered F, R, U, B, L, D from zero.
+    pub fn facelets(&self) -> Vec<u8> {
+        self.cube.facelet_faces().map(|face| face as u8).collect()
+    }
+
+    /// The state of the cube as a facelet string, such as `"UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"`.
+    #[wasm_bindgen(js_name = faceletString)]
+    pub fn facelet_string(&self) -> String {
+        self.cube.to_facelets()
+    }
+}
+
 #[derive(Clone, Copy)]
 enum FaceSegment {
     Top,
@@ -933,7 +2482,29 @@ enum FaceSegment {
 }
 use FaceSegment::*;
 
-use crate::group::{CyclicGroup, Enumerable, InverseSemigroup, Magma, Semigroup};
+impl FaceType {
+    /// The segment of this face that borders the other face,
+    /// when the faces are laid out as in the `Display` impl for `Cube`.
+    /// Returns `None` if the faces are not adjacent.
+    fn segment_towards(self, other: FaceType) -> Option<FaceSegment> {
+        match (self, other) {
+            (F, U) | (R, U) | (U, B) | (B, U) | (L, U) | (D, F) => Some(Top),
+            (F, R) | (R, B) | (U, R) | (B, L) | (L, F) | (D, R) => Some(Right),
+            (F, D) | (R, D) | (U, F) | (B, D) | (L, D) | (D, B) => Some(Bottom),
+            (F, L) | (R, F) | (U, L) | (B, R) | (L, B) | (D, L) => Some(Left),
+            _ => None,
+        }
+    }
+}
+
+use crate::{
+    error::Error,
+    group::{CyclicGroup, Enumerable, GroupAction, InverseSemigroup, Magma, Semigroup, Unital},
+    permute::{
+        CentreCubelet, CentrePermutation, CornerCubelet, CornerPermutation, CubePermutation3,
+        EdgeCubelet, EdgePermutation,
+    },
+};
 
 // The range is there as an optimisation for the compiler, since we
 // know the size of each array at compile time. It also helps unify
@@ -1070,3 +2641,1056 @@ impl<const N: usize> Index<(usize, usize)> for Face<N> {
         &self.rows[row][col]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::scramble::random_moves;
+
+    #[test]
+    fn equivalence() {
+        let seq = |alg: &str| alg.parse::<MoveSequence>().unwrap();
+        let empty = MoveSequence { moves: Vec::new() };
+        assert_eq!(empty.permutation(), Some(CubePermutation3::identity()));
+        assert!(seq("R U R' U'").repeat(6).is_equivalent(&empty));
+        assert!(!seq("R U R' U'").repeat(3).is_equivalent(&empty));
+        assert!(!seq("R").is_equivalent(&seq("R'")));
+        assert!(seq("R R").is_equivalent(&seq("R2")));
+        assert!(seq("R L'").is_equivalent(&seq("L' R")));
+        assert!(seq("Rw").is_equivalent(&seq("L x")));
+    }
+
+    #[test]
+    fn face_map() {
+        let mut map = FaceMap::new(["F", "R", "U", "B", "L", "D"]);
+        for face in FaceType::enumerate() {
+            assert_eq!(*map.get(face), face.to_string());
+            assert_eq!(usize::from(face), face.index());
+        }
+        *map.get_mut(U) = "up";
+        assert_eq!(*map.get(U), "up");
+        assert_eq!(*map.get(D), "D");
+
+        let opposites = FaceMap::from_fn(FaceType::opposite);
+        for (face, opposite) in opposites.iter() {
+            assert_eq!(*opposite, face.opposite());
+        }
+        let axes = opposites.map(FaceType::axis);
+        assert_eq!(*axes.get(F), Axis::FB);
+        assert_eq!(axes.iter().count(), 6);
+    }
+
+    #[test]
+    fn face_helpers() {
+        assert_eq!(F.opposite(), B);
+        assert_eq!(R.axis(), Axis::RL);
+        assert_eq!(U.adjacent(), [F, L, B, R]);
+        for face in FaceType::enumerate() {
+            assert_eq!(face.opposite().opposite(), face);
+            assert_eq!(face.opposite().axis(), face.axis());
+            let adjacent = face.adjacent();
+            assert!(adjacent
+                .iter()
+                .all(|adjacent| adjacent.axis() != face.axis()));
+            // Viewed from the opposite face, the adjacent faces are in the opposite order.
+            let mut reversed = face.opposite().adjacent();
+            reversed.reverse();
+            assert!((0..4).any(|i| {
+                let mut rotated = reversed;
+                rotated.rotate_left(i);
+                rotated == adjacent
+            }));
+        }
+    }
+
+    /// Checks that every tag in the given string is closed in the right order,
+    /// and that there is no text outside of tags.
+    fn is_well_formed_xml(xml: &str) -> bool {
+        let mut open_tags = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            if !rest[..start].trim().is_empty() {
+                return false;
+            }
+            let end = match rest[start..].find('>') {
+                Some(end) => start + end,
+                None => return false,
+            };
+            let tag = &rest[start + 1..end];
+            if let Some(name) = tag.strip_prefix('/') {
+                if open_tags.pop() != Some(name) {
+                    return false;
+                }
+            } else if !tag.ends_with('/') {
+                open_tags.push(tag.split(' ').next().unwrap());
+            }
+            rest = &rest[end + 1..];
+        }
+        rest.trim().is_empty() && open_tags.is_empty()
+    }
+
+    #[test]
+    fn svg() {
+        fn check<const N: usize>() {
+            let cube = Cube::<N>::new().perform(Move::from_str_sized("R", N).unwrap());
+            let svg = cube.to_svg();
+            assert!(is_well_formed_xml(&svg));
+            assert_eq!(svg.matches("<rect ").count(), 6 * N * N);
+            for face in FaceType::enumerate() {
+                assert_eq!(svg.matches(Colour::from(face).hex()).count(), N * N);
+            }
+        }
+        check::<2>();
+        check::<3>();
+        check::<5>();
+
+        // Swapping the U and D colours swaps white and yellow in the image.
+        let scheme = ColourScheme::new([
+            Colour::Green,
+            Colour::Red,
+            Colour::Yellow,
+            Colour::Blue,
+            Colour::Orange,
+            Colour::White,
+        ])
+        .unwrap();
+        let (white, yellow) = (Colour::White.hex(), Colour::Yellow.hex());
+        assert_eq!(
+            Cube::<3>::with_scheme(scheme).to_svg(),
+            Cube::<3>::new()
+                .to_svg()
+                .replace(white, "_")
+                .replace(yellow, white)
+                .replace('_', yellow)
+        );
+
+        assert!(!is_well_formed_xml("<svg><rect/>"));
+        assert!(!is_well_formed_xml("<svg><g></svg></g>"));
+    }
+
+    #[test]
+    fn ansi() {
+        let cube = Cube::<3>::new().perform_sequence(&"R U F'".parse().unwrap());
+        let ansi = cube.to_ansi();
+        assert_eq!(ansi.matches("\x1b[0m").count(), 54);
+        for face in FaceType::enumerate() {
+            let code = format!("\x1b[30;{}m", Colour::from(face).ansi_background());
+            assert_eq!(ansi.matches(&code).count(), 9);
+        }
+        assert_eq!(cube.to_terminal_string(false), cube.to_string());
+        assert!(!cube.to_terminal_string(false).contains('\x1b'));
+    }
+
+    #[test]
+    fn colour_scheme() {
+        assert_eq!(ColourScheme::new([Colour::Green; 6]), None);
+        // Swap the U and D colours.
+        let scheme = ColourScheme::new([
+            Colour::Green,
+            Colour::Red,
+            Colour::Yellow,
+            Colour::Blue,
+            Colour::Orange,
+            Colour::White,
+        ])
+        .unwrap();
+        assert_eq!(scheme.colour(U), Colour::Yellow);
+        assert_eq!(scheme.recolour(Colour::Green), Colour::Green);
+
+        let default = Cube::<3>::new();
+        let cube = Cube::<3>::with_scheme(scheme);
+        assert_eq!(
+            default.to_string(),
+            cube.to_string()
+                .replace('w', "_")
+                .replace('y', "w")
+                .replace('_', "y")
+        );
+        assert!(cube.is_solved());
+        // The scheme only changes how the cube is displayed.
+        assert_eq!(cube, default);
+
+        let scramble: MoveSequence = "R U2 F' L D B2".parse().unwrap();
+        let default = default.perform_sequence(&scramble);
+        let cube = cube.perform_sequence(&scramble);
+        assert!(!cube.is_solved());
+        assert_eq!(cube.scheme(), scheme);
+        assert_ne!(default.to_string(), cube.to_string());
+        assert_eq!(cube, default);
+        assert!(cube.perform_sequence(&scramble.inverse()).is_solved());
+    }
+
+    #[test]
+    fn colour_letters() {
+        for face in FaceType::enumerate() {
+            let colour = Colour::from(face);
+            assert_eq!(Colour::from_letter(colour.letter()), Some(colour));
+            assert_eq!(colour.letter().to_string().parse(), Ok(colour));
+            assert_eq!(face.to_string().parse(), Ok(colour));
+        }
+        assert_eq!(Colour::from_letter('B'), Some(Colour::Blue));
+        assert_eq!(Colour::from_letter('R'), Some(Colour::Red));
+        assert_eq!(Colour::from_letter('U'), Some(Colour::White));
+        assert_eq!(Colour::from_letter('x'), None);
+        assert_eq!(Colour::from_letter('G'), None);
+        assert_eq!(
+            "".parse::<Colour>(),
+            Err(ColourParseError::NotOneLetter(String::new()))
+        );
+        assert_eq!(
+            "gr".parse::<Colour>(),
+            Err(ColourParseError::NotOneLetter("gr".to_string()))
+        );
+        assert_eq!(
+            "x".parse::<Colour>(),
+            Err(ColourParseError::UnknownLetter('x'))
+        );
+        assert_eq!(
+            "x".parse::<Colour>().unwrap_err().to_string(),
+            "'x' is not a colour or face"
+        );
+    }
+
+    #[test]
+    fn corner_faces() {
+        for corner in CornerType::enumerate() {
+            let [f1, f2, f3] = corner.faces();
+            assert_eq!(CornerType::from_faces_ordered(f1, f2, f3), Some(corner));
+            assert_eq!(corner.to_string(), format!("{:?}{:?}{:?}", f1, f2, f3));
+
+            let faces = corner.faces_clockwise();
+            for orientation in 0..3 {
+                let [f1, f2, f3] = [0, 1, 2].map(|i| faces[(i + 3 - orientation) % 3]);
+                assert_eq!(
+                    CornerType::from_faces(f1, f2, f3),
+                    Some((corner, CyclicGroup::new(orientation as u8)))
+                );
+            }
+
+            // Anticlockwise orders do not describe a corner.
+            assert_eq!(CornerType::from_faces(faces[0], faces[2], faces[1]), None);
+        }
+        assert_eq!(
+            CornerType::from_faces(R, F, U),
+            Some((FUR, CyclicGroup::new(2)))
+        );
+        assert_eq!(
+            CornerType::from_faces(F, U, R),
+            Some((FUR, CyclicGroup::new(1)))
+        );
+        assert_eq!(CornerType::from_faces(U, R, B), None);
+    }
+
+    #[test]
+    fn move_display() {
+        for notation in [
+            "F", "F'", "F2", "R", "U'", "B", "B'", "B2", "L", "L2", "D'", "M", "M'", "M2", "E",
+            "E'", "S", "S2", "Fw", "Rw'", "Uw2", "Bw", "Lw'", "Dw2", "x", "x'", "y2", "z",
+        ] {
+            assert_eq!(notation.parse::<Move>().unwrap().to_string(), notation);
+        }
+
+        // Some notation is canonicalised when displayed, but the direction of a double turn is kept.
+        assert_eq!("U2'".parse::<Move>().unwrap().to_string(), "U2'");
+        assert_eq!("D2'".parse::<Move>().unwrap().to_string(), "D2'");
+        assert_eq!("f".parse::<Move>().unwrap().to_string(), "Fw");
+        assert_eq!("l'".parse::<Move>().unwrap().to_string(), "Lw'");
+
+        assert_eq!(Move::new(FB, RotationType::Normal, 0, 1).to_string(), "F");
+        assert_eq!(Move::new(RL, RotationType::Normal, 1, 2).to_string(), "M'");
+    }
+
+    #[test]
+    fn signed_double_turns() {
+        let clockwise = "U2".parse::<Move>().unwrap();
+        let anticlockwise = "U2'".parse::<Move>().unwrap();
+        // The moves are distinguishable, but have the same effect.
+        assert_ne!(clockwise, anticlockwise);
+        assert_eq!(clockwise.key(), anticlockwise.key());
+        assert_eq!(
+            [clockwise, anticlockwise]
+                .into_iter()
+                .collect::<HashSet<_>>()
+                .len(),
+            2
+        );
+        assert_eq!(anticlockwise.to_string(), "U2'");
+        assert_eq!(clockwise.rotation_type, RotationType::Double);
+        assert_eq!(anticlockwise.rotation_type, RotationType::Double);
+        assert_eq!(
+            clockwise.signed_rotation_type(),
+            SignedRotationType::DoubleClockwise
+        );
+        assert_eq!(
+            anticlockwise.signed_rotation_type(),
+            SignedRotationType::DoubleAnticlockwise
+        );
+        assert_eq!(anticlockwise.signed_rotation_type().rotations(), -2);
+        assert_eq!(
+            RotationType::from(anticlockwise.signed_rotation_type()),
+            RotationType::Double
+        );
+        // By default, the direction is kept when inverting.
+        assert_eq!(anticlockwise.inverse(), anticlockwise);
+        assert_eq!(clockwise.inverse(), clockwise);
+        let seq = "R U2' M2".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.inverse().to_string(), "M2 U2' R'");
+        assert_eq!(anticlockwise.inverse_signed(), clockwise);
+        assert_eq!(clockwise.inverse_signed(), anticlockwise);
+        assert_eq!(seq.inverse_signed().to_string(), "M2' U2 R'");
+
+        // The direction of a double turn never changes its effect on the cube.
+        for (a, b) in [("U2", "U2'"), ("R2 D2'", "R2' D2"), ("M2' Rw2'", "M2 Rw2")] {
+            assert_eq!(
+                CubePermutation3::from_move_sequence(a.parse().unwrap()).unwrap(),
+                CubePermutation3::from_move_sequence(b.parse().unwrap()).unwrap()
+            );
+        }
+        assert_eq!(
+            CubePermutation3::from_move(anticlockwise),
+            CubePermutation3::from_move(clockwise)
+        );
+        assert_eq!(
+            Cube::<4>::new().perform(anticlockwise),
+            Cube::<4>::new().perform(clockwise)
+        );
+    }
+
+    #[test]
+    fn move_sequence_display() {
+        let superflip = "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2";
+        let seq = superflip.parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.to_string(), superflip);
+        assert_eq!(seq.to_string().parse::<MoveSequence>().unwrap(), seq);
+
+        // Notation such as lowercase wide moves is canonicalised, but still parses to the same moves.
+        let seq = "r U2' R' U' M2".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.to_string(), "Rw U2' R' U' M2");
+        assert_eq!(seq.to_string().parse::<MoveSequence>().unwrap(), seq);
+
+        assert_eq!(seq.to_string_with_sep(", "), "Rw, U2', R', U', M2");
+        assert_eq!(MoveSequence { moves: Vec::new() }.to_string(), "");
+        assert_eq!(
+            MoveSequence { moves: Vec::new() }.to_string_with_sep(", "),
+            ""
+        );
+    }
+
+    #[test]
+    fn move_parse_errors() {
+        assert_eq!("X".parse::<Move>(), Err(MoveParseError::UnknownFace('X')));
+        assert_eq!(
+            "R3".parse::<Move>(),
+            Err(MoveParseError::InvalidModifier('3'))
+        );
+        assert_eq!(
+            "R2w".parse::<Move>(),
+            Err(MoveParseError::TrailingGarbage("w".to_string()))
+        );
+        assert_eq!("".parse::<Move>(), Err(MoveParseError::EmptyToken));
+
+        let error = " // No moves here".parse::<MoveSequence>().unwrap_err();
+        assert_eq!(
+            error,
+            MoveSequenceParseError {
+                index: 0,
+                error: MoveParseError::EmptyToken
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "could not parse move 1: expected a move, but found nothing"
+        );
+
+        let error = "R U R3".parse::<MoveSequence>().unwrap_err();
+        assert_eq!(error.index, 2);
+        assert_eq!(
+            error.to_string(),
+            "could not parse move 3: '3' is not a valid modifier"
+        );
+
+        assert_eq!("R U R' U'".parse::<MoveSequence>().unwrap().moves.len(), 4);
+    }
+
+    #[test]
+    fn whitespace_and_comments() {
+        let parse = |s: &str| s.parse::<MoveSequence>();
+        assert_eq!(parse("U  R"), parse("U R"));
+        assert_eq!(parse("\tR U\r\nR' "), parse("R U R'"));
+
+        let sheet = "
+            // T perm
+            R U R' U' # sexy move
+            R' F R2 U' R' U'
+
+            // Finish with a sledgehammer variant.
+            R U R' F'
+        ";
+        let seq = parse(sheet).unwrap();
+        assert_eq!(seq.moves.len(), 14);
+        assert_eq!(seq, parse("R U R' U' R' F R2 U' R' U' R U R' F'").unwrap());
+        assert_eq!(
+            CubePermutation3::from_move_sequence(seq)
+                .unwrap()
+                .cycle_order(),
+            2
+        );
+    }
+
+    #[test]
+    fn bracket_notation() {
+        let parse = |s: &str| s.parse::<MoveSequence>();
+        assert_eq!(parse("[R, U]"), parse("R U R' U'"));
+        assert_eq!(parse("[R: U]"), parse("R U R'"));
+        assert_eq!(parse("[R U R', D2]"), parse("R U R' D2 R U' R' D2"));
+        assert_eq!(parse("[F: [R, U]]"), parse("F R U R' U' F'"));
+        assert_eq!(parse("[R' U': [R2, D]]"), parse("R' U' R2 D R2 D' U R"));
+        assert_eq!(parse("x [ R ,U ]  y'"), parse("x R U R' U' y'"));
+        assert_eq!(parse("R2 [U: R U R'] R"), parse("R2 U R U R' U' R"));
+
+        assert_eq!(
+            parse("[R, U"),
+            Err(MoveSequenceParseError {
+                index: 2,
+                error: MoveParseError::UnclosedBracket
+            })
+        );
+        assert_eq!(
+            parse("R U]"),
+            Err(MoveSequenceParseError {
+                index: 2,
+                error: MoveParseError::UnexpectedSymbol(']')
+            })
+        );
+        assert_eq!(
+            parse("[R U]"),
+            Err(MoveSequenceParseError {
+                index: 2,
+                error: MoveParseError::UnexpectedSymbol(']')
+            })
+        );
+        assert_eq!(
+            parse("[R, U, F]").unwrap_err().error,
+            MoveParseError::UnexpectedSymbol(',')
+        );
+        assert_eq!(
+            parse("[R, X]").unwrap_err().to_string(),
+            "could not parse move 2: 'X' is not a face or slice"
+        );
+        assert_eq!(parse("").unwrap_err().error, MoveParseError::EmptyToken);
+        assert_eq!(parse("   ").unwrap_err().error, MoveParseError::EmptyToken);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let scramble = "U2 B D' B U2 L F' D B' U2 D R' U2 B R2 D' B' D2 L B2 F2 U D2 F B2 Rw M' x2"
+            .parse::<MoveSequence>()
+            .unwrap();
+        let json = serde_json::to_string(&scramble).unwrap();
+        assert_eq!(json, format!("\"{}\"", scramble));
+        assert_eq!(
+            serde_json::from_str::<MoveSequence>(&json).unwrap(),
+            scramble
+        );
+
+        let json = serde_json::to_string(&scramble.moves).unwrap();
+        assert!(json.starts_with(r#"["U2","B","D'","#));
+        assert_eq!(
+            serde_json::from_str::<Vec<Move>>(&json).unwrap(),
+            scramble.moves
+        );
+
+        let empty = MoveSequence { moves: Vec::new() };
+        let json = serde_json::to_string(&empty).unwrap();
+        assert_eq!(json, r#""""#);
+        assert_eq!(serde_json::from_str::<MoveSequence>(&json).unwrap(), empty);
+
+        assert!(serde_json::from_str::<MoveSequence>(r#""R X""#).is_err());
+        assert!(serde_json::from_str::<Move>(r#""R U""#).is_err());
+
+        // Moves that reach beyond the layers of a 3x3x3 cube would not be read back as the same move.
+        let mv = Move::from_str_sized("4Rw", 5).unwrap();
+        assert_eq!(
+            serde_json::to_string(&mv).unwrap_err().to_string(),
+            "the move RL0-4 is not a move on a 3x3x3 cube, so it cannot be serialized"
+        );
+        let seq = MoveSequence {
+            moves: vec!["R".parse().unwrap(), Move::from_str_sized("4U", 4).unwrap()],
+        };
+        assert!(serde_json::to_string(&seq).is_err());
+    }
+
+    #[test]
+    fn repeated_groups() {
+        let parse = |s: &str| s.parse::<MoveSequence>();
+        assert_eq!(parse("(R U)2"), parse("R U R U"));
+        assert_eq!(parse("(M U)4"), parse("M U M U M U M U"));
+        assert_eq!(parse("(R U)"), parse("R U"));
+        assert_eq!(parse("(R U)'"), parse("U' R'"));
+        assert_eq!(parse("(R U2)2'"), parse("U2 R' U2 R'"));
+        assert_eq!(
+            parse("F (R U R' U')2 F'"),
+            parse("F R U R' U' R U R' U' F'")
+        );
+        assert_eq!(parse("((R U)2 D)2"), parse("R U R U D R U R U D"));
+        assert_eq!(parse("[(R U)2, D]"), parse("R U R U D U' R' U' R' D'"));
+        assert_eq!(parse("(R U)0 F").unwrap().moves.len(), 1);
+
+        assert_eq!(
+            parse("(R U"),
+            Err(MoveSequenceParseError {
+                index: 2,
+                error: MoveParseError::UnclosedBracket
+            })
+        );
+        assert_eq!(
+            parse("(R U]").unwrap_err().error,
+            MoveParseError::UnexpectedSymbol(']')
+        );
+        assert_eq!(
+            parse("R U)2").unwrap_err().error,
+            MoveParseError::UnexpectedSymbol(')')
+        );
+        assert_eq!(
+            parse("(R U)2x").unwrap_err().error,
+            MoveParseError::InvalidModifier('2')
+        );
+
+        // Expanding a group may not produce more than the maximum number of moves.
+        let too_many_moves = MoveParseError::TooManyMoves {
+            limit: MAX_SEQUENCE_LENGTH,
+        };
+        for s in [
+            "(R U)99999999999",
+            "(R U)999999999999999999999999",
+            "((R)999999)999999",
+            "((R U)999)999",
+            "()99999999999",
+            "[((R)5000)2, U]",
+        ] {
+            assert_eq!(parse(s).unwrap_err().error, too_many_moves, "{}", s);
+        }
+        assert_eq!(parse("(R U)5000").unwrap().moves.len(), MAX_SEQUENCE_LENGTH);
+        assert_eq!(
+            parse("F (R U)99999999999").unwrap_err(),
+            MoveSequenceParseError {
+                index: 3,
+                error: too_many_moves
+            }
+        );
+
+        // Brackets may only be nested so deeply, however many there are.
+        let nested = |depth: usize| "(".repeat(depth) + "R" + &")".repeat(depth);
+        assert_eq!(parse(&nested(MAX_NESTING_DEPTH)).unwrap().to_string(), "R");
+        let too_deeply_nested = MoveParseError::TooDeeplyNested {
+            limit: MAX_NESTING_DEPTH,
+        };
+        for s in [
+            nested(MAX_NESTING_DEPTH + 1),
+            nested(200_000),
+            "[R: ".repeat(100_000) + "U",
+        ] {
+            assert_eq!(parse(&s).unwrap_err().error, too_deeply_nested);
+        }
+    }
+
+    #[test]
+    fn canonicalise() {
+        let canonicalise = |s: &str| {
+            s.parse::<MoveSequence>()
+                .unwrap()
+                .canonicalise()
+                .to_string()
+        };
+        assert_eq!(canonicalise("R R"), "R2");
+        assert_eq!(canonicalise("U U U"), "U'");
+        assert_eq!(canonicalise("R R'"), "");
+        assert_eq!(canonicalise("R2 R2"), "");
+        assert_eq!(canonicalise("R U U' R'"), "");
+        assert_eq!(canonicalise("R U R'"), "R U R'");
+        assert_eq!(canonicalise("F R U2 U"), "F R U'");
+        // Opposite faces commute, so they are merged through each other.
+        assert_eq!(canonicalise("R L R"), "R2 L");
+        // Adjacent slices turning together become a wide move.
+        assert_eq!(canonicalise("R M'"), "Rw");
+        assert_eq!(canonicalise("L' M' R"), "x");
+    }
+
+    #[test]
+    fn iteration() {
+        let seq = "R U R' U'".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.len(), 4);
+        assert!(!seq.is_empty());
+        assert!(seq.repeat(0).is_empty());
+        assert_eq!(seq[1], "U".parse().unwrap());
+
+        let inverted = seq.into_iter().map(Move::inverse).collect::<MoveSequence>();
+        assert_eq!(inverted.to_string(), "R' U' R U");
+        assert_eq!(
+            (&inverted)
+                .into_iter()
+                .filter(|mv| mv.axis == Axis::RL)
+                .count(),
+            2
+        );
+        let mut names = Vec::new();
+        for mv in &inverted {
+            names.push(mv.to_string());
+        }
+        assert_eq!(names, ["R'", "U'", "R", "U"]);
+        assert_eq!(
+            inverted
+                .into_iter()
+                .rev()
+                .collect::<MoveSequence>()
+                .to_string(),
+            "U R U' R'"
+        );
+    }
+
+    #[test]
+    fn concatenation() {
+        let seq = "R U R' U'".parse::<MoveSequence>().unwrap();
+        assert!(seq.repeat(0).moves.is_empty());
+        assert_eq!(seq.repeat(1), seq);
+        assert_eq!((seq.clone() * 2).to_string(), "R U R' U' R U R' U'");
+        assert_eq!(
+            (seq.clone() + "F".parse().unwrap()).to_string(),
+            "R U R' U' F"
+        );
+        assert_eq!(
+            CubePermutation3::from_move_sequence(seq.clone() + seq.inverse()).unwrap(),
+            CubePermutation3::identity()
+        );
+        assert_eq!(
+            CubePermutation3::from_move_sequence(seq * 6).unwrap(),
+            CubePermutation3::identity()
+        );
+
+        let r = "R".parse::<MoveSequence>().unwrap();
+        assert_eq!(
+            CubePermutation3::from_move_sequence(r * 4)
+                .unwrap()
+                .cycle_order(),
+            1
+        );
+    }
+
+    #[test]
+    fn absorb_rotations() {
+        let absorb = |s: &str| {
+            let seq = s.parse::<MoveSequence>().unwrap();
+            let absorbed = seq.absorb_rotations();
+            assert_eq!(
+                Cube::<3>::new().perform_sequence(&seq),
+                Cube::<3>::new().perform_sequence(&absorbed),
+                "{}",
+                s
+            );
+            absorbed.to_string()
+        };
+        assert_eq!(absorb("y R U R' y'"), "B U B'");
+        assert_eq!(absorb("R U"), "R U");
+        assert_eq!(absorb("y R"), "B y");
+        assert_eq!(absorb("x U M"), "F M x");
+        assert_eq!(absorb("z2 R x' F2 y D' z"), "L D2 F' z2 y");
+        // A double turn keeps its direction as seen from the same physical face.
+        assert_eq!(absorb("x2 U2' E2' Rw2'"), "D2' E2 Rw2' x2");
+        assert_eq!(
+            CubePermutation3::from_move_sequence("y R U R' y'".parse().unwrap()).unwrap(),
+            CubePermutation3::from_move_sequence("B U B'".parse().unwrap()).unwrap()
+        );
+
+        // Every orientation can be written down after absorbing rotations.
+        let moves = "F F2 F' S S2 S' B B2 B' z z2 z' R R2 R' M M2 M' L L2 L' x x2 x' \
+            U U2 U' E E2 E' D D2 D' y y2 y'"
+            .parse::<MoveSequence>()
+            .unwrap()
+            .moves;
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let seq = random_moves(&mut rng, &moves, 10);
+            let absorbed = seq.absorb_rotations();
+            assert_eq!(
+                Cube::<3>::new().perform_sequence(&seq),
+                Cube::<3>::new().perform_sequence(&absorbed)
+            );
+            let rotations = absorbed.moves.iter().filter(|mv| mv.is_rotation()).count();
+            assert!(rotations <= 2);
+            assert_eq!(
+                absorbed.without_rotations().moves.len(),
+                absorbed.moves.len() - rotations
+            );
+        }
+    }
+
+    #[test]
+    fn wide_moves() {
+        let mv = "Rw".parse::<Move>().unwrap();
+        let expanded = mv.expand_wide();
+        assert_eq!(expanded.to_string(), "R M'");
+        assert_eq!(
+            Cube::<3>::new().perform(mv),
+            Cube::<3>::new().perform_sequence(&expanded)
+        );
+        assert_eq!(expanded.contract_to_wide().to_string(), "Rw");
+
+        let mv = Move::from_str_sized("3Lw'", 5).unwrap();
+        let expanded = mv.expand_wide();
+        assert_eq!(expanded.moves.len(), 3);
+        assert_eq!(
+            Cube::<5>::new().perform(mv),
+            Cube::<5>::new().perform_sequence(&expanded)
+        );
+        assert_eq!(expanded.contract_to_wide().moves, vec![mv]);
+
+        let seq = "R U M' R2 L2 B".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.contract_to_wide(), seq);
+        let seq = "L' M' R".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.contract_to_wide().to_string(), "x");
+    }
+
+    #[test]
+    fn sized_moves() {
+        let mv = Move::from_str_sized("3R", 5).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (RL, 2, 3));
+        assert_eq!(mv.rotation_type, RotationType::Normal);
+
+        let mv = Move::from_str_sized("3Rw", 5).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (RL, 0, 3));
+
+        // Back face depths are measured against the size of the cube.
+        let mv = Move::from_str_sized("2L'", 5).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (RL, 3, 4));
+        assert_eq!(mv.rotation_type, RotationType::Normal);
+        let mv = Move::from_str_sized("Bw2", 4).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (FB, 2, 4));
+
+        let mv = Move::from_str_sized("M", 5).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (RL, 1, 4));
+        let mv = Move::from_str_sized("y'", 4).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (UD, 0, 4));
+
+        // Parsing without a size assumes a 3x3x3 cube.
+        assert_eq!(
+            "L".parse::<Move>().unwrap(),
+            Move::from_str_sized("L", 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn layer_prefixes() {
+        let mv = Move::from_str_sized("2Rw", 4).unwrap();
+        assert_eq!((mv.axis, mv.start_depth, mv.end_depth), (RL, 0, 2));
+        assert_eq!(mv, Move::from_str_sized("Rw", 4).unwrap());
+
+        let mv = Move::from_str_sized("3Rw2", 5).unwrap();
+        assert_eq!((mv.start_depth, mv.end_depth), (0, 3));
+        assert_eq!(mv.rotation_type, RotationType::Double);
+
+        let mv = Move::from_str_sized("2R'", 4).unwrap();
+        assert_eq!((mv.start_depth, mv.end_depth), (1, 2));
+        assert_eq!(mv.rotation_type, RotationType::Inverse);
+
+        // On a 3x3x3, the second layer is the M slice, and three wide layers are a rotation.
+        assert_eq!("1R".parse::<Move>().unwrap(), "R".parse::<Move>().unwrap());
+        assert_eq!("2L".parse::<Move>().unwrap(), "M".parse::<Move>().unwrap());
+        assert_eq!("3Fw".parse::<Move>().unwrap(), "z".parse::<Move>().unwrap());
+
+        assert_eq!(
+            Move::from_str_sized("4Rw", 3),
+            Err(MoveParseError::LayerOutOfRange { layers: 4, size: 3 })
+        );
+        assert_eq!(
+            "0R".parse::<Move>(),
+            Err(MoveParseError::LayerOutOfRange { layers: 0, size: 3 })
+        );
+        assert_eq!(
+            "2M".parse::<Move>(),
+            Err(MoveParseError::LayerOutOfRange { layers: 2, size: 3 })
+        );
+        assert_eq!("2".parse::<Move>(), Err(MoveParseError::EmptyToken));
+
+        // Slices and rotations cannot be made wider.
+        for mv in ["xw", "Mw2", "Sw'"] {
+            assert_eq!(
+                mv.parse::<Move>(),
+                Err(MoveParseError::InvalidModifier('w'))
+            );
+        }
+        assert_eq!(
+            Move::from_str_sized("M", 2),
+            Err(MoveParseError::CubeTooSmall { size: 2 })
+        );
+        assert_eq!(
+            Move::from_str_sized("R", 0),
+            Err(MoveParseError::CubeTooSmall { size: 0 })
+        );
+        assert_eq!(
+            Move::from_str_sized("R", 0).unwrap_err().to_string(),
+            "a 0x0x0 cube has no layers that this move could turn"
+        );
+        assert!(Move::from_str_sized("x", 1).is_ok());
+    }
+
+    #[test]
+    fn try_perform() {
+        let cube = Cube::<3>::new();
+        assert!(cube.clone().try_perform("Rw".parse().unwrap()).is_ok());
+
+        let mv = Move::new(RL, RotationType::Normal, 0, 4);
+        let error = cube.try_perform(mv).unwrap_err();
+        assert_eq!(error, Error::DepthOutOfRange { mv, size: 3 });
+        assert_eq!(
+            error.to_string(),
+            "the move RL0-4 turns slices 0 to 4, but a 3x3x3 cube only has 3 slices"
+        );
+    }
+
+    #[test]
+    fn full_width_turns() {
+        fn perform_all<const N: usize>(mut cube: Cube<N>, seq: &str) -> Cube<N> {
+            for mv in seq.split(' ') {
+                cube = cube.perform(Move::from_str_sized(mv, N).unwrap());
+            }
+            cube
+        }
+
+        // After an `x` rotation, the front face is the old down face, and so on.
+        let cube = Cube::<3>::new().perform("x".parse().unwrap());
+        for (face, colour) in [
+            (F, Colour::Yellow),
+            (R, Colour::Red),
+            (U, Colour::Green),
+            (B, Colour::White),
+            (L, Colour::Orange),
+            (D, Colour::Blue),
+        ] {
+            assert_eq!(cube.face(face).rows, [[colour; 3]; 3]);
+        }
+
+        // A full-width turn is the same as turning every slice on its axis.
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B";
+        for (rotation, slices) in [
+            ("x", "R M' L'"),
+            ("x2", "R2 M2 L2"),
+            ("y'", "U' E D"),
+            ("z", "F S B'"),
+        ] {
+            let cube = perform_all(Cube::<3>::new(), scramble);
+            assert_eq!(
+                perform_all(cube.clone(), rotation),
+                perform_all(cube, slices)
+            );
+        }
+
+        // On larger cubes, a full-width turn includes the opposite face.
+        let cube = perform_all(Cube::<4>::new(), "Rw U 2F' L D2");
+        assert_eq!(
+            perform_all(cube.clone(), "x"),
+            perform_all(cube.clone(), "Rw Lw'")
+        );
+        assert_eq!(perform_all(cube.clone(), "x x x x"), cube);
+        let cube = perform_all(Cube::<5>::new(), "3Rw U 2F' L D2");
+        assert_eq!(
+            perform_all(cube.clone(), "y"),
+            perform_all(cube, "U 2U 3U 2D' D'")
+        );
+    }
+
+    #[test]
+    fn back_face_turns() {
+        // `F` moves the top row of D onto the right column of L,
+        // then `L` turns that column onto the bottom row of L.
+        let cube = Cube::<3>::new()
+            .perform("F".parse().unwrap())
+            .perform("L".parse().unwrap());
+        assert_eq!(cube.face(L).rows[2], [Colour::Yellow; 3]);
+        assert_eq!(cube.face(L).rows[0], [Colour::Orange; 3]);
+
+        // Turning the back face as part of a rotation agrees with turning it on its own.
+        let cube = Cube::<3>::new().perform("U".parse().unwrap());
+        for (rotation, slices) in [("x", "R M' L'"), ("y", "U E' D'"), ("z'", "F' S' B")] {
+            let seq = slices.parse::<MoveSequence>().unwrap();
+            assert_eq!(
+                cube.clone().perform(rotation.parse().unwrap()),
+                cube.clone().perform_sequence(&seq)
+            );
+        }
+    }
+
+    #[test]
+    fn play() {
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B"
+            .parse::<MoveSequence>()
+            .unwrap();
+        let states = Cube::<3>::new().play(&scramble).collect::<Vec<_>>();
+        assert_eq!(states.len(), scramble.len());
+        assert_eq!(states[0], Cube::new().perform(scramble[0]));
+        assert_eq!(
+            states.last(),
+            Some(&Cube::new().perform_sequence(&scramble))
+        );
+
+        // Playing a scramble and then its solution ends with a solved cube.
+        let moves = scramble.clone() + scramble.inverse();
+        let last = Cube::<3>::new().play(&moves).last().unwrap();
+        assert!(last.is_solved());
+        assert!(Cube::<3>::new()
+            .play(&moves)
+            .take(scramble.len())
+            .all(|cube| !cube.is_solved()));
+        assert_eq!(
+            Cube::<3>::new()
+                .play(&MoveSequence { moves: Vec::new() })
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn perform_sequence() {
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B"
+            .parse::<MoveSequence>()
+            .unwrap();
+        let cube = Cube::<3>::new().perform_sequence(&scramble);
+        assert_ne!(cube, Cube::new());
+        assert_eq!(cube.perform_sequence(&scramble.inverse()), Cube::new());
+
+        // Moves are performed left to right.
+        let seq = "R U".parse::<MoveSequence>().unwrap();
+        assert_eq!(
+            Cube::<3>::new().perform_sequence(&seq),
+            Cube::new()
+                .perform("R".parse().unwrap())
+                .perform("U".parse().unwrap())
+        );
+        assert_ne!(
+            Cube::<3>::new().perform_sequence(&seq),
+            Cube::new()
+                .perform("U".parse().unwrap())
+                .perform("R".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn equality() {
+        let r = "R".parse::<Move>().unwrap();
+        let cube = Cube::<3>::new().perform(r);
+        assert_ne!(cube, Cube::new());
+        assert_eq!(cube.clone(), cube);
+        assert_eq!(cube.perform(r).perform(r).perform(r), Cube::new());
+
+        assert_eq!(Face::<3>::new(U), Face::new(U));
+        assert_ne!(Face::<3>::new(U), Face::new(D));
+        let cube = Cube::<4>::new().perform(Move::from_str_sized("2R", 4).unwrap());
+        assert_eq!(cube.face(R), &Face::new(R));
+        assert_ne!(cube.face(U), &Face::new(U));
+    }
+
+    #[test]
+    fn wasm_cube() {
+        let mut cube = WasmCube::new();
+        assert_eq!(cube.facelet_string(), Cube::<3>::new().to_facelets());
+
+        cube.apply("R".parse().unwrap()).unwrap();
+        cube.apply_str("U R' U'").unwrap();
+        let expected = Cube::<3>::new().perform_sequence(&"R U R' U'".parse().unwrap());
+        assert_eq!(cube.facelet_string(), expected.to_facelets());
+
+        // Each byte names the face whose colour the sticker has.
+        let facelets = cube.facelets();
+        assert_eq!(facelets.len(), 54);
+        let letters = facelets
+            .iter()
+            .map(|&face| FaceType::enumerate()[face as usize].to_string())
+            .collect::<String>();
+        assert_eq!(letters, expected.to_facelets());
+        assert_eq!(Cube::from_facelets(&letters), Ok(expected));
+
+        cube.apply_str("(R U R' U')5").unwrap();
+        assert_eq!(cube, WasmCube::new());
+    }
+
+    #[test]
+    fn facelets() {
+        let solved = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+        assert_eq!(Cube::<3>::new().to_facelets(), solved);
+        assert_eq!(Cube::from_facelets(solved), Ok(Cube::new()));
+
+        let r = "UUFUUFUUFRRRRRRRRRFFDFFDFFDDDBDDBDDBLLLLLLLLLUBBUBBUBB";
+        let cube = Cube::new().perform("R".parse().unwrap());
+        assert_eq!(cube.to_facelets(), r);
+        assert_eq!(Cube::from_facelets(r), Ok(cube));
+
+        let f = "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB";
+        let cube = Cube::new().perform("F".parse().unwrap());
+        assert_eq!(cube.to_facelets(), f);
+        assert_eq!(Cube::from_facelets(f), Ok(cube));
+
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B M y"
+            .parse::<MoveSequence>()
+            .unwrap();
+        let cube = Cube::new().perform_sequence(&scramble);
+        assert_eq!(Cube::from_facelets(&cube.to_facelets()), Ok(cube));
+
+        assert_eq!(
+            Cube::from_facelets(&solved[1..]),
+            Err(FaceletError::WrongLength(53))
+        );
+        assert_eq!(
+            Cube::from_facelets(&solved.replace('B', "X")),
+            Err(FaceletError::UnknownColour('X'))
+        );
+        assert_eq!(
+            Cube::from_facelets(&solved.replacen('U', "R", 1)),
+            Err(FaceletError::WrongColourCount {
+                colour: U,
+                count: 8
+            })
+        );
+        // Swap the U centre with an R sticker, so that there are two R centres.
+        let two_r_centres = "UUUURUUUUURRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+        assert_eq!(
+            Cube::from_facelets(two_r_centres),
+            Err(FaceletError::DuplicateCentre(R))
+        );
+        assert_eq!(
+            FaceletError::WrongColourCount {
+                colour: U,
+                count: 8
+            }
+            .to_string(),
+            "expected 9 facelets of colour U, but found 8"
+        );
+    }
+
+    #[test]
+    fn is_solved() {
+        let r = "R".parse::<Move>().unwrap();
+        assert!(Cube::<3>::new().is_solved());
+        let cube = Cube::<3>::new().perform(r);
+        assert!(!cube.is_solved());
+        assert!(cube.perform(r).perform(r).perform(r).is_solved());
+        assert!(Cube::<3>::new().perform("x".parse().unwrap()).is_solved());
+
+        // Even cubes have no fixed centres, so only the colour of each face matters.
+        let cube = Cube::<4>::new().perform(Move::from_str_sized("Rw", 4).unwrap());
+        assert!(!cube.is_solved());
+        assert!(cube
+            .perform(Move::from_str_sized("Lw'", 4).unwrap())
+            .perform(Move::from_str_sized("x'", 4).unwrap())
+            .is_solved());
+        assert!(Cube::<4>::new()
+            .perform(Move::from_str_sized("2R", 4).unwrap())
+            .perform(Move::from_str_sized("2L'", 4).unwrap())
+            .perform(Move::from_str_sized("R", 4).unwrap())
+            .perform(Move::from_str_sized("L'", 4).unwrap())
+            .is_solved());
+    }
+}
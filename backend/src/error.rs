@@ -0,0 +1,94 @@
+use std::fmt::Display;
+
+use crate::cube::{FaceletError, Move, MoveSequenceParseError};
+
+/// The error type for fallible operations across the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The given input could not be parsed.
+    /// The contained string describes why the input was rejected.
+    Parse(String),
+    /// The given cube state does not contain a valid arrangement of pieces,
+    /// or cannot be reached from the solved state by any sequence of moves.
+    InvalidState,
+    /// Every step of a solve succeeded, but the cube was not solved at the end.
+    Unsolvable,
+    /// A step of a solve method could not find a move sequence for the current cube state.
+    /// If the method can describe it, the signature of the cube state that the step looked up is given,
+    /// which helps to find out why the step failed.
+    StepFailed {
+        step: &'static str,
+        signature: Option<String>,
+    },
+    /// A move tried to turn slices that do not exist on a cube of the given size.
+    DepthOutOfRange { mv: Move, size: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(reason) => write!(f, "{}", reason),
+            Error::InvalidState => write!(f, "the cube state is not a valid arrangement of pieces"),
+            Error::Unsolvable => write!(f, "the cube cannot be solved"),
+            Error::StepFailed {
+                step,
+                signature: None,
+            } => write!(f, "could not complete the step \"{}\"", step),
+            Error::StepFailed {
+                step,
+                signature: Some(signature),
+            } => write!(
+                f,
+                "could not complete the step \"{}\" from the signature {}",
+                step, signature
+            ),
+            Error::DepthOutOfRange { mv, size } => write!(
+                f,
+                "the move {} turns slices {} to {}, but a {}x{}x{} cube only has {} slices",
+                mv, mv.start_depth, mv.end_depth, size, size, size, size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<MoveSequenceParseError> for Error {
+    fn from(error: MoveSequenceParseError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+impl From<FaceletError> for Error {
+    fn from(error: FaceletError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::{Cube, MoveSequence};
+
+    #[test]
+    fn messages() {
+        let error: Error = "R X".parse::<MoveSequence>().unwrap_err().into();
+        assert_eq!(
+            error.to_string(),
+            "could not parse move 2: 'X' is not a face or slice"
+        );
+        let error: Error = Cube::from_facelets("UUU").unwrap_err().into();
+        assert_eq!(
+            error,
+            Error::Parse("expected 54 facelets, but found 3".to_string())
+        );
+        assert_eq!(
+            Error::InvalidState.to_string(),
+            "the cube state is not a valid arrangement of pieces"
+        );
+
+        // The error should be usable as a boxed standard library error.
+        let boxed: Box<dyn std::error::Error> = Box::new(Error::Unsolvable);
+        assert_eq!(boxed.to_string(), "the cube cannot be solved");
+    }
+}
@@ -31,6 +31,53 @@ pub trait Group: InverseSemigroup + Unital + Eq + Clone + Sized {
         }
         i
     }
+
+    /// Returns this element composed with itself `n` times.
+    /// Negative powers are powers of the inverse, and the zeroth power is the identity.
+    fn pow(&self, n: i64) -> Self {
+        let mut base = if n < 0 {
+            self.inverse()
+        } else {
+            self.clone()
+        };
+        let mut n = n.unsigned_abs();
+        let mut result = Self::identity();
+        // Exponentiation by squaring: consume the bits of `n` from least significant to most.
+        while n > 0 {
+            if n % 2 == 1 {
+                result = result.op(base.clone());
+            }
+            base = base.clone().op(base);
+            n /= 2;
+        }
+        result
+    }
+
+    /// Returns the conjugate of this element by `by`.
+    /// Reading left to right in the order the elements are performed, as in move notation,
+    /// this is `by`, then `self`, then the inverse of `by`.
+    ///
+    /// Since [Magma::op] performs its right operand first,
+    /// this is `by.inverse().op(self).op(by)`.
+    /// For example, if `self` is `U` and `by` is `R`, the conjugate is the move sequence `R U R'`.
+    fn conjugate(&self, by: &Self) -> Self {
+        by.inverse().op(self.clone()).op(by.clone())
+    }
+
+    /// Returns the commutator `[self, other]`.
+    /// Reading left to right in the order the elements are performed, as in move notation,
+    /// this is `self`, then `other`, then the inverse of `self`, then the inverse of `other`.
+    ///
+    /// Since [Magma::op] performs its right operand first,
+    /// this is `other.inverse().op(self.inverse()).op(other).op(self)`.
+    /// For example, if `self` is `R` and `other` is `U`, the commutator is the move sequence `R U R' U'`.
+    fn commutator(&self, other: &Self) -> Self {
+        other
+            .inverse()
+            .op(self.inverse())
+            .op(other.clone())
+            .op(self.clone())
+    }
 }
 impl<G: InverseSemigroup + Unital + Eq + Clone + Sized> Group for G {}
 
@@ -41,6 +88,22 @@ pub trait GroupAction<S>: Group + Sized {
     fn unact(&self, s: &S) -> S {
         self.inverse().act(s)
     }
+
+    /// Repeatedly acts on `start` until it returns to `start`, giving the cycle that contains it.
+    /// The first element of the orbit is `start` itself.
+    fn orbit(&self, start: &S) -> Vec<S>
+    where
+        S: PartialEq + Clone,
+    {
+        let mut orbit = vec![start.clone()];
+        let mut current = self.act(start);
+        while current != *start {
+            let next = self.act(&current);
+            orbit.push(current);
+            current = next;
+        }
+        orbit
+    }
 }
 
 pub trait Enumerable: Sized {
@@ -96,7 +159,13 @@ impl Default for TrivialGroup {
 pub struct CyclicGroup<const K: u8>(u8);
 
 impl<const K: u8> CyclicGroup<K> {
+    /// Evaluating this constant fails to compile if `K` is zero, since there is no cyclic group of order zero.
+    /// Every way of constructing an element evaluates it.
+    const ORDER_IS_POSITIVE: () = assert!(K >= 1, "a cyclic group must have an element");
+
+    /// The element `value` reduced modulo `K`.
     pub fn new(value: u8) -> Self {
+        let () = Self::ORDER_IS_POSITIVE;
         Self(value % K)
     }
 
@@ -105,26 +174,63 @@ impl<const K: u8> CyclicGroup<K> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl<const K: u8> serde::Serialize for CyclicGroup<K> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+/// The value must be in the range `0..K`.
+#[cfg(feature = "serde")]
+impl<'de, const K: u8> serde::Deserialize<'de> for CyclicGroup<K> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let () = Self::ORDER_IS_POSITIVE;
+        let value = u8::deserialize(deserializer)?;
+        if value < K {
+            Ok(Self(value))
+        } else {
+            Err(serde::de::Error::custom(format!(
+                "the value {} is not less than {}",
+                value, K
+            )))
+        }
+    }
+}
+
 impl<const K: u8> Magma for CyclicGroup<K> {
     fn op(self, other: Self) -> Self {
-        Self::new(self.0 + other.0)
+        // Add in a wider type, since the sum can exceed `u8::MAX` when `K` is larger than 128.
+        Self(((self.0 as u16 + other.0 as u16) % K as u16) as u8)
     }
 }
 
 impl<const K: u8> Semigroup for CyclicGroup<K> {}
 
+/// The inverse of `r` is `K - r`, except that the identity `0` is its own inverse.
 impl<const K: u8> InverseSemigroup for CyclicGroup<K> {
     fn inverse(&self) -> Self {
-        Self::new(K - self.get_value())
+        if self.0 == 0 {
+            *self
+        } else {
+            Self(K - self.0)
+        }
     }
 }
 
 impl<const K: u8> Unital for CyclicGroup<K> {
     fn identity() -> Self {
+        let () = Self::ORDER_IS_POSITIVE;
         Self(0)
     }
 }
 
+impl<const K: u8> Default for CyclicGroup<K> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
 impl<const K: u8> Display for CyclicGroup<K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(&self.0, f)
@@ -135,7 +241,7 @@ impl<const K: u8> Display for CyclicGroup<K> {
 ///
 /// Note that this symmetric group acts like a group, that is, in cycle notation,
 /// `(a b) (b c) = (a b c)` - the symmetries act in reverse order.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct SymmetricGroup<S>
 where
     S: Enumerable,
@@ -146,6 +252,64 @@ where
     map: [S; S::N],
 }
 
+/// Splits the permutation of `0..n` that maps `i` to `image(i)` into disjoint cycles.
+/// Each cycle starts with its smallest element, and fixed points are included as cycles of length one.
+fn cycle_decomposition(n: usize, image: impl Fn(usize) -> usize) -> Vec<Vec<usize>> {
+    let mut visited = vec![false; n];
+    let mut cycles = Vec::new();
+    for start in 0..n {
+        if visited[start] {
+            continue;
+        }
+        let mut cycle = Vec::new();
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            cycle.push(i);
+            i = image(i);
+        }
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The least common multiple of two positive integers.
+pub(crate) fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+/// Computes the sign of the permutation of `0..n` that maps `i` to `image(i)`.
+fn sign(n: usize, image: impl Fn(usize) -> usize) -> i8 {
+    // A cycle of length `k` is a product of `k - 1` transpositions.
+    let even_length_cycles = cycle_decomposition(n, image)
+        .into_iter()
+        .filter(|cycle| cycle.len() % 2 == 0)
+        .count();
+    if even_length_cycles % 2 == 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Joins already formatted cycles, writing the identity as `()`.
+fn join_cycles(cycles: impl Iterator<Item = String>) -> String {
+    let result = cycles.collect::<String>();
+    if result.is_empty() {
+        "()".to_string()
+    } else {
+        result
+    }
+}
+
 impl<S> SymmetricGroup<S>
 where
     S: Enumerable,
@@ -155,6 +319,65 @@ where
     pub fn new_unchecked(map: [S; S::N]) -> Self {
         Self { map }
     }
+
+    /// Composes the given disjoint cycles into a permutation.
+    /// The cycle `[a, b, c]` maps `a` to `b`, `b` to `c`, and `c` to `a`, as in [SymmetricGroup::cycles],
+    /// and elements that are in no cycle are fixed.
+    /// Returns `None` if any element appears more than once.
+    pub fn from_cycles(cycles: &[&[S]]) -> Option<Self>
+    where
+        S: Clone,
+    {
+        let mut seen = [false; S::N];
+        let mut map = S::enumerate();
+        for cycle in cycles {
+            for (i, s) in cycle.iter().enumerate() {
+                if std::mem::replace(&mut seen[s.index()], true) {
+                    return None;
+                }
+                map[s.index()] = cycle[(i + 1) % cycle.len()].clone();
+            }
+        }
+        Some(Self { map })
+    }
+
+    /// Returns `1` if this permutation is even, and `-1` if it is odd.
+    ///
+    /// A permutation is even if it can be written as a product of an even number of transpositions.
+    /// Equivalently, it is even if its cycle decomposition has an even number of cycles of even length.
+    /// The sign is multiplicative, so `a.op(b).sign() == a.sign() * b.sign()`.
+    pub fn sign(&self) -> i8 {
+        sign(S::N, |i| self.map[i].index())
+    }
+
+    /// Returns the order of this permutation, the least common multiple of its cycle lengths.
+    /// This agrees with [Group::order], but does not need to compose the permutation repeatedly.
+    pub fn cycle_order(&self) -> usize {
+        cycle_decomposition(S::N, |i| self.map[i].index())
+            .into_iter()
+            .fold(1, |order, cycle| lcm(order, cycle.len()))
+    }
+
+    /// Writes this permutation in disjoint cycle notation, such as `(UR UL UB)(FR FL)`.
+    /// The cycle `(a b c)` maps `a` to `b`, `b` to `c`, and `c` to `a`.
+    /// Fixed points are omitted, and the identity is written `()`.
+    pub fn cycles(&self) -> String
+    where
+        S: Display,
+    {
+        join_cycles(
+            cycle_decomposition(S::N, |i| self.map[i].index())
+                .into_iter()
+                .filter(|cycle| cycle.len() > 1)
+                .map(|cycle| {
+                    let elements = cycle
+                        .into_iter()
+                        .map(|i| S::from_index(i).to_string())
+                        .collect::<Vec<_>>();
+                    format!("({})", elements.join(" "))
+                }),
+        )
+    }
 }
 
 impl<S> Default for SymmetricGroup<S>
@@ -245,6 +468,65 @@ where
     }
 }
 
+/// Serializes the permutation as the list of indices that each element is mapped to,
+/// in the order given by [Enumerable::enumerate].
+#[cfg(feature = "serde")]
+impl<S> serde::Serialize for SymmetricGroup<S>
+where
+    S: Enumerable,
+    [(); S::N]: ,
+{
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.map.iter().map(|s| s.index()))
+    }
+}
+
+/// The group axioms are not assumed: the list must contain each index exactly once.
+#[cfg(feature = "serde")]
+impl<'de, S> serde::Deserialize<'de> for SymmetricGroup<S>
+where
+    S: Enumerable,
+    [(); S::N]: ,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let indices = Vec::<usize>::deserialize(deserializer)?;
+        check_permutation(S::N, &indices)?;
+        let map = indices.into_iter().map(S::from_index).collect::<Vec<_>>();
+        Ok(Self {
+            map: map
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("the length was checked")),
+        })
+    }
+}
+
+/// Checks that the deserialized indices are a permutation of `0..n`.
+#[cfg(feature = "serde")]
+fn check_permutation<E: serde::de::Error>(n: usize, indices: &[usize]) -> Result<(), E> {
+    if indices.len() != n {
+        return Err(E::invalid_length(
+            indices.len(),
+            &format!("a permutation of {} elements", n).as_str(),
+        ));
+    }
+    let mut seen = vec![false; n];
+    for &i in indices {
+        if i >= n || seen[i] {
+            return Err(E::custom(format!(
+                "{} is not a permutation of {} elements",
+                indices
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                n
+            )));
+        }
+        seen[i] = true;
+    }
+    Ok(())
+}
+
 impl<S> Debug for SymmetricGroup<S>
 where
     S: Enumerable + Clone + Display + Debug,
@@ -262,7 +544,7 @@ where
 ///
 /// Note that this symmetric group acts like a group, that is, in cycle notation,
 /// `(a b) (b c) = (a b c)` - the symmetries act in reverse order.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct OrientedSymmetricGroup<S, const K: u8>
 where
     S: Enumerable,
@@ -282,6 +564,121 @@ where
     pub fn new_unchecked(map: [(S, CyclicGroup<K>); S::N]) -> Self {
         Self { map }
     }
+
+    /// Composes the given disjoint cycles into a permutation, as in [SymmetricGroup::from_cycles].
+    /// Each element is paired with the orientation that it gains as it moves to the next element in its cycle.
+    /// Returns `None` if any element appears more than once.
+    pub fn from_cycles(cycles: &[&[(S, CyclicGroup<K>)]]) -> Option<Self>
+    where
+        S: Clone,
+    {
+        let mut seen = [false; S::N];
+        let mut map = S::enumerate().map(|s| (s, CyclicGroup::identity()));
+        for cycle in cycles {
+            for (i, (s, r)) in cycle.iter().enumerate() {
+                if std::mem::replace(&mut seen[s.index()], true) {
+                    return None;
+                }
+                map[s.index()] = (cycle[(i + 1) % cycle.len()].0.clone(), *r);
+            }
+        }
+        Some(Self { map })
+    }
+
+    /// The position that the element `s` is moved to, ignoring its orientation.
+    /// This is the position given by [GroupAction::act].
+    pub fn position_of(&self, s: &S) -> S
+    where
+        S: Clone,
+    {
+        self.map[s.index()].0.clone()
+    }
+
+    /// The orientation of whichever element has been moved into the position `home`,
+    /// measured relative to `home` rather than to where that element started.
+    /// This is the orientation given by [GroupAction::unact] on `(home, 0)`,
+    /// but does not need to invert the whole permutation.
+    pub fn orientation_at(&self, home: &S) -> CyclicGroup<K>
+    where
+        S: Eq,
+    {
+        self.map
+            .iter()
+            .find(|(s, _)| s == home)
+            .map(|(_, r)| r.inverse())
+            .expect("every position is the image of some element")
+    }
+
+    /// Returns `1` if the underlying permutation is even, and `-1` if it is odd.
+    /// Orientations are ignored, so this is the sign of the positions alone, as in [SymmetricGroup::sign].
+    pub fn sign(&self) -> i8 {
+        sign(S::N, |i| self.map[i].0.index())
+    }
+
+    /// The sum of the orientations of every element.
+    pub fn total_orientation(&self) -> CyclicGroup<K> {
+        self.map
+            .iter()
+            .fold(CyclicGroup::identity(), |total, (_, r)| total.op(*r))
+    }
+
+    /// Returns the order of this permutation.
+    /// This agrees with [Group::order], but does not need to compose the permutation repeatedly.
+    ///
+    /// If going once around a cycle of length `n` changes the orientation of each element by `r`,
+    /// the elements of that cycle return to their original state after `n * K / gcd(r, K)` steps.
+    /// The order is the least common multiple of these values.
+    pub fn cycle_order(&self) -> usize {
+        cycle_decomposition(S::N, |i| self.map[i].0.index())
+            .into_iter()
+            .fold(1, |order, cycle| {
+                let twist = cycle
+                    .iter()
+                    .fold(CyclicGroup::<K>::identity(), |total, &i| {
+                        total.op(self.map[i].1)
+                    })
+                    .get_value();
+                let twist_order = K as usize / gcd(twist as usize, K as usize);
+                lcm(order, cycle.len() * twist_order)
+            })
+    }
+
+    /// Writes this permutation in disjoint cycle notation, as in [SymmetricGroup::cycles].
+    /// If an element comes back to its starting point reoriented after going once around its cycle,
+    /// the cycle is annotated with that change in orientation: `+` for one step, `-` for `K - 1` steps,
+    /// and `+n` otherwise. For example, a corner twisted in place is written `(FUR+)`.
+    /// Fixed points are omitted only if they are not reoriented.
+    pub fn cycles(&self) -> String
+    where
+        S: Display,
+    {
+        join_cycles(
+            cycle_decomposition(S::N, |i| self.map[i].0.index())
+                .into_iter()
+                .filter_map(|cycle| {
+                    let twist = cycle
+                        .iter()
+                        .fold(CyclicGroup::<K>::identity(), |total, &i| {
+                            total.op(self.map[i].1)
+                        })
+                        .get_value();
+                    if cycle.len() == 1 && twist == 0 {
+                        return None;
+                    }
+                    let annotation = match twist {
+                        0 => String::new(),
+                        1 => "+".to_string(),
+                        n if n == K - 1 => "-".to_string(),
+                        n => format!("+{}", n),
+                    };
+                    let elements = cycle
+                        .into_iter()
+                        .map(|i| S::from_index(i).to_string())
+                        .collect::<Vec<_>>();
+                    Some(format!("({}{})", elements.join(" "), annotation))
+                }),
+        )
+    }
 }
 
 impl<S, const K: u8> Default for OrientedSymmetricGroup<S, K>
@@ -385,6 +782,49 @@ where
     }
 }
 
+/// Serializes the permutation as a list of pairs, one for each element in the order given by [Enumerable::enumerate].
+/// Each pair contains the index that the element is mapped to, and the orientation that it gains.
+#[cfg(feature = "serde")]
+impl<S, const K: u8> serde::Serialize for OrientedSymmetricGroup<S, K>
+where
+    S: Enumerable,
+    [(); S::N]: ,
+{
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_seq(self.map.iter().map(|(s, r)| (s.index(), r.get_value())))
+    }
+}
+
+/// The group axioms are not assumed: the indices must form a permutation,
+/// and each orientation must be less than `K`.
+#[cfg(feature = "serde")]
+impl<'de, S, const K: u8> serde::Deserialize<'de> for OrientedSymmetricGroup<S, K>
+where
+    S: Enumerable,
+    [(); S::N]: ,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pairs = Vec::<(usize, u8)>::deserialize(deserializer)?;
+        let indices = pairs.iter().map(|&(i, _)| i).collect::<Vec<_>>();
+        check_permutation(S::N, &indices)?;
+        if let Some(&(_, r)) = pairs.iter().find(|&&(_, r)| r >= K) {
+            return Err(serde::de::Error::custom(format!(
+                "the orientation {} is not less than {}",
+                r, K
+            )));
+        }
+        let map = pairs
+            .into_iter()
+            .map(|(i, r)| (S::from_index(i), CyclicGroup::new(r)))
+            .collect::<Vec<_>>();
+        Ok(Self {
+            map: map
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("the length was checked")),
+        })
+    }
+}
+
 impl<S, const K: u8> Debug for OrientedSymmetricGroup<S, K>
 where
     S: Enumerable + Clone + Display + Debug,
@@ -396,3 +836,264 @@ where
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cube::FaceType::{self, *};
+
+    fn faces(map: [FaceType; 6]) -> SymmetricGroup<FaceType> {
+        SymmetricGroup::new_unchecked(map)
+    }
+
+    #[test]
+    fn sign() {
+        let identity = SymmetricGroup::<FaceType>::identity();
+        assert_eq!(identity.sign(), 1);
+
+        let transposition = faces([R, F, U, B, L, D]);
+        assert_eq!(transposition.sign(), -1);
+
+        let three_cycle = faces([R, U, F, B, L, D]);
+        assert_eq!(three_cycle.sign(), 1);
+        let four_cycle = faces([R, U, B, F, L, D]);
+        assert_eq!(four_cycle.sign(), -1);
+
+        // The sign is multiplicative.
+        assert_eq!(transposition.op(three_cycle).sign(), -1);
+        assert_eq!(transposition.op(four_cycle).sign(), 1);
+
+        // Orientations do not affect the sign.
+        let oriented = OrientedSymmetricGroup::<FaceType, 3>::new_unchecked(
+            [R, F, U, B, L, D].map(|face| (face, CyclicGroup::new(1))),
+        );
+        assert_eq!(oriented.sign(), -1);
+        assert_eq!(OrientedSymmetricGroup::<FaceType, 3>::identity().sign(), 1);
+    }
+
+    #[test]
+    fn pow() {
+        let g = OrientedSymmetricGroup::<FaceType, 3>::new_unchecked([
+            (R, CyclicGroup::new(1)),
+            (U, CyclicGroup::new(0)),
+            (F, CyclicGroup::new(0)),
+            (L, CyclicGroup::new(0)),
+            (D, CyclicGroup::new(0)),
+            (B, CyclicGroup::new(0)),
+        ]);
+        let e = OrientedSymmetricGroup::<FaceType, 3>::identity();
+        assert_eq!(g.pow(0), e);
+        assert_eq!(g.pow(1), g);
+        assert_eq!(g.pow(g.order() as i64), e);
+        assert_eq!(g.pow(-1), g.inverse());
+
+        // Check against repeated composition.
+        let mut x = e;
+        for n in 0..20 {
+            assert_eq!(g.pow(n), x);
+            assert_eq!(g.pow(-n), x.inverse());
+            x = x.op(g);
+        }
+
+        let c = CyclicGroup::<5>::new(2);
+        assert_eq!(c.pow(3), CyclicGroup::new(1));
+        assert_eq!(c.pow(-1), c.inverse());
+        assert_eq!(c.pow(1_000_000_007), CyclicGroup::new(4));
+    }
+
+    #[test]
+    fn cyclic_inverse() {
+        assert_eq!(CyclicGroup::<3>::default(), CyclicGroup::identity());
+        assert_eq!(CyclicGroup::<3>::new(0).inverse(), CyclicGroup::identity());
+        assert_eq!(CyclicGroup::<3>::new(1).inverse(), CyclicGroup::new(2));
+        for value in 0..3 {
+            let r = CyclicGroup::<3>::new(value);
+            assert_eq!(r.op(r.inverse()), CyclicGroup::identity());
+            assert_eq!(r.inverse().op(r), CyclicGroup::identity());
+        }
+        for value in 0..200 {
+            let r = CyclicGroup::<200>::new(value);
+            assert_eq!(r.op(r.inverse()), CyclicGroup::identity());
+        }
+        // Sums are reduced without overflowing.
+        assert_eq!(
+            CyclicGroup::<200>::new(150).op(CyclicGroup::new(100)),
+            CyclicGroup::new(50)
+        );
+        assert_eq!(CyclicGroup::<1>::new(7), CyclicGroup::identity());
+    }
+
+    #[test]
+    fn order() {
+        let permutations = [
+            faces([F, R, U, B, L, D]),
+            faces([R, F, U, B, L, D]),
+            faces([R, U, F, B, L, D]),
+            faces([R, U, B, F, L, D]),
+            faces([R, F, L, D, B, U]),
+            faces([R, U, F, L, D, B]),
+        ];
+        for g in permutations {
+            assert_eq!(g.cycle_order(), g.order());
+        }
+        assert_eq!(permutations[5].order(), 3);
+
+        let oriented = |orientations: [u8; 6]| {
+            OrientedSymmetricGroup::<FaceType, 3>::new_unchecked([
+                (R, CyclicGroup::new(orientations[0])),
+                (U, CyclicGroup::new(orientations[1])),
+                (F, CyclicGroup::new(orientations[2])),
+                (B, CyclicGroup::new(orientations[3])),
+                (L, CyclicGroup::new(orientations[4])),
+                (D, CyclicGroup::new(orientations[5])),
+            ])
+        };
+        let permutations = [
+            oriented([0, 0, 0, 0, 0, 0]),
+            oriented([1, 2, 0, 0, 0, 0]),
+            oriented([1, 0, 0, 0, 0, 0]),
+            oriented([1, 1, 1, 0, 0, 0]),
+            oriented([0, 0, 0, 0, 1, 0]),
+            oriented([2, 0, 0, 1, 0, 2]),
+        ];
+        for g in permutations {
+            assert_eq!(g.cycle_order(), g.order());
+        }
+        // A 3-cycle whose elements are twisted once on each pass has order 9.
+        assert_eq!(permutations[2].order(), 9);
+        assert_eq!(permutations[3].order(), 3);
+        assert_eq!(permutations[4].order(), 3);
+        assert_eq!(permutations[5].order(), 9);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let g = faces([R, U, F, B, L, D]);
+        let json = serde_json::to_string(&g).unwrap();
+        assert_eq!(json, "[1,2,0,3,4,5]");
+        assert_eq!(serde_json::from_str::<SymmetricGroup<FaceType>>(&json).unwrap(), g);
+
+        // Maps that are not permutations are rejected.
+        for json in ["[0,1,2,3,4]", "[0,1,2,3,4,4]", "[0,1,2,3,4,6]"] {
+            assert!(serde_json::from_str::<SymmetricGroup<FaceType>>(json).is_err());
+        }
+
+        let g = OrientedSymmetricGroup::<FaceType, 3>::new_unchecked([
+            (R, CyclicGroup::new(1)),
+            (F, CyclicGroup::new(2)),
+            (U, CyclicGroup::new(0)),
+            (B, CyclicGroup::new(0)),
+            (L, CyclicGroup::new(0)),
+            (D, CyclicGroup::new(0)),
+        ]);
+        let json = serde_json::to_string(&g).unwrap();
+        assert_eq!(json, "[[1,1],[0,2],[2,0],[3,0],[4,0],[5,0]]");
+        assert_eq!(
+            serde_json::from_str::<OrientedSymmetricGroup<FaceType, 3>>(&json).unwrap(),
+            g
+        );
+        let json = "[[1,1],[0,3],[2,0],[3,0],[4,0],[5,0]]";
+        assert!(serde_json::from_str::<OrientedSymmetricGroup<FaceType, 3>>(json).is_err());
+    }
+
+    #[test]
+    fn cycles() {
+        assert_eq!(SymmetricGroup::<FaceType>::identity().cycles(), "()");
+        assert_eq!(faces([R, F, U, B, L, D]).cycles(), "(F R)");
+        assert_eq!(faces([F, U, R, L, D, B]).cycles(), "(R U)(B L D)");
+
+        let twist = |r| {
+            OrientedSymmetricGroup::<FaceType, 3>::new_unchecked(
+                FaceType::enumerate().map(|face| {
+                    if face == U {
+                        (face, CyclicGroup::new(r))
+                    } else {
+                        (face, CyclicGroup::identity())
+                    }
+                }),
+            )
+        };
+        assert_eq!(twist(0).cycles(), "()");
+        assert_eq!(twist(1).cycles(), "(U+)");
+        assert_eq!(twist(2).cycles(), "(U-)");
+
+        // Only the net change in orientation around each cycle is shown.
+        let oriented = OrientedSymmetricGroup::<FaceType, 3>::new_unchecked([
+            (R, CyclicGroup::new(1)),
+            (U, CyclicGroup::new(2)),
+            (F, CyclicGroup::new(0)),
+            (L, CyclicGroup::new(1)),
+            (D, CyclicGroup::new(0)),
+            (B, CyclicGroup::new(0)),
+        ]);
+        assert_eq!(oriented.cycles(), "(F R U)(B L D+)");
+    }
+
+    #[test]
+    fn position_and_orientation() {
+        let oriented = OrientedSymmetricGroup::<FaceType, 3>::new_unchecked([
+            (R, CyclicGroup::new(1)),
+            (U, CyclicGroup::new(2)),
+            (F, CyclicGroup::new(0)),
+            (L, CyclicGroup::new(1)),
+            (D, CyclicGroup::new(0)),
+            (B, CyclicGroup::new(0)),
+        ]);
+        assert_eq!(oriented.position_of(&F), R);
+        assert_eq!(oriented.position_of(&D), B);
+        // F moves to R, gaining one twist, so the piece at R is twisted once relative to R.
+        assert_eq!(oriented.orientation_at(&R), CyclicGroup::new(2));
+        assert_eq!(oriented.orientation_at(&F), CyclicGroup::new(0));
+        for face in FaceType::enumerate() {
+            let home = (face, CyclicGroup::identity());
+            assert_eq!(oriented.position_of(&face), oriented.act(&home).0);
+            assert_eq!(oriented.orientation_at(&face), oriented.unact(&home).1);
+        }
+    }
+
+    #[test]
+    fn from_cycles() {
+        assert_eq!(
+            SymmetricGroup::<FaceType>::from_cycles(&[]),
+            Some(SymmetricGroup::identity())
+        );
+        assert_eq!(
+            SymmetricGroup::from_cycles(&[&[F, R]]),
+            Some(faces([R, F, U, B, L, D]))
+        );
+        assert_eq!(
+            SymmetricGroup::from_cycles(&[&[R, U], &[B, L, D]]),
+            Some(faces([F, U, R, L, D, B]))
+        );
+        // The cycles must be disjoint.
+        assert_eq!(SymmetricGroup::from_cycles(&[&[R, U], &[U, L]]), None);
+        assert_eq!(SymmetricGroup::from_cycles(&[&[R, U, R]]), None);
+
+        let oriented = OrientedSymmetricGroup::<FaceType, 3>::from_cycles(&[
+            &[
+                (F, CyclicGroup::new(1)),
+                (R, CyclicGroup::new(2)),
+                (U, CyclicGroup::new(0)),
+            ],
+            &[
+                (B, CyclicGroup::new(1)),
+                (L, CyclicGroup::new(0)),
+                (D, CyclicGroup::new(0)),
+            ],
+        ])
+        .unwrap();
+        assert_eq!(
+            oriented.act(&(F, CyclicGroup::new(1))),
+            (R, CyclicGroup::new(2))
+        );
+        assert_eq!(oriented.cycles(), "(F R U)(B L D+)");
+        assert_eq!(
+            OrientedSymmetricGroup::<FaceType, 3>::from_cycles(&[
+                &[(F, CyclicGroup::new(1))],
+                &[(F, CyclicGroup::new(2))],
+            ]),
+            None
+        );
+    }
+}
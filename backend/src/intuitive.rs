@@ -2,13 +2,15 @@ use instant::Instant;
 use std::{
     collections::{HashMap, VecDeque},
     hash::Hash,
+    marker::PhantomData,
+    sync::OnceLock,
 };
 
 use priority_queue::PriorityQueue;
 
 use crate::{
-    cube::MoveSequence,
-    group::{InverseSemigroup, Magma, Unital},
+    cube::{Move, MoveSequence, RotationType},
+    group::{CyclicGroup, Enumerable, InverseSemigroup, Magma, Unital},
     permute::CubePermutation3,
     utils::log,
 };
@@ -19,8 +21,15 @@ use crate::{
 pub struct SequenceGraph<S> {
     graph: HashMap<S, State<S>>,
     graph_name: &'static str,
+    /// The transitions into each signature, and the signatures they come from,
+    /// built the first time that [SequenceGraph::solve_single] searches backwards.
+    reverse_transitions: OnceLock<HashMap<S, Vec<(MoveSequence, S)>>>,
 }
 
+/// For each signature reached by a search through a [SequenceGraph], its distance from where the search started,
+/// and the move sequence and signature through which it was reached.
+type Reached<'a, S> = HashMap<&'a S, (u64, Option<(&'a MoveSequence, &'a S)>)>;
+
 #[derive(Debug)]
 struct State<S> {
     /// Given a sequence of moves, which node do we transition to?
@@ -33,6 +42,108 @@ struct State<S> {
 #[derive(Debug)]
 pub struct SequenceSolver<S> {
     node_info: HashMap<S, MoveSequence>,
+    /// For each signature, every transition towards the target signature that begins an optimal move sequence,
+    /// together with the signature that it leads to.
+    /// This is only filled in by [SequenceGraph::search_all_optimal].
+    optimal_transitions: HashMap<S, Vec<(MoveSequence, S)>>,
+}
+
+/// A signature with finitely many values, each of which has a different coordinate in `0..RANGE`.
+/// Solvers for these signatures can be stored in an [IndexedSequenceSolver].
+pub trait Indexable {
+    const RANGE: usize;
+
+    fn coordinate(&self) -> usize;
+}
+
+/// S is a 'signature' of the current cube state (see [SequenceGraph] for more info).
+/// This stores the same information as a [SequenceSolver], but looks up signatures by their coordinates,
+/// which is faster and uses much less memory when the signatures are [Indexable].
+#[derive(Debug)]
+pub struct IndexedSequenceSolver<S> {
+    node_info: Vec<Option<MoveSequence>>,
+    signature: PhantomData<S>,
+}
+
+/// What a lookup table builder is doing when it reports its progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressPhase {
+    /// Exploring cube states in [SequenceGraph::new_with_progress].
+    GeneratingGraph,
+    /// Finding the shortest move sequences in [SequenceGraph::search_with_progress].
+    SearchingGraph,
+    /// Trying each algorithm in [crate::algorithmic::AlgorithmicSolver::new_with_progress].
+    GeneratingAlgorithms,
+}
+
+/// Reports how far a lookup table builder has got, so that front ends can show a loading bar.
+/// Events are reported periodically, and once more when the phase is finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub name: &'static str,
+    pub phase: ProgressPhase,
+    /// The number of signatures (or algorithms) that have been processed so far.
+    pub nodes_expanded: usize,
+    /// The number of cube states (or algorithms) waiting to be processed.
+    pub queue_size: usize,
+    /// True for the last event of the phase.
+    pub finished: bool,
+}
+
+/// The number of cube states processed between progress events.
+const PROGRESS_INTERVAL: usize = 1024;
+
+/// The most move sequences returned by [SequenceSolver::solve_all_optimal],
+/// since the number of optimal move sequences can grow exponentially with their length.
+pub const MAX_OPTIMAL_SOLUTIONS: usize = 100;
+
+/// A generating set for a [SequenceGraph] containing every turn of each of the given faces:
+/// a clockwise quarter turn, a half turn, and an anticlockwise quarter turn.
+/// Slices and rotations such as `M` or `x` may also be given.
+///
+/// Panics if one of the faces is not a valid move.
+pub fn gen_set(faces: &[&str]) -> Vec<MoveSequence> {
+    faces
+        .iter()
+        .flat_map(|face| {
+            let mv = face
+                .parse::<Move>()
+                .unwrap_or_else(|_| panic!("{} is not a valid move", face));
+            let double = Move::new(mv.axis, RotationType::Double, mv.start_depth, mv.end_depth);
+            [mv, double, mv.inverse()]
+        })
+        .map(|mv| MoveSequence { moves: vec![mv] })
+        .collect()
+}
+
+/// Commonly used sets of moves that a [SequenceGraph] can be generated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveGroup {
+    /// `<R, U>`, which preserves edge orientation and the left two layers.
+    Ru,
+    /// `<R, U, F>`.
+    Ruf,
+    /// `<R, r, U, M>`, the moves used after the first block in the Roux method.
+    Roux,
+    /// Every face turn, as counted by the half turn metric.
+    Htm,
+}
+
+impl MoveGroup {
+    /// The faces whose turns are in this group.
+    pub fn faces(self) -> &'static [&'static str] {
+        match self {
+            MoveGroup::Ru => &["R", "U"],
+            MoveGroup::Ruf => &["R", "U", "F"],
+            MoveGroup::Roux => &["R", "r", "U", "M"],
+            MoveGroup::Htm => &["U", "D", "R", "L", "F", "B"],
+        }
+    }
+
+    /// Every turn of each face in this group. See [gen_set].
+    pub fn gen_set(self) -> Vec<MoveSequence> {
+        gen_set(self.faces())
+    }
 }
 
 impl<S> SequenceGraph<S>
@@ -42,44 +153,53 @@ where
     /// Create a new sequence graph from the given generating set.
     /// For each generated move sequence, we generate the signature of the resulting cube permutation.
     /// The signature function should generate the signature of a cube permutation.
+    /// Move sequences that turn layers a 3x3x3 cube does not have are left out of the generating set.
     pub fn new(
         graph_name: &'static str,
         gen_set: Vec<MoveSequence>,
         signature: impl Fn(CubePermutation3) -> S,
+    ) -> Self {
+        Self::new_with_progress(graph_name, gen_set, signature, |_| {})
+    }
+
+    /// Creates a new sequence graph as in [SequenceGraph::new], reporting its progress to the given callback.
+    pub fn new_with_progress(
+        graph_name: &'static str,
+        gen_set: Vec<MoveSequence>,
+        signature: impl Fn(CubePermutation3) -> S,
+        mut progress: impl FnMut(ProgressEvent),
     ) -> Self {
         let start_time = Instant::now();
 
         let mut this = Self {
             graph: HashMap::new(),
             graph_name,
+            reverse_transitions: OnceLock::new(),
         };
 
-        // Generate double and inverse moves.
-        let mut real_gen_set = gen_set
-            .iter()
-            .map(|mv| {
-                if mv.moves.len() > 1 {
-                    // Don't generate inverses etc. for full algorithms or conjugates.
-                    // These algorithms must, however, be reversed.
-                    // This is because the move sequences themselves will be reversed when solving as opposed to exploring.
-                    vec![mv.inverse()]
-                } else {
-                    vec![mv.inverse(), mv.clone().op(mv.clone()), mv.clone()]
-                }
-            })
-            .flatten()
-            .map(|mv| mv.canonicalise())
-            .filter(|mv| !mv.moves.is_empty())
+        let real_gen_set = real_gen_set(&gen_set)
+            .into_iter()
+            .filter_map(|seq| Some((CubePermutation3::from_move_sequence(seq.clone())?, seq)))
             .collect::<Vec<_>>();
-        real_gen_set.sort();
-        real_gen_set.dedup();
 
         let mut new_permutations = VecDeque::new();
         // Initialise the list of permutations with the identity,
         // so we have a source to explore from.
         new_permutations.push_back(CubePermutation3::identity());
 
+        let mut iterations = 0;
         while let Some(permutation) = new_permutations.pop_front() {
+            iterations += 1;
+            if iterations % PROGRESS_INTERVAL == 0 {
+                progress(ProgressEvent {
+                    name: graph_name,
+                    phase: ProgressPhase::GeneratingGraph,
+                    nodes_expanded: this.graph.len(),
+                    queue_size: new_permutations.len(),
+                    finished: false,
+                });
+            }
+
             let current_signature = signature(permutation);
             this.graph
                 .entry(current_signature.clone())
@@ -89,8 +209,7 @@ where
                     };
                     // Try each move in the generating set.
                     // Check what the signature is.
-                    for seq in &real_gen_set {
-                        let seq_perm = CubePermutation3::from_move_sequence(seq.clone());
+                    for (seq_perm, seq) in &real_gen_set {
                         let new_permutation = seq_perm.op(permutation);
                         let new_signature = signature(new_permutation);
 
@@ -103,6 +222,14 @@ where
                 });
         }
 
+        progress(ProgressEvent {
+            name: graph_name,
+            phase: ProgressPhase::GeneratingGraph,
+            nodes_expanded: this.graph.len(),
+            queue_size: 0,
+            finished: true,
+        });
+
         let end_time = Instant::now();
         let duration = end_time - start_time;
         log!(
@@ -115,6 +242,97 @@ where
         this
     }
 
+    /// Creates the same sequence graph as [SequenceGraph::new], exploring the cube states on many threads.
+    /// The graph is explored one layer at a time: the states in each layer are expanded in parallel,
+    /// then the new signatures they reach are collected into the next layer on the current thread.
+    #[cfg(feature = "rayon")]
+    pub fn new_parallel(
+        graph_name: &'static str,
+        gen_set: Vec<MoveSequence>,
+        signature: impl Fn(CubePermutation3) -> S + Sync,
+    ) -> Self
+    where
+        S: Send + Sync,
+    {
+        use rayon::prelude::*;
+        use std::collections::HashSet;
+
+        let start_time = Instant::now();
+
+        let mut this = Self {
+            graph: HashMap::new(),
+            graph_name,
+            reverse_transitions: OnceLock::new(),
+        };
+
+        let real_gen_set = real_gen_set(&gen_set)
+            .into_iter()
+            .filter_map(|seq| Some((CubePermutation3::from_move_sequence(seq.clone())?, seq)))
+            .collect::<Vec<_>>();
+
+        let identity = CubePermutation3::identity();
+        let mut layer = vec![(signature(identity), identity)];
+        while !layer.is_empty() {
+            let expanded = layer
+                .into_par_iter()
+                .map(|(current_signature, permutation)| {
+                    let mut state = State {
+                        transitions: HashMap::default(),
+                    };
+                    let mut reached = Vec::new();
+                    for (seq_perm, seq) in &real_gen_set {
+                        let new_permutation = seq_perm.op(permutation);
+                        let new_signature = signature(new_permutation);
+
+                        if new_signature != current_signature {
+                            reached.push((new_signature.clone(), new_permutation));
+                            state.transitions.insert(seq.clone(), new_signature);
+                        }
+                    }
+                    (current_signature, state, reached)
+                })
+                .collect::<Vec<_>>();
+
+            let mut next_layer = Vec::new();
+            let mut next_signatures = HashSet::new();
+            for (current_signature, state, reached) in expanded {
+                this.graph.insert(current_signature, state);
+                next_layer.extend(reached);
+            }
+            next_layer.retain(|(new_signature, _)| {
+                !this.graph.contains_key(new_signature)
+                    && next_signatures.insert(new_signature.clone())
+            });
+            layer = next_layer;
+        }
+
+        let end_time = Instant::now();
+        let duration = end_time - start_time;
+        log!(
+            "Generated sequence graph {} with {} nodes in {} ms on {} threads",
+            graph_name,
+            this.graph.len(),
+            duration.as_millis(),
+            rayon::current_num_threads()
+        );
+
+        this
+    }
+
+    /// The number of signatures in the graph.
+    pub fn len(&self) -> usize {
+        self.graph.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graph.is_empty()
+    }
+
+    /// The signatures in the graph, in no particular order.
+    pub fn signatures(&self) -> impl Iterator<Item = &S> {
+        self.graph.keys()
+    }
+
     /// Searches the sequence graph using Dijkstra's algorithm
     /// to provide (essentially) a lookup table containing the shortest move sequences that will
     /// repair the cube to a specific 'target' signature.
@@ -123,70 +341,146 @@ where
     /// Lower is better.
     /// The target signature should have a metric of zero.
     /// A typical example of a metric is STM, or 'slice turn metric'.
+    ///
+    /// The metric is given each whole candidate move sequence in the order it will be performed,
+    /// so it may depend on pairs of adjacent moves, as [crate::metric::pairwise] metrics do.
+    /// Only one move sequence is kept for each signature, so such a metric is not always minimised exactly.
     pub fn search(
         &self,
         target_signature: S,
         metric: impl Fn(&MoveSequence) -> u64,
+    ) -> SequenceSolver<S> {
+        self.search_with_progress(target_signature, metric, |_| {})
+    }
+
+    /// Searches the sequence graph as in [SequenceGraph::search], reporting its progress to the given callback.
+    pub fn search_with_progress(
+        &self,
+        target_signature: S,
+        metric: impl Fn(&MoveSequence) -> u64,
+        progress: impl FnMut(ProgressEvent),
+    ) -> SequenceSolver<S> {
+        self.dijkstra(target_signature, metric, false, progress)
+    }
+
+    /// Searches the sequence graph as in [SequenceGraph::search], but also remembers every optimal route
+    /// to each signature, so that [SequenceSolver::solve_all_optimal] can list every optimal move sequence.
+    /// This uses more memory than [SequenceGraph::search].
+    ///
+    /// Routes are compared by applying the metric to each transition separately, so it should be additive,
+    /// which is true of every metric in [crate::metric].
+    pub fn search_all_optimal(
+        &self,
+        target_signature: S,
+        metric: impl Fn(&MoveSequence) -> u64,
+    ) -> SequenceSolver<S> {
+        self.dijkstra(target_signature, metric, true, |_| {})
+    }
+
+    /// Performs the search for [SequenceGraph::search_with_progress] and [SequenceGraph::search_all_optimal].
+    fn dijkstra(
+        &self,
+        target_signature: S,
+        metric: impl Fn(&MoveSequence) -> u64,
+        all_optimal: bool,
+        mut progress: impl FnMut(ProgressEvent),
     ) -> SequenceSolver<S> {
         let start_time = Instant::now();
 
         // The set of unvisited nodes, ordered by current distance.
-        // The priority of an element is given by `std::u64::MAX` minus the distance.
+        // The priority of an element is given by `u64::MAX` minus the distance.
         let mut unvisited_queue = self
             .graph
             .keys()
             .map(|s| (s, 0))
             .collect::<PriorityQueue<_, _>>();
 
-        // Stores the tentative move sequences used to reach each unvisited node
+        // Stores the tentative move sequences that repair each unvisited node into the target signature,
         // with the distance stored in the unvisited queue.
+        // They are stored in the order they will be performed, so that the metric can
+        // take into account which moves are next to each other.
         let mut unvisited_move_sequences = HashMap::new();
 
         // Add in the unvisited queue entry for the target signature.
         // It should have distance zero, so max priority.
-        unvisited_queue.change_priority(&target_signature, std::u64::MAX);
+        unvisited_queue.change_priority(&target_signature, u64::MAX);
         unvisited_move_sequences.insert(&target_signature, MoveSequence { moves: Vec::new() });
 
         // The distance and move sequence for each visited signature node.
         // Node info and the unvisited queue are mutually exclusive.
         // Their union is the set of all S.
-        // Note that the given move sequence is the reverse of the move sequence in unvisited_move_sequences:
-        // this move sequence will repair the cube into the target signature, wheread unvisited_move_sequences
-        // will convert the cube from the target signature into the given signature.
         let mut node_info = HashMap::new();
 
+        // The transitions from each unvisited node towards the target that achieve its current distance,
+        // and the same for each visited node.
+        let mut unvisited_transitions = HashMap::<&S, Vec<(MoveSequence, S)>>::new();
+        let mut optimal_transitions = HashMap::new();
+
         while let Some((signature, _priority)) = unvisited_queue.pop() {
-            // let distance = std::u64::MAX - priority;
+            // let distance = u64::MAX - priority;
             let move_sequence = unvisited_move_sequences
                 .remove(signature)
                 .expect("node was not given a move sequence but had max search priority");
 
-            node_info.insert(signature.clone(), move_sequence.inverse());
+            node_info.insert(signature.clone(), move_sequence.clone());
+            if let Some(transitions) = unvisited_transitions.remove(signature) {
+                optimal_transitions.insert(signature.clone(), transitions);
+            }
+            if node_info.len() % PROGRESS_INTERVAL == 0 {
+                progress(ProgressEvent {
+                    name: self.graph_name,
+                    phase: ProgressPhase::SearchingGraph,
+                    nodes_expanded: node_info.len(),
+                    queue_size: unvisited_queue.len(),
+                    finished: false,
+                });
+            }
 
             // For the current node, consider all of its unvisited neighbours.
             for (transition_sequence, new_signature) in &self.graph[signature].transitions {
                 if let Some(&existing_priority) = unvisited_queue.get_priority(new_signature) {
                     // This is an unvisited node.
-
+                    // To repair it, undo the transition, then repair the current node.
+                    let reverse_transition = transition_sequence.inverse();
                     let tentative_move_sequence = MoveSequence {
-                        moves: move_sequence
+                        moves: reverse_transition
                             .moves
                             .iter()
-                            .chain(&transition_sequence.moves)
+                            .chain(&move_sequence.moves)
                             .cloned()
                             .collect(),
                     };
                     let tentative_metric = metric(&tentative_move_sequence);
-                    let tentative_priority = std::u64::MAX - tentative_metric;
+                    let tentative_priority = u64::MAX - tentative_metric;
                     if tentative_priority > existing_priority {
                         // We found a better route to this signature.
                         unvisited_queue.change_priority(new_signature, tentative_priority);
                         unvisited_move_sequences.insert(new_signature, tentative_move_sequence);
+                        if all_optimal {
+                            unvisited_transitions.insert(
+                                new_signature,
+                                vec![(reverse_transition, signature.clone())],
+                            );
+                        }
+                    } else if all_optimal && tentative_priority == existing_priority {
+                        // We found another route to this signature that is just as good.
+                        unvisited_transitions
+                            .entry(new_signature)
+                            .or_default()
+                            .push((reverse_transition, signature.clone()));
                     }
                 }
             }
         }
 
+        progress(ProgressEvent {
+            name: self.graph_name,
+            phase: ProgressPhase::SearchingGraph,
+            nodes_expanded: node_info.len(),
+            queue_size: 0,
+            finished: true,
+        });
+
         let end_time = Instant::now();
         let duration = end_time - start_time;
         log!(
@@ -195,10 +489,156 @@ where
             duration.as_millis()
         );
 
-        SequenceSolver { node_info }
+        SequenceSolver {
+            node_info,
+            optimal_transitions,
+        }
+    }
+
+    /// Finds an optimal move sequence that takes a cube with signature `from` to signature `to`,
+    /// without building a whole [SequenceSolver].
+    /// This is a bidirectional Dijkstra search: it searches forwards from `from` and backwards from `to`
+    /// until the two searches meet, so it usually visits far fewer signatures than [SequenceGraph::search].
+    ///
+    /// The metric is applied to each transition separately, so it should be additive,
+    /// which is true of every metric in [crate::metric].
+    pub fn solve_single(
+        &self,
+        from: &S,
+        to: &S,
+        metric: impl Fn(&MoveSequence) -> u64,
+    ) -> Option<MoveSequence> {
+        let (from, _) = self.graph.get_key_value(from)?;
+        let (to, _) = self.graph.get_key_value(to)?;
+        if from == to {
+            return Some(MoveSequence { moves: Vec::new() });
+        }
+
+        // The transitions into each signature, for the backwards search.
+        let reverse_transitions = self.reverse_transitions.get_or_init(|| {
+            let mut reverse_transitions = HashMap::<S, Vec<(MoveSequence, S)>>::new();
+            for (signature, state) in &self.graph {
+                for (seq, new_signature) in &state.transitions {
+                    reverse_transitions
+                        .entry(new_signature.clone())
+                        .or_default()
+                        .push((seq.clone(), signature.clone()));
+                }
+            }
+            reverse_transitions
+        });
+
+        // As in `search`, the priority of a signature is `u64::MAX` minus its distance.
+        // Each search stores the distance to each signature it has reached,
+        // and the transition through which it was reached.
+        let mut queues = [PriorityQueue::new(), PriorityQueue::new()];
+        let mut reached: [Reached<S>; 2] = [HashMap::new(), HashMap::new()];
+        queues[0].push(from, u64::MAX);
+        queues[1].push(to, u64::MAX);
+        reached[0].insert(from, (0, None));
+        reached[1].insert(to, (0, None));
+
+        // The shortest distance found so far, and the signature at which the searches met.
+        let mut best: Option<(u64, &S)> = None;
+        loop {
+            let top_distance = |queue: &PriorityQueue<&S, u64>| {
+                queue.peek().map(|(_, priority)| u64::MAX - priority)
+            };
+            let (forward, backward) = match (top_distance(&queues[0]), top_distance(&queues[1])) {
+                (Some(forward), Some(backward)) => (forward, backward),
+                _ => break,
+            };
+            if let Some((distance, _)) = best {
+                if forward + backward >= distance {
+                    break;
+                }
+            }
+
+            // Expand the search with the closer frontier.
+            let direction = if forward <= backward { 0 } else { 1 };
+            let (signature, priority) = queues[direction].pop().unwrap();
+            let distance = u64::MAX - priority;
+            let neighbours = if direction == 0 {
+                self.graph[signature].transitions.iter().collect::<Vec<_>>()
+            } else {
+                reverse_transitions
+                    .get(signature)
+                    .map(|transitions| transitions.iter().map(|(seq, s)| (seq, s)).collect())
+                    .unwrap_or_default()
+            };
+            for (seq, neighbour) in neighbours {
+                let new_distance = distance + metric(seq);
+                let improved = match reached[direction].get(neighbour) {
+                    Some(&(existing, _)) => new_distance < existing,
+                    None => true,
+                };
+                if improved {
+                    reached[direction].insert(neighbour, (new_distance, Some((seq, signature))));
+                    queues[direction].push(neighbour, u64::MAX - new_distance);
+                }
+                if let Some(&(other, _)) = reached[1 - direction].get(neighbour) {
+                    let total = new_distance + other;
+                    let better = match best {
+                        Some((distance, _)) => total < distance,
+                        None => true,
+                    };
+                    if better {
+                        best = Some((total, neighbour));
+                    }
+                }
+            }
+        }
+
+        let (_, middle) = best?;
+
+        // Follow the transitions back from the middle to each end.
+        let mut first_half = Vec::new();
+        let mut signature = middle;
+        while let Some((seq, previous)) = reached[0][signature].1 {
+            first_half.push(seq);
+            signature = previous;
+        }
+        let mut second_half = Vec::new();
+        let mut signature = middle;
+        while let Some((seq, next)) = reached[1][signature].1 {
+            second_half.push(seq);
+            signature = next;
+        }
+
+        Some(MoveSequence {
+            moves: first_half
+                .into_iter()
+                .rev()
+                .chain(second_half)
+                .flat_map(|seq| seq.moves.iter().cloned())
+                .collect(),
+        })
     }
 }
 
+/// Generates double and inverse moves for each move in the generating set.
+fn real_gen_set(gen_set: &[MoveSequence]) -> Vec<MoveSequence> {
+    let mut real_gen_set = gen_set
+        .iter()
+        .map(|mv| {
+            if mv.moves.len() > 1 {
+                // Don't generate inverses etc. for full algorithms or conjugates.
+                // These algorithms must, however, be reversed.
+                // This is because the move sequences themselves will be reversed when solving as opposed to exploring.
+                vec![mv.inverse()]
+            } else {
+                vec![mv.inverse(), mv.clone().op(mv.clone()), mv.clone()]
+            }
+        })
+        .flatten()
+        .map(|mv| mv.canonicalise())
+        .filter(|mv| !mv.moves.is_empty())
+        .collect::<Vec<_>>();
+    real_gen_set.sort();
+    real_gen_set.dedup();
+    real_gen_set
+}
+
 impl<S> SequenceSolver<S>
 where
     S: Eq + Hash,
@@ -207,4 +647,457 @@ where
     pub fn solve(&self, signature: &S) -> Option<&MoveSequence> {
         self.node_info.get(signature)
     }
+
+    /// The number of signatures that can be solved.
+    pub fn len(&self) -> usize {
+        self.node_info.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node_info.is_empty()
+    }
+
+    /// Whether the given signature can be solved into the target signature.
+    pub fn contains(&self, signature: &S) -> bool {
+        self.node_info.contains_key(signature)
+    }
+
+    /// Gives every optimal move sequence that solves the given signature into the target signature,
+    /// up to [MAX_OPTIMAL_SOLUTIONS] of them.
+    /// The solver must have been made by [SequenceGraph::search_all_optimal];
+    /// otherwise, only the move sequence given by [SequenceSolver::solve] is returned.
+    pub fn solve_all_optimal(&self, signature: &S) -> Vec<MoveSequence> {
+        let mut solutions = Vec::new();
+        self.add_optimal_solutions(
+            signature,
+            MoveSequence { moves: Vec::new() },
+            &mut solutions,
+        );
+        solutions
+    }
+
+    /// Adds each optimal move sequence from the given signature to `solutions`, after the given prefix.
+    fn add_optimal_solutions(
+        &self,
+        signature: &S,
+        prefix: MoveSequence,
+        solutions: &mut Vec<MoveSequence>,
+    ) {
+        if solutions.len() >= MAX_OPTIMAL_SOLUTIONS {
+            return;
+        }
+        match self.optimal_transitions.get(signature) {
+            Some(transitions) => {
+                for (transition, next) in transitions {
+                    self.add_optimal_solutions(
+                        next,
+                        prefix.clone() + transition.clone(),
+                        solutions,
+                    );
+                }
+            }
+            None => {
+                // This is the target signature, or only one optimal move sequence was stored.
+                if let Some(solution) = self.node_info.get(signature) {
+                    let solution = prefix + solution.clone();
+                    if !solutions.contains(&solution) {
+                        solutions.push(solution);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S> SequenceSolver<S>
+where
+    S: Eq + Hash + Indexable,
+{
+    /// Moves the lookup table into an [IndexedSequenceSolver].
+    pub fn into_indexed(self) -> IndexedSequenceSolver<S> {
+        let mut node_info = vec![None; S::RANGE];
+        for (signature, move_sequence) in self.node_info {
+            node_info[signature.coordinate()] = Some(move_sequence);
+        }
+        IndexedSequenceSolver {
+            node_info,
+            signature: PhantomData,
+        }
+    }
+}
+
+impl<S> IndexedSequenceSolver<S>
+where
+    S: Indexable,
+{
+    /// Gives an optimal move sequence to solve the given signature into the target signature.
+    pub fn solve(&self, signature: &S) -> Option<&MoveSequence> {
+        self.node_info[signature.coordinate()].as_ref()
+    }
+}
+
+impl<T> Indexable for T
+where
+    T: Enumerable,
+{
+    const RANGE: usize = T::N;
+
+    fn coordinate(&self) -> usize {
+        self.index()
+    }
+}
+
+impl<const K: u8> Indexable for CyclicGroup<K> {
+    const RANGE: usize = K as usize;
+
+    fn coordinate(&self) -> usize {
+        self.get_value() as usize
+    }
+}
+
+impl Indexable for bool {
+    const RANGE: usize = 2;
+
+    fn coordinate(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Arrays are indexed as numbers in base `T::RANGE`.
+impl<T, const N: usize> Indexable for [T; N]
+where
+    T: Indexable,
+{
+    const RANGE: usize = T::RANGE.pow(N as u32);
+
+    fn coordinate(&self) -> usize {
+        self.iter().fold(0, |coordinate, value| {
+            coordinate * T::RANGE + value.coordinate()
+        })
+    }
+}
+
+/// Tuples are indexed as numbers whose digits are the coordinates of their fields.
+macro_rules! impl_indexable_for_tuple {
+    ($($name:ident)+) => {
+        impl<$($name),+> Indexable for ($($name,)+)
+        where
+            $($name: Indexable),+
+        {
+            const RANGE: usize = 1 $(* $name::RANGE)+;
+
+            #[allow(non_snake_case)]
+            fn coordinate(&self) -> usize {
+                let ($($name,)+) = self;
+                let mut coordinate = 0;
+                $(coordinate = coordinate * $name::RANGE + $name.coordinate();)+
+                coordinate
+            }
+        }
+    };
+}
+
+impl_indexable_for_tuple!(A B);
+impl_indexable_for_tuple!(A B C);
+impl_indexable_for_tuple!(A B C D);
+
+#[cfg(feature = "tables")]
+impl<S> SequenceSolver<S>
+where
+    S: Eq + Hash + serde::Serialize + serde::de::DeserializeOwned,
+{
+    /// Writes the lookup table in a compact binary format.
+    /// It can be read with [SequenceSolver::from_bytes], so that tables can be precomputed at build time.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self.node_info).expect("lookup tables can always be serialized")
+    }
+
+    /// Reads a lookup table written by [SequenceSolver::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(bytes).map(|node_info| Self {
+            node_info,
+            optimal_transitions: HashMap::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cube::{EdgeType, EdgeType::*},
+        group::GroupAction,
+        metric,
+        permute::EdgeCubelet,
+    };
+
+    /// Every face turn, together with turns of the M slice.
+    const FACES: [&str; 7] = ["F", "R", "U", "B", "L", "D", "M"];
+
+    /// Where each of the given edges is, and how it is oriented.
+    type EdgesSignature<const N: usize> = [(EdgeCubelet, CyclicGroup<2>); N];
+
+    /// The signature function that tracks the given edges.
+    fn edges<const N: usize>(
+        edges: [EdgeType; N],
+    ) -> impl Fn(CubePermutation3) -> EdgesSignature<N> + Copy + Sync {
+        move |cube| {
+            edges.map(|edge| {
+                cube.edges()
+                    .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+            })
+        }
+    }
+
+    /// The graph of the given edges generated by every turn of the given faces.
+    /// Only a few edges are tracked, so that the graph is small enough to generate quickly.
+    fn edge_graph<const N: usize>(
+        faces: &[&str],
+        tracked: [EdgeType; N],
+    ) -> SequenceGraph<EdgesSignature<N>> {
+        SequenceGraph::new("test", gen_set(faces), edges(tracked))
+    }
+
+    #[test]
+    fn graph_generation() {
+        // The DL edge can reach each of the 12 edge positions in either orientation.
+        assert_eq!(edge_graph(&FACES, [DL]).signatures().count(), 24);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_graph() {
+        let sequential = edge_graph(&["U", "M"], [UF, UB]);
+        let parallel = SequenceGraph::new_parallel("test", gen_set(&["U", "M"]), edges([UF, UB]));
+        assert_eq!(sequential.len(), parallel.len());
+        assert_eq!(
+            sequential
+                .signatures()
+                .collect::<std::collections::HashSet<_>>(),
+            parallel
+                .signatures()
+                .collect::<std::collections::HashSet<_>>()
+        );
+    }
+
+    #[test]
+    fn indexed_solver() {
+        let graph = edge_graph(&["U", "M"], [UF, UB]);
+        let target = edges([UF, UB])(CubePermutation3::identity());
+        let solver = graph.search(target, metric::etm);
+        let indexed = graph.search(target, metric::etm).into_indexed();
+
+        let mut coordinates = graph
+            .signatures()
+            .map(|signature| signature.coordinate())
+            .collect::<Vec<_>>();
+        coordinates.sort_unstable();
+        coordinates.dedup();
+        assert_eq!(coordinates.len(), graph.len());
+        assert!(coordinates.iter().all(|&c| c < EdgesSignature::<2>::RANGE));
+
+        for signature in graph.signatures() {
+            assert_eq!(solver.solve(signature), indexed.solve(signature));
+        }
+        // The U layer and the M slice never move these edges to the DL or DR positions.
+        let unreachable = [DL, DR].map(|edge| (EdgeCubelet(edge), CyclicGroup::identity()));
+        assert_eq!(solver.solve(&unreachable), None);
+        assert_eq!(indexed.solve(&unreachable), None);
+    }
+
+    #[test]
+    fn solve_single() {
+        let graph = edge_graph(&["U", "M"], [UF, UB]);
+        let target = edges([UF, UB])(CubePermutation3::identity());
+        let solver = graph.search(target, metric::etm);
+
+        for signature in graph.signatures() {
+            let solution = graph.solve_single(signature, &target, metric::etm).unwrap();
+            assert_eq!(
+                metric::etm(&solution),
+                metric::etm(solver.solve(signature).unwrap())
+            );
+        }
+
+        // The generating set contains algorithms, whose inverses are not transitions of the graph.
+        let gen_set = ["U", "M2", "M U2 M", "M U2 M'", "M' U2 M'", "M' U2 M"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+        let graph = SequenceGraph::new("test", gen_set, edges([UL, UR]));
+        let target = edges([UL, UR])(CubePermutation3::identity());
+        let solver = graph.search(target, metric::stm);
+        for signature in graph.signatures() {
+            assert_eq!(
+                graph
+                    .solve_single(signature, &target, metric::stm)
+                    .map(|solution| metric::stm(&solution)),
+                solver.solve(signature).map(metric::stm)
+            );
+        }
+    }
+
+    #[test]
+    fn progress() {
+        let mut events = Vec::new();
+        let graph = SequenceGraph::new_with_progress(
+            "test",
+            gen_set(&["U", "M"]),
+            edges([UF, UB]),
+            |event| events.push(event),
+        );
+        graph.search_with_progress(
+            edges([UF, UB])(CubePermutation3::identity()),
+            metric::etm,
+            |event| events.push(event),
+        );
+
+        let last = |phase| {
+            *events
+                .iter()
+                .rev()
+                .find(|event: &&ProgressEvent| event.phase == phase)
+                .unwrap()
+        };
+        let generated = last(ProgressPhase::GeneratingGraph);
+        assert!(generated.finished);
+        assert_eq!(generated.nodes_expanded, graph.len());
+        let searched = last(ProgressPhase::SearchingGraph);
+        assert!(searched.finished);
+        assert_eq!(searched.nodes_expanded, graph.len());
+    }
+
+    #[test]
+    fn all_optimal_solutions() {
+        let signature = edges([DL]);
+        let graph = edge_graph(&FACES, [DL]);
+        let solver = graph.search_all_optimal(signature(CubePermutation3::identity()), metric::etm);
+
+        let mut found_alternatives = false;
+        let gen_set = gen_set(&FACES);
+        for first in &gen_set {
+            for second in &gen_set {
+                let scramble =
+                    CubePermutation3::from_move_sequence(first.clone() + second.clone()).unwrap();
+                let solutions = solver.solve_all_optimal(&signature(scramble));
+                assert!(!solutions.is_empty());
+                assert!(solutions.len() <= MAX_OPTIMAL_SOLUTIONS);
+                // The stored solution is one of the optimal solutions, and they all have the same length.
+                let best = solver.solve(&signature(scramble)).unwrap();
+                assert!(solutions.contains(best));
+                for solution in &solutions {
+                    assert_eq!(metric::etm(solution), metric::etm(best), "{}", solution);
+                    let solved = CubePermutation3::from_move_sequence(solution.clone())
+                        .unwrap()
+                        .op(scramble);
+                    assert_eq!(
+                        signature(solved),
+                        signature(CubePermutation3::identity()),
+                        "{}",
+                        solution
+                    );
+                }
+                found_alternatives |= solutions.len() > 1;
+            }
+        }
+        // Some edges can be solved in more than one optimal way.
+        assert!(found_alternatives);
+
+        // A solver made by an ordinary search only knows one solution.
+        let solver = graph.search(signature(CubePermutation3::identity()), metric::etm);
+        let scramble = CubePermutation3::from_move_sequence("F D".parse().unwrap()).unwrap();
+        assert_eq!(
+            solver.solve_all_optimal(&signature(scramble)),
+            vec![solver.solve(&signature(scramble)).unwrap().clone()]
+        );
+    }
+
+    #[test]
+    fn ergonomic_solutions() {
+        // The FR edge can be solved with either U or D, since it is between them.
+        let signature = edges([FR]);
+        let graph = edge_graph(&FACES, [FR]);
+        let target = signature(CubePermutation3::identity());
+        let stm_solver = graph.search_all_optimal(target, metric::stm);
+        let ergonomic_solver = graph.search(target, metric::rouxy_ergonomic);
+
+        // Both R' U F and R D' F' are optimal in the slice turn metric,
+        // but the ergonomic solver does not need to turn D.
+        let scramble =
+            signature(CubePermutation3::from_move_sequence("F M B".parse().unwrap()).unwrap());
+        let solutions = stm_solver
+            .solve_all_optimal(&scramble)
+            .iter()
+            .map(|seq| seq.to_string())
+            .collect::<Vec<_>>();
+        assert!(solutions.contains(&"R' U F".to_string()));
+        assert!(solutions.contains(&"R D' F'".to_string()));
+        let ergonomic = ergonomic_solver.solve(&scramble).unwrap();
+        assert_eq!(metric::stm(ergonomic), 3);
+        assert!(
+            ergonomic
+                .moves
+                .iter()
+                .all(|mv| !mv.to_string().starts_with('D')),
+            "{}",
+            ergonomic
+        );
+
+        // Whenever a solution needs no regrips, the ergonomic solver finds one that is just as short.
+        let needs_regrip =
+            |seq: &MoveSequence| metric::rouxy_ergonomic(seq) > 10 * metric::stm(seq);
+        let gen_set = gen_set(&FACES);
+        for first in &gen_set {
+            for second in &gen_set {
+                let scramble = signature(
+                    CubePermutation3::from_move_sequence(first.clone() + second.clone()).unwrap(),
+                );
+                let solutions = stm_solver.solve_all_optimal(&scramble);
+                if solutions.iter().any(|seq| !needs_regrip(seq)) {
+                    let ergonomic = ergonomic_solver.solve(&scramble).unwrap();
+                    assert_eq!(metric::stm(ergonomic), metric::stm(&solutions[0]));
+                    assert!(!needs_regrip(ergonomic), "{}", ergonomic);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn move_groups() {
+        let htm = MoveGroup::Htm
+            .gen_set()
+            .into_iter()
+            .map(|seq| seq.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            htm,
+            [
+                "U", "U2", "U'", "D", "D2", "D'", "R", "R2", "R'", "L", "L2", "L'", "F", "F2",
+                "F'", "B", "B2", "B'"
+            ]
+        );
+        assert_eq!(
+            MoveGroup::Roux
+                .gen_set()
+                .into_iter()
+                .map(|seq| seq.to_string())
+                .collect::<Vec<_>>(),
+            ["R", "R2", "R'", "Rw", "Rw2", "Rw'", "U", "U2", "U'", "M", "M2", "M'"]
+        );
+        assert_eq!(
+            MoveGroup::Ru
+                .gen_set()
+                .into_iter()
+                .map(|seq| seq.to_string())
+                .collect::<Vec<_>>(),
+            ["R", "R2", "R'", "U", "U2", "U'"]
+        );
+
+        // Every move in a smaller group is in a larger one.
+        let ruf = MoveGroup::Ruf.gen_set();
+        assert_eq!(ruf.len(), 9);
+        for seq in MoveGroup::Ru.gen_set() {
+            assert!(ruf.contains(&seq), "{}", seq);
+        }
+    }
 }
@@ -0,0 +1,340 @@
+use crate::{
+    cfop::{self, slot_inserts, BL_SLOT, BR_SLOT, FL_SLOT, FR_SLOT, U_CORNERS, U_EDGES},
+    cube::{
+        CornerType, EdgeType, MoveSequence,
+        {CornerType::*, EdgeType::*},
+    },
+    group::{CyclicGroup, GroupAction, Magma, Unital},
+    intuitive::{SequenceGraph, SequenceSolver},
+    metric,
+    permute::{CornerCubelet, CubePermutation3, EdgeCubelet},
+    solve::{move_sequence_to_intuitive_action, run_method, Action, Method, Step},
+};
+
+type LblCornerSignature = (CornerCubelet, CyclicGroup<3>);
+type LblEdgeSignature = (EdgeCubelet, CyclicGroup<2>);
+type LblCrossSignature = [CyclicGroup<2>; 4];
+type LblOrientSignature = [CyclicGroup<3>; 4];
+type LblPermuteSignature = ([EdgeCubelet; 4], [CornerCubelet; 4]);
+
+// Every step of this method is performed with the white face on the bottom,
+// so the white layer is the D layer and the yellow layer is the U layer.
+
+fn corner_solver(
+    graph_name: &'static str,
+    slots: &[[&str; 2]],
+    corner: CornerType,
+) -> SequenceSolver<LblCornerSignature> {
+    // Only the inserts of this corner and the unsolved corners are used, so the solved corners are preserved.
+    let gen_set = std::iter::once("U".to_string())
+        .chain(slots.iter().flat_map(|&faces| slot_inserts(faces)))
+        .map(|x| x.parse::<MoveSequence>().unwrap())
+        .collect::<Vec<_>>();
+
+    let graph = SequenceGraph::new(graph_name, gen_set, |cube| {
+        cube.corners()
+            .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+    });
+    graph.search(
+        (CornerCubelet(corner), CyclicGroup::identity()),
+        metric::etm,
+    )
+}
+
+fn edge_solver(
+    graph_name: &'static str,
+    inserts: &[&str],
+    edge: EdgeType,
+) -> SequenceSolver<LblEdgeSignature> {
+    let gen_set = std::iter::once("U")
+        .chain(inserts.iter().copied())
+        .map(|x| x.parse::<MoveSequence>().unwrap())
+        .collect::<Vec<_>>();
+
+    let graph = SequenceGraph::new(graph_name, gen_set, |cube| {
+        cube.edges()
+            .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+    });
+    graph.search((EdgeCubelet(edge), CyclicGroup::identity()), metric::etm)
+}
+
+/// The algorithms that insert an edge from the U layer into each second layer slot,
+/// from the left and from the right.
+/// These do not affect the white layer, or any other second layer slot.
+const FR_INSERTS: [&str; 2] = ["U R U' R' U' F' U F", "U' F' U F U R U' R'"];
+const FL_INSERTS: [&str; 2] = ["U' L' U L U F U' F'", "U F U' F' U' L' U L"];
+const BR_INSERTS: [&str; 2] = ["U' R' U R U B U' B'", "U B U' B' U' R' U R"];
+const BL_INSERTS: [&str; 2] = ["U' B' U B U L U' L'", "U L U' L' U' B' U B"];
+
+fn yellow_cross_signature(cube: CubePermutation3) -> LblCrossSignature {
+    // Unact is used to get edge orientation: we don't care which edge is in this position,
+    // just how it is oriented relative to where the edge should belong.
+    U_EDGES.map(|edge| {
+        cube.edges()
+            .unact(&(EdgeCubelet(edge), CyclicGroup::identity()))
+            .1
+    })
+}
+
+fn orient_signature(cube: CubePermutation3) -> LblOrientSignature {
+    U_CORNERS.map(|corner| {
+        cube.corners()
+            .unact(&(CornerCubelet(corner), CyclicGroup::identity()))
+            .1
+    })
+}
+
+fn permute_signature(cube: CubePermutation3) -> LblPermuteSignature {
+    (
+        U_EDGES.map(|edge| {
+            cube.edges()
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .0
+        }),
+        U_CORNERS.map(|corner| {
+            cube.corners()
+                .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+                .0
+        }),
+    )
+}
+
+lazy_static::lazy_static! {
+    static ref LBL_CORNERS: [SequenceSolver<LblCornerSignature>; 4] = [
+        corner_solver("lbl1c", &[FR_SLOT, FL_SLOT, BR_SLOT, BL_SLOT], FDR),
+        corner_solver("lbl2c", &[FL_SLOT, BR_SLOT, BL_SLOT], FDL),
+        corner_solver("lbl3c", &[BR_SLOT, BL_SLOT], BDR),
+        corner_solver("lbl4c", &[BL_SLOT], BDL),
+    ];
+
+    static ref LBL_EDGES: [SequenceSolver<LblEdgeSignature>; 4] = [
+        edge_solver("lbl1e", &[FR_INSERTS, FL_INSERTS, BR_INSERTS, BL_INSERTS].concat(), FR),
+        edge_solver("lbl2e", &[FL_INSERTS, BR_INSERTS, BL_INSERTS].concat(), FL),
+        edge_solver("lbl3e", &[BR_INSERTS, BL_INSERTS].concat(), BR),
+        edge_solver("lbl4e", &BL_INSERTS, BL),
+    ];
+
+    /// Beginners orient the yellow edges by repeating a single algorithm.
+    static ref YELLOW_CROSS: SequenceSolver<LblCrossSignature> = {
+        let gen_set = vec!["U", "F R U R' U' F'"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("lbl_cross", gen_set, yellow_cross_signature);
+        graph.search([CyclicGroup::identity(); 4], metric::etm)
+    };
+
+    /// The yellow corners are twisted by repeating the Sune.
+    static ref ORIENT: SequenceSolver<LblOrientSignature> = {
+        let gen_set = vec!["U", "R U R' U R U2 R'"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("lbl_orient", gen_set, orient_signature);
+        graph.search([CyclicGroup::identity(); 4], metric::etm)
+    };
+
+    /// The yellow corners are cycled with the A permutation, and the yellow edges with the U permutation.
+    static ref PERMUTE: SequenceSolver<LblPermuteSignature> = {
+        let gen_set = vec!["U", "R' F R' B2 R F' R' B2 R2", "R U' R U R U R U' R' U' R2"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("lbl_permute", gen_set, permute_signature);
+        graph.search(permute_signature(CubePermutation3::identity()), metric::etm)
+    };
+}
+
+/// Solves each piece in turn with the given solvers, concatenating the move sequences.
+fn solve_pieces<S>(
+    mut permutation: CubePermutation3,
+    solvers: &[SequenceSolver<S>],
+    signature: impl Fn(CubePermutation3, usize) -> S,
+) -> Option<MoveSequence>
+where
+    S: Eq + std::hash::Hash,
+{
+    let mut result = MoveSequence { moves: Vec::new() };
+    for (i, solver) in solvers.iter().enumerate() {
+        let seq = solver.solve(&signature(permutation, i))?;
+        permutation = CubePermutation3::from_move_sequence(seq.clone())?.op(permutation);
+        result = seq.clone().op(result);
+    }
+    Some(result)
+}
+
+/// Solves the white edges, with the white face on the bottom.
+pub fn white_cross(permutation: CubePermutation3) -> Option<MoveSequence> {
+    cfop::cross(permutation)
+}
+
+/// Solves the white corners one at a time, with the white face on the bottom.
+pub fn white_corners(permutation: CubePermutation3) -> Option<MoveSequence> {
+    solve_pieces(permutation, &*LBL_CORNERS, |cube, i| {
+        let corner = [FDR, FDL, BDR, BDL][i];
+        cube.corners()
+            .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+    })
+}
+
+/// Solves the second layer edges one at a time, with the white face on the bottom.
+pub fn second_layer_edges(permutation: CubePermutation3) -> Option<MoveSequence> {
+    solve_pieces(permutation, &*LBL_EDGES, |cube, i| {
+        let edge = [FR, FL, BR, BL][i];
+        cube.edges()
+            .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+    })
+}
+
+/// Orients the yellow edges, with the white face on the bottom.
+pub fn yellow_cross(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    YELLOW_CROSS.solve(&yellow_cross_signature(permutation))
+}
+
+/// Orients the yellow corners, with the white face on the bottom.
+pub fn orient_last_layer(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    ORIENT.solve(&orient_signature(permutation))
+}
+
+/// Permutes the yellow pieces, with the white face on the bottom.
+pub fn permute_last_layer(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    PERMUTE.solve(&permute_signature(permutation))
+}
+
+/// The steps of the beginner's method, in the order they are performed, with a description for each step.
+#[allow(clippy::type_complexity)]
+const STEP_SOLVERS: [(&str, &str, fn(CubePermutation3) -> Option<MoveSequence>); 6] = [
+    (
+        "White cross",
+        "Hold the cube with the white face on the bottom. \
+        Move each white edge to the bottom layer so that its other colour matches the centre beside it.",
+        white_cross,
+    ),
+    (
+        "White corners",
+        "Bring each white corner above the slot where it belongs, \
+        then turn that side down, turn the top layer, and turn the side back up to insert it.",
+        white_corners,
+    ),
+    (
+        "Second layer edges",
+        "Find an edge in the top layer without yellow on it, and line it up with its centre. \
+        Then take the slot beside it out of the first layer and put it back with the edge.",
+        second_layer_edges,
+    ),
+    (
+        "Yellow cross",
+        "Repeat F R U R' U' F', turning the top layer in between, \
+        until there is a yellow cross on top.",
+        |permutation| yellow_cross(permutation).cloned(),
+    ),
+    (
+        "Orient last layer",
+        "Repeat R U R' U R U2 R', turning the top layer in between, \
+        until the whole top face is yellow.",
+        |permutation| orient_last_layer(permutation).cloned(),
+    ),
+    (
+        "Permute last layer",
+        "Swap the yellow corners into place, then cycle the yellow edges into place. \
+        Finally, turn the cube back over.",
+        |permutation| permute_last_layer(permutation).cloned(),
+    ),
+];
+
+/// Performs the step of the beginner's method with the given index.
+/// The steps are written with the white face on the bottom, so the first step starts by turning the cube over,
+/// and the last step ends by turning it back.
+fn lbl_step<const I: usize>(permutation: CubePermutation3) -> Option<Action> {
+    let (step, description, func) = STEP_SOLVERS[I];
+    let rotation = "z2".parse::<MoveSequence>().unwrap();
+    let rotation_permutation = CubePermutation3::from_move_sequence(rotation.clone())?;
+    // Label each piece by where it belongs on the cube as it looks after turning it over.
+    let permutation = if I == 0 {
+        rotation_permutation
+            .op(permutation)
+            .op(rotation_permutation)
+    } else {
+        permutation.op(rotation_permutation)
+    };
+
+    let mut seq = func(permutation)?;
+    if I == 0 {
+        seq = seq.op(rotation.clone());
+    }
+    if I == STEP_SOLVERS.len() - 1 {
+        seq = rotation.op(seq);
+    }
+    Some(Action {
+        description: Some(description.to_string()),
+        ..move_sequence_to_intuitive_action(step, seq)
+    })
+}
+
+const STEPS: [Step; 6] = [
+    (STEP_SOLVERS[0].0, lbl_step::<0>, None),
+    (STEP_SOLVERS[1].0, lbl_step::<1>, None),
+    (STEP_SOLVERS[2].0, lbl_step::<2>, None),
+    (STEP_SOLVERS[3].0, lbl_step::<3>, None),
+    (STEP_SOLVERS[4].0, lbl_step::<4>, None),
+    (STEP_SOLVERS[5].0, lbl_step::<5>, None),
+];
+
+/// The layer by layer method taught to beginners: the white cross and corners, the second layer,
+/// then the yellow face in three steps.
+pub struct Lbl;
+
+impl Method for Lbl {
+    fn name(&self) -> &'static str {
+        "Layer by layer method"
+    }
+
+    fn steps(&self) -> &'static [Step] {
+        &STEPS
+    }
+}
+
+/// Solves the cube with the layer by layer method taught to beginners.
+/// The solution starts by turning the cube over so that the white face is on the bottom, and ends by turning it back.
+pub fn solve(permutation: CubePermutation3) -> Option<Action> {
+    run_method(&Lbl, permutation)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::solve::ActionSteps;
+
+    use super::*;
+
+    #[test]
+    fn lbl_solve() {
+        let mut rng = StdRng::seed_from_u64(24680);
+        for _ in 0..5 {
+            let permutation = CubePermutation3::random_solvable(&mut rng);
+            let solution = solve(permutation).unwrap();
+            let final_permutation =
+                CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                    .unwrap()
+                    .op(permutation);
+            assert_eq!(
+                final_permutation,
+                CubePermutation3::identity(),
+                "{}",
+                permutation
+            );
+
+            match solution.steps {
+                ActionSteps::Sequence { actions } => {
+                    assert_eq!(actions.len(), 6);
+                    assert!(actions.iter().all(|action| action.description.is_some()));
+                }
+                _ => panic!("expected a sequence of steps"),
+            }
+        }
+    }
+}
@@ -3,18 +3,38 @@
 #![feature(generic_const_exprs)]
 #![allow(incomplete_features)]
 
+mod cfop;
+mod commutator;
+mod coordinate;
 mod cube;
+mod error;
 mod group;
 mod intuitive;
+mod lbl;
+mod metric;
+mod optimal;
+mod patterns;
 mod permute;
+mod petrus;
+mod recognition;
+mod reduction;
 mod roux;
+#[cfg(feature = "rand")]
+mod scramble;
 mod solve;
+mod two_phase;
 mod utils;
+mod zz;
 mod algorithmic;
 
 use wasm_bindgen::prelude::*;
 
 use crate::cube::*;
+pub use crate::cube::WasmCube;
+pub use crate::error::Error;
+pub use crate::roux::solve_roux;
+#[cfg(feature = "rand")]
+pub use crate::scramble::generate_scramble;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
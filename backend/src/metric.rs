@@ -0,0 +1,152 @@
+use crate::cube::{FaceType, Move, MoveSequence, RotationType};
+
+/// The kinds of move that the metrics distinguish between.
+/// Moves are interpreted as moves on a 3x3x3 cube.
+enum MoveKind {
+    /// A turn of one outer layer, or of an outer layer together with inner layers.
+    Face,
+    /// A turn of inner layers only.
+    Slice,
+    /// A turn of the whole cube.
+    Rotation,
+}
+
+fn kind(mv: &Move) -> MoveKind {
+    match (mv.start_depth, mv.end_depth) {
+        (0, 3) => MoveKind::Rotation,
+        (start, end) if start > 0 && end < 3 => MoveKind::Slice,
+        _ => MoveKind::Face,
+    }
+}
+
+fn is_double(mv: &Move) -> bool {
+    mv.rotation_type == RotationType::Double
+}
+
+/// The half turn metric, also called the face turn metric.
+/// Each face turn counts as one move, including double turns and wide turns.
+/// Slice moves count as two, since they are equivalent to turning both outer layers.
+/// Rotations are free.
+pub fn htm(seq: &MoveSequence) -> u64 {
+    seq.moves
+        .iter()
+        .map(|mv| match kind(mv) {
+            MoveKind::Face => 1,
+            MoveKind::Slice => 2,
+            MoveKind::Rotation => 0,
+        })
+        .sum()
+}
+
+/// The quarter turn metric.
+/// This is the half turn metric, except that double turns count twice.
+pub fn qtm(seq: &MoveSequence) -> u64 {
+    seq.moves
+        .iter()
+        .map(|mv| {
+            let quarter_turns = if is_double(mv) { 2 } else { 1 };
+            let layers = match kind(mv) {
+                MoveKind::Face => 1,
+                MoveKind::Slice => 2,
+                MoveKind::Rotation => 0,
+            };
+            quarter_turns * layers
+        })
+        .sum()
+}
+
+/// The slice turn metric.
+/// Each face turn or slice move counts as one move, including double turns.
+/// Rotations are free.
+pub fn stm(seq: &MoveSequence) -> u64 {
+    seq.moves
+        .iter()
+        .map(|mv| match kind(mv) {
+            MoveKind::Face | MoveKind::Slice => 1,
+            MoveKind::Rotation => 0,
+        })
+        .sum()
+}
+
+/// The execution turn metric.
+/// Every move counts as one move, including rotations.
+pub fn etm(seq: &MoveSequence) -> u64 {
+    seq.moves.len() as u64
+}
+
+/// Makes a metric from the cost of each move, given the move performed just before it, if any.
+pub fn pairwise(cost: impl Fn(Option<&Move>, &Move) -> u64) -> impl Fn(&MoveSequence) -> u64 {
+    move |seq| {
+        std::iter::once(None)
+            .chain(seq.moves.iter().map(Some))
+            .zip(&seq.moves)
+            .map(|(previous, mv)| cost(previous, mv))
+            .sum()
+    }
+}
+
+/// The outer layer turned by a face turn, or `None` for slice moves and rotations.
+fn face(mv: &Move) -> Option<FaceType> {
+    match kind(mv) {
+        MoveKind::Face if mv.start_depth == 0 => Some(mv.axis.face()),
+        MoveKind::Face => Some(mv.axis.face().opposite()),
+        MoveKind::Slice | MoveKind::Rotation => None,
+    }
+}
+
+/// An ergonomic metric for a right-handed solver using the Roux method, counted in tenths of a move.
+/// Each move counts as ten, as in the slice turn metric.
+/// Turning the L, D, or B face needs a regrip, so costs three more,
+/// unless the previous move turned the same face and the hand is already in place.
+/// Rotations also need a regrip, so cost three.
+pub fn rouxy_ergonomic(seq: &MoveSequence) -> u64 {
+    pairwise(|previous, mv| {
+        let needs_regrip = match face(mv) {
+            Some(FaceType::L | FaceType::D | FaceType::B) => previous.and_then(face) != face(mv),
+            _ => matches!(kind(mv), MoveKind::Rotation),
+        };
+        10 * stm(&MoveSequence { moves: vec![*mv] }) + if needs_regrip { 3 } else { 0 }
+    })(seq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics() {
+        // A face turn, a double turn, a slice, a rotation, a wide turn, and a double slice.
+        let seq = "R U2 M' x r' E2".parse::<MoveSequence>().unwrap();
+        assert_eq!(htm(&seq), 7);
+        assert_eq!(qtm(&seq), 10);
+        assert_eq!(stm(&seq), 5);
+        assert_eq!(etm(&seq), 6);
+
+        // Moves written on the opposite face have the same length.
+        let seq = "L' D2 S y2".parse::<MoveSequence>().unwrap();
+        assert_eq!(htm(&seq), 4);
+        assert_eq!(qtm(&seq), 5);
+        assert_eq!(stm(&seq), 3);
+        assert_eq!(etm(&seq), 4);
+    }
+
+    #[test]
+    fn ergonomic_metric() {
+        let cost = |seq: &str| rouxy_ergonomic(&seq.parse().unwrap());
+        assert_eq!(cost("R U M' U'"), 40);
+        assert_eq!(cost("D"), 13);
+        assert_eq!(cost("R' D' R"), 33);
+        // Turning the same face again needs no regrip, but going back to it does.
+        assert_eq!(cost("D D"), 23);
+        assert_eq!(cost("D R D"), 36);
+        assert_eq!(cost("L2 l"), 23);
+        assert_eq!(cost("x U"), 13);
+
+        // A metric can penalise particular pairs of moves.
+        let double_flick = pairwise(|previous, mv| match previous {
+            Some(previous) if previous == mv => 2,
+            _ => 1,
+        });
+        assert_eq!(double_flick(&"U U R U".parse().unwrap()), 5);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    coordinate::{
+        corner_orientation, edge_orientation, is_redundant_after, ud_slice, CoordinateTable,
+    },
+    cube::{Move, MoveSequence},
+    group::{Magma, Unital},
+    permute::{CentrePermutation, CubePermutation3},
+};
+
+lazy_static::lazy_static! {
+    /// The face turns of the half turn metric, together with their permutations.
+    static ref MOVES: Vec<(Move, CubePermutation3)> = {
+        ["U", "U2", "U'", "D", "D2", "D'", "R", "R2", "R'", "L", "L2", "L'", "F", "F2", "F'", "B", "B2", "B'"]
+            .into_iter()
+            .map(|mv| {
+                let mv = mv.parse::<Move>().unwrap();
+                (mv, CubePermutation3::from_move(mv))
+            })
+            .collect()
+    };
+
+    static ref TABLES: [CoordinateTable; 3] = {
+        let moves = MOVES.iter().map(|(_, mv)| *mv).collect::<Vec<_>>();
+        [
+            CoordinateTable::new(2187, &moves, corner_orientation),
+            CoordinateTable::new(2048, &moves, edge_orientation),
+            CoordinateTable::new(495, &moves, ud_slice),
+        ]
+    };
+}
+
+/// Finds a shortest sequence of face turns that solves the given cube, counting moves in the half turn metric.
+/// Returns `None` if every solution is longer than `max_depth`,
+/// or if the cube cannot be solved without rotating it.
+///
+/// This uses iterative deepening A*, guided by the number of moves needed to solve
+/// the corner orientation, edge orientation, and the positions of the edges in the slice between U and D.
+/// Deep searches are very slow, so `max_depth` should be small.
+pub fn solve_optimal(permutation: CubePermutation3, max_depth: usize) -> Option<MoveSequence> {
+    if *permutation.centres() != CentrePermutation::identity() {
+        return None;
+    }
+
+    let coordinates = [
+        corner_orientation(&permutation),
+        edge_orientation(&permutation),
+        ud_slice(&permutation),
+    ];
+    let mut solution = Vec::new();
+    (0..=max_depth)
+        .find(|&depth| search(permutation, coordinates, depth, &mut solution))
+        .map(|_| MoveSequence {
+            moves: solution.into_iter().map(|i| MOVES[i].0).collect(),
+        })
+}
+
+/// The number of face turns needed to solve the given cube in the half turn metric.
+/// Returns `None` if the distance is greater than `max_depth`,
+/// or if the cube cannot be solved without rotating it.
+pub fn distance_to_solved(permutation: CubePermutation3, max_depth: usize) -> Option<usize> {
+    if permutation == CubePermutation3::identity() {
+        return Some(0);
+    }
+    solve_optimal(permutation, max_depth).map(|solution| solution.len())
+}
+
+/// A lower bound on the number of face turns needed to orient every corner, in the half turn metric.
+/// Since solving the cube orients the corners, this is also a lower bound on the distance to solved,
+/// if the cube can be solved without rotating it.
+pub fn co_distance(permutation: &CubePermutation3) -> u8 {
+    TABLES[0].distance[corner_orientation(permutation)]
+}
+
+/// A lower bound on the number of face turns needed to orient every edge, in the half turn metric.
+/// Like [co_distance], this is also a lower bound on the distance to solved.
+pub fn eo_distance(permutation: &CubePermutation3) -> u8 {
+    TABLES[1].distance[edge_orientation(permutation)]
+}
+
+/// A lower bound on the number of face turns needed to bring the four edges that belong between the U and D faces
+/// back into that slice, in the half turn metric.
+/// Like [co_distance], this is also a lower bound on the distance to solved.
+pub fn slice_distance(permutation: &CubePermutation3) -> u8 {
+    TABLES[2].distance[ud_slice(permutation)]
+}
+
+/// Searches for a solution of exactly `remaining` moves, pushing the indices of its moves onto `solution`.
+fn search(
+    permutation: CubePermutation3,
+    coordinates: [usize; 3],
+    remaining: usize,
+    solution: &mut Vec<usize>,
+) -> bool {
+    let lower_bound = TABLES
+        .iter()
+        .zip(coordinates)
+        .map(|(table, c)| table.distance[c] as usize)
+        .max()
+        .unwrap();
+    if lower_bound > remaining {
+        return false;
+    }
+    if remaining == 0 {
+        return permutation == CubePermutation3::identity();
+    }
+
+    for (i, (mv, turn)) in MOVES.iter().enumerate() {
+        if let Some(&previous) = solution.last() {
+            if is_redundant_after(MOVES[previous].0, *mv) {
+                continue;
+            }
+        }
+
+        let mut next_coordinates = coordinates;
+        for (c, table) in next_coordinates.iter_mut().zip(TABLES.iter()) {
+            *c = table.transitions[*c][i];
+        }
+        solution.push(i);
+        if search(
+            turn.op(permutation),
+            next_coordinates,
+            remaining - 1,
+            solution,
+        ) {
+            return true;
+        }
+        solution.pop();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::scramble::random_moves;
+
+    fn solve(scramble: &str, max_depth: usize) -> Option<MoveSequence> {
+        let permutation = CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+        let solution = solve_optimal(permutation, max_depth)?;
+        assert_eq!(
+            CubePermutation3::from_move_sequence(solution.clone()).unwrap().op(permutation),
+            CubePermutation3::identity()
+        );
+        Some(solution)
+    }
+
+    #[test]
+    fn optimal_solutions() {
+        for (scramble, length) in [
+            ("R", 1),
+            ("R2 U2", 2),
+            ("R L'", 2),
+            ("R U R' U'", 4),
+            ("F R U R' U' F'", 6),
+            ("R U R' U R U2 R'", 7),
+        ] {
+            assert_eq!(
+                solve(scramble, 7).unwrap().moves.len(),
+                length,
+                "{}",
+                scramble
+            );
+        }
+        assert_eq!(solve("R U R' U'", 3), None);
+        assert_eq!(solve("R x", 5), None);
+    }
+
+    #[test]
+    fn distances() {
+        let distance = |scramble: &str, max_depth| {
+            distance_to_solved(
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap(),
+                max_depth,
+            )
+        };
+        assert_eq!(distance_to_solved(CubePermutation3::identity(), 0), Some(0));
+        assert_eq!(distance("R", 5), Some(1));
+        assert_eq!(distance("R U R' U'", 5), Some(4));
+        assert_eq!(distance("R U R' U'", 3), None);
+        assert_eq!(distance("x", 5), None);
+        // The superflip is 20 moves from solved, and flipping every edge alone takes more than 6 moves,
+        // so the search gives up without searching deeply.
+        let superflip = "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2";
+        let permutation = CubePermutation3::from_move_sequence(superflip.parse().unwrap()).unwrap();
+        assert!(eo_distance(&permutation) > 6);
+        assert_eq!(distance(superflip, 6), None);
+    }
+
+    #[test]
+    fn lower_bounds() {
+        let permutation = |alg: &str| CubePermutation3::from_move_sequence(alg.parse().unwrap()).unwrap();
+        let identity = CubePermutation3::identity();
+        assert_eq!(co_distance(&identity), 0);
+        assert_eq!(eo_distance(&identity), 0);
+        assert_eq!(slice_distance(&identity), 0);
+        assert_eq!(co_distance(&permutation("R")), 1);
+        assert_eq!(eo_distance(&permutation("R")), 0);
+        assert_eq!(eo_distance(&permutation("F")), 1);
+        assert_eq!(slice_distance(&permutation("U")), 0);
+
+        // The bounds never exceed the real distance to solved.
+        for alg in [
+            "R",
+            "R U",
+            "F R2",
+            "R U R' U'",
+            "F R U R' U' F'",
+            "R U R' U R U2 R'",
+            "L2 B D' F R",
+            "U F' L D2 B'",
+        ] {
+            let p = permutation(alg);
+            let distance = distance_to_solved(p, 7).unwrap() as u8;
+            assert!(co_distance(&p) <= distance, "{}", alg);
+            assert!(eo_distance(&p) <= distance, "{}", alg);
+            assert!(slice_distance(&p) <= distance, "{}", alg);
+        }
+    }
+
+    #[test]
+    fn random_scrambles() {
+        let moves = MOVES.iter().map(|&(mv, _)| mv).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(12345);
+        for _ in 0..10 {
+            let scramble = random_moves(&mut rng, &moves, 5);
+            assert!(solve(&scramble.to_string(), 5).unwrap().moves.len() <= 5);
+        }
+    }
+}
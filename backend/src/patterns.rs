@@ -0,0 +1,104 @@
+use crate::{cube::MoveSequence, permute::CubePermutation3};
+
+/// The permutation performed by a move sequence, which must be valid notation.
+pub(crate) fn from_algorithm(algorithm: &str) -> CubePermutation3 {
+    CubePermutation3::from_move_sequence(algorithm.parse::<MoveSequence>().unwrap())
+        .expect("algorithms are written for a 3x3x3 cube")
+}
+
+lazy_static::lazy_static! {
+    /// Every edge is flipped in place, and every other piece is solved.
+    /// Generated by `U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2`.
+    pub static ref SUPERFLIP: CubePermutation3 = from_algorithm("U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2");
+
+    /// Each face alternates between its own colour and the colour of the opposite face.
+    /// Generated by `R2 L2 U2 D2 F2 B2`.
+    pub static ref CHECKERBOARD: CubePermutation3 = from_algorithm("R2 L2 U2 D2 F2 B2");
+
+    /// A 2x2x2 cube appears to sit in the corner of a larger cube.
+    /// Generated by `F L F U' R U F2 L2 U' L' B D' B' L2 U`.
+    pub static ref CUBE_IN_CUBE: CubePermutation3 = from_algorithm("F L F U' R U F2 L2 U' L' B D' B' L2 U");
+
+    /// Each face is a single colour, apart from its centre.
+    /// Generated by `U D' R L' F B' U D'`.
+    pub static ref SIX_SPOTS: CubePermutation3 = from_algorithm("U D' R L' F B' U D'");
+
+    static ref ALL: Vec<(&'static str, CubePermutation3)> = vec![
+        ("Superflip", *SUPERFLIP),
+        ("Checkerboard", *CHECKERBOARD),
+        ("Cube in a cube", *CUBE_IN_CUBE),
+        ("Six spots", *SIX_SPOTS),
+    ];
+}
+
+/// Every named pattern, for displaying in a gallery.
+pub fn all() -> &'static [(&'static str, CubePermutation3)] {
+    &ALL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cube::FaceType,
+        group::*,
+        permute::{CornerPermutation, EdgeCubelet},
+    };
+
+    #[test]
+    fn superflip() {
+        assert_eq!(SUPERFLIP.order(), 2);
+        assert_eq!(*SUPERFLIP.corners(), CornerPermutation::identity());
+        for edge in EdgeCubelet::enumerate() {
+            assert_eq!(
+                SUPERFLIP.edges().act(&(edge, CyclicGroup::identity())),
+                (edge, CyclicGroup::new(1))
+            );
+        }
+    }
+
+    #[test]
+    fn stickers() {
+        let checkerboard = CHECKERBOARD.to_cube();
+        let six_spots = SIX_SPOTS.to_cube();
+        for face in FaceType::enumerate() {
+            for row in 0..3 {
+                for col in 0..3 {
+                    let colour = checkerboard.face(face)[(row, col)];
+                    if (row + col) % 2 == 0 {
+                        assert_eq!(colour, face.into());
+                    } else {
+                        assert_eq!(colour, face.opposite().into());
+                    }
+
+                    let colour = six_spots.face(face)[(row, col)];
+                    if (row, col) == (1, 1) {
+                        assert_eq!(colour, face.into());
+                    } else {
+                        assert_ne!(colour, face.into());
+                        assert_eq!(colour, six_spots.face(face)[(0, 0)]);
+                    }
+                }
+            }
+        }
+
+        // The inner cube and the centre share a colour on every face, and the rest of the face does not.
+        let cube_in_cube = CUBE_IN_CUBE.to_cube();
+        for face in FaceType::enumerate() {
+            let matching = (0..3)
+                .flat_map(|row| (0..3).map(move |col| (row, col)))
+                .filter(|&pos| cube_in_cube.face(face)[pos] == face.into())
+                .count();
+            assert_eq!(matching, 4);
+        }
+    }
+
+    #[test]
+    fn gallery() {
+        for (i, (name, pattern)) in all().iter().enumerate() {
+            assert!(pattern.is_solvable(), "{} is not solvable", name);
+            assert_ne!(*pattern, CubePermutation3::identity());
+            assert!(all()[..i].iter().all(|(other, _)| other != name));
+        }
+    }
+}
@@ -1,15 +1,75 @@
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 use crate::cube::CornerType::*;
 use crate::cube::EdgeType::*;
 use crate::cube::FaceType::*;
 use crate::{
-    cube::{Axis, CornerType, EdgeType, FaceType, Move, MoveSequence, RotationType},
+    cube::{Axis, CornerType, Cube, EdgeType, FaceType, Move, MoveSequence, RotationType},
     group::*,
 };
 
+/// The rotation types, in the order of their discriminants.
+const ROTATION_TYPES: [RotationType; 3] = [
+    RotationType::Normal,
+    RotationType::Double,
+    RotationType::Inverse,
+];
+
+lazy_static::lazy_static! {
+    /// `EDGE_FACE_TURNS[face.index()][rotation_type as usize]` is the permutation of edges caused by that face turn.
+    static ref EDGE_FACE_TURNS: [[EdgePermutation; 3]; FaceType::N] = FaceType::enumerate().map(|face| {
+        ROTATION_TYPES.map(|rotation_type| EdgePermutation::compute_face_turn(face, rotation_type))
+    });
+
+    /// The permutations caused by each move on a 3x3x3 cube, including slice moves, wide moves, and rotations.
+    /// Moves are looked up by [Move::key], so that both directions of a double turn are found.
+    static ref MOVES: HashMap<(Axis, RotationType, usize, usize), CubePermutation3> = {
+        let mut moves = HashMap::new();
+        for axis in [Axis::FB, Axis::RL, Axis::UD] {
+            for rotation_type in ROTATION_TYPES {
+                for start_depth in 0..3 {
+                    for end_depth in start_depth + 1..=3 {
+                        let mv = Move::new(axis, rotation_type, start_depth, end_depth);
+                        let permutation = CubePermutation3::compute_move(mv)
+                            .expect("move turns layers of a 3x3x3 cube");
+                        moves.insert(mv.key(), permutation);
+                    }
+                }
+            }
+        }
+        moves
+    };
+
+    /// The 24 rotations of the whole cube, each written as a rotation that moves a face to the top,
+    /// followed by a rotation about the vertical axis.
+    static ref ROTATIONS: Vec<(MoveSequence, CubePermutation3)> = {
+        let mut rotations = Vec::new();
+        for up in ["", "x", "x2", "x'", "z", "z'"] {
+            for front in ["", "y", "y2", "y'"] {
+                let seq = MoveSequence {
+                    moves: [up, front]
+                        .into_iter()
+                        .filter(|mv| !mv.is_empty())
+                        .map(|mv| mv.parse::<Move>().unwrap())
+                        .collect(),
+                };
+                let permutation = CubePermutation3::from_move_sequence(seq.clone())
+                    .expect("rotations turn layers of a 3x3x3 cube");
+                rotations.push((seq, permutation));
+            }
+        }
+        rotations
+    };
+
+    /// `CORNER_FACE_TURNS[face.index()][rotation_type as usize]` is the permutation of corners caused by that face turn.
+    static ref CORNER_FACE_TURNS: [[CornerPermutation; 3]; FaceType::N] = FaceType::enumerate().map(|face| {
+        ROTATION_TYPES.map(|rotation_type| CornerPermutation::compute_face_turn(face, rotation_type))
+    });
+}
+
 /// Represents a centre piece of an odd-sized cube.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CentreCubelet(pub FaceType);
 
 impl Display for CentreCubelet {
@@ -36,6 +96,7 @@ impl Enumerable for CentreCubelet {
 
 /// Represents one of 12 centred edge pieces of an odd-sized cube.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EdgeCubelet(pub EdgeType);
 
 impl Display for EdgeCubelet {
@@ -44,6 +105,13 @@ impl Display for EdgeCubelet {
     }
 }
 
+impl EdgeCubelet {
+    /// Yields the edge between the two faces, along with its orientation, as in [EdgeType::from_faces].
+    pub fn from_faces(f1: FaceType, f2: FaceType) -> Option<(EdgeCubelet, CyclicGroup<2>)> {
+        EdgeType::from_faces(f1, f2).map(|(edge, orientation)| (EdgeCubelet(edge), orientation))
+    }
+}
+
 impl Enumerable for EdgeCubelet {
     const N: usize = EdgeType::N;
 
@@ -62,6 +130,7 @@ impl Enumerable for EdgeCubelet {
 
 /// Represents one of 8 corner pieces of a cube.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CornerCubelet(pub CornerType);
 
 impl Display for CornerCubelet {
@@ -70,6 +139,19 @@ impl Display for CornerCubelet {
     }
 }
 
+impl CornerCubelet {
+    /// Yields the corner between the three faces, along with its orientation, as in [CornerType::from_faces].
+    /// The faces must be given clockwise around the corner.
+    pub fn from_faces(
+        f1: FaceType,
+        f2: FaceType,
+        f3: FaceType,
+    ) -> Option<(CornerCubelet, CyclicGroup<3>)> {
+        CornerType::from_faces(f1, f2, f3)
+            .map(|(corner, orientation)| (CornerCubelet(corner), orientation))
+    }
+}
+
 impl Enumerable for CornerCubelet {
     const N: usize = CornerType::N;
 
@@ -90,6 +172,13 @@ impl Enumerable for CornerCubelet {
 /// Ignores centre orientation.
 pub type CentrePermutation = SymmetricGroup<CentreCubelet>;
 
+/// Represents an element of the symmetric group of the centre pieces of a odd-sized cube,
+/// including centre orientation, as on a supercube or picture cube.
+/// Centres have a rotational cyclic group of order 4.
+/// Orientation 0 is oriented "correctly", that is, the centre points towards the first face in [FaceType::adjacent].
+/// Orientations 1, 2, 3 are clockwise quarter turns from this direction.
+pub type SupercubeCentrePermutation = OrientedSymmetricGroup<CentreCubelet, 4>;
+
 /// Represents an element of the symmetric group of the 12 centred edge pieces of an odd-sized cube.
 /// Edges have a rotational cyclic group of order 2.
 /// That is, in any position, an edge may be positioned in one of two orientations.
@@ -103,9 +192,51 @@ pub type EdgePermutation = OrientedSymmetricGroup<EdgeCubelet, 2>;
 /// Orientations 1, 2 are clockwise 120-degree and 240-degree turns.
 pub type CornerPermutation = OrientedSymmetricGroup<CornerCubelet, 3>;
 
+fn mirror_face(face: FaceType) -> FaceType {
+    match face {
+        R => L,
+        L => R,
+        face => face,
+    }
+}
+
+fn mirror_edge(edge: EdgeType) -> EdgeType {
+    let [key, other] = edge.faces();
+    EdgeType::from_faces_ordered(key, mirror_face(other))
+        .expect("the key sticker is never on R or L")
+}
+
+fn mirror_corner(corner: CornerType) -> CornerType {
+    match corner {
+        FUR => FUL,
+        FUL => FUR,
+        FDR => FDL,
+        FDL => FDR,
+        BUR => BUL,
+        BUL => BUR,
+        BDR => BDL,
+        BDL => BDR,
+    }
+}
+
+/// Whether the given indices are `0..n` in some order.
+fn is_permutation(indices: impl Iterator<Item = usize>, n: usize) -> bool {
+    let mut seen = vec![false; n];
+    for i in indices {
+        if i >= n || seen[i] {
+            return false;
+        }
+        seen[i] = true;
+    }
+    true
+}
+
 /// Represents a permutation of a 3x3x3 cube.
 /// This is the direct product of a centre permutation, edge permutation, and corner permutation group.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+///
+/// When serialized, the state is not checked to be solvable; use [CubePermutation3::is_solvable] if needed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CubePermutation3 {
     centres: CentrePermutation,
     edges: EdgePermutation,
@@ -167,6 +298,66 @@ impl CentrePermutation {
     }
 }
 
+impl SupercubeCentrePermutation {
+    /// A face turn rotates the centre of that face in place.
+    pub fn from_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
+        let quarter_turns = match rotation_type {
+            RotationType::Normal => 1,
+            RotationType::Double => 2,
+            RotationType::Inverse => 3,
+        };
+        Self::new_unchecked(CentreCubelet::enumerate().map(|centre| {
+            if centre.0 == face {
+                (centre, CyclicGroup::new(quarter_turns))
+            } else {
+                (centre, CyclicGroup::identity())
+            }
+        }))
+    }
+
+    /// Slice turns are inferred from their axis, as in [CentrePermutation::from_normal_slice_turn].
+    pub fn from_normal_slice_turn(axis: Axis) -> Self {
+        let front = match axis {
+            Axis::FB => F,
+            Axis::RL => R,
+            Axis::UD => U,
+        };
+        // The face that `face` is moved to by a clockwise turn about `front`.
+        let turn = |face: FaceType| {
+            let adjacent = front.adjacent();
+            match adjacent.iter().position(|&other| other == face) {
+                Some(i) => adjacent[(i + 1) % 4],
+                None => face,
+            }
+        };
+
+        Self::new_unchecked(CentreCubelet::enumerate().map(|centre| {
+            if centre.0.axis() == axis {
+                return (centre, CyclicGroup::identity());
+            }
+            // Track where the direction of orientation 0 ends up.
+            let target = turn(centre.0);
+            let direction = turn(centre.0.adjacent()[0]);
+            let orientation = target
+                .adjacent()
+                .iter()
+                .position(|&face| face == direction)
+                .expect("a turn preserves adjacency");
+            (CentreCubelet(target), CyclicGroup::new(orientation as u8))
+        }))
+    }
+
+    /// Slice turns are inferred from their axis, as in [CentrePermutation::from_normal_slice_turn].
+    pub fn from_slice_turn(axis: Axis, rotation_type: RotationType) -> Self {
+        let s = Self::from_normal_slice_turn(axis);
+        match rotation_type {
+            RotationType::Normal => s,
+            RotationType::Double => s.op(s),
+            RotationType::Inverse => s.inverse(),
+        }
+    }
+}
+
 impl EdgePermutation {
     pub fn from_normal_face_turn(face: FaceType) -> Self {
         match face {
@@ -263,9 +454,12 @@ impl EdgePermutation {
         }
     }
 
-    /// TODO: Cache the results of this function if profiling indicates it is a hot path.
-    /// For example, using the `memoize` crate.
+    /// Looks up the permutation in a table, since this is called for every move in [CubePermutation3::from_move].
     pub fn from_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
+        EDGE_FACE_TURNS[face.index()][rotation_type as usize]
+    }
+
+    fn compute_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
         let s = Self::from_normal_face_turn(face);
         match rotation_type {
             RotationType::Normal => s,
@@ -418,8 +612,12 @@ impl CornerPermutation {
         }
     }
 
-    /// TODO: Cache the results of this function if profiling indicates it is a hot path.
+    /// Looks up the permutation in a table, since this is called for every move in [CubePermutation3::from_move].
     pub fn from_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
+        CORNER_FACE_TURNS[face.index()][rotation_type as usize]
+    }
+
+    fn compute_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
         let s = Self::from_normal_face_turn(face);
         match rotation_type {
             RotationType::Normal => s,
@@ -461,8 +659,13 @@ impl Unital for CubePermutation3 {
     }
 }
 
+/// The alternate format `{:#}` writes the [CubePermutation3::summary] of the permutation.
 impl Display for CubePermutation3 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return write!(f, "{}", self.summary());
+        }
+
         let c = self.corners.to_string();
         let e = self.edges.to_string();
         let x = self.centres.to_string();
@@ -481,6 +684,64 @@ impl Display for CubePermutation3 {
 }
 
 impl CubePermutation3 {
+    /// Does not check that the resulting permutation can be reached from the solved state.
+    pub fn new_unchecked(
+        centres: CentrePermutation,
+        edges: EdgePermutation,
+        corners: CornerPermutation,
+    ) -> Self {
+        Self {
+            centres,
+            edges,
+            corners,
+        }
+    }
+
+    /// Writes the centres, edges, and corners that are not solved in cycle notation, in that order,
+    /// annotating pieces that are reoriented as in [OrientedSymmetricGroup::cycles].
+    /// Solved pieces are omitted, so a nearly solved cube has a short summary.
+    /// The solved cube is written `()`.
+    pub fn summary(&self) -> String {
+        let parts = [
+            self.centres.cycles(),
+            self.edges.cycles(),
+            self.corners.cycles(),
+        ]
+        .into_iter()
+        .filter(|cycles| cycles != "()")
+        .collect::<Vec<_>>();
+        if parts.is_empty() {
+            "()".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+
+    /// Whether the given edge is in its home position with orientation zero.
+    /// The centres are not taken into account, so after a rotation some edges may not count as solved.
+    pub fn edge_solved(&self, edge: EdgeCubelet) -> bool {
+        self.edges.act(&(edge, CyclicGroup::identity())) == (edge, CyclicGroup::identity())
+    }
+
+    /// Whether the given corner is in its home position with orientation zero, as in [CubePermutation3::edge_solved].
+    pub fn corner_solved(&self, corner: CornerCubelet) -> bool {
+        self.corners.act(&(corner, CyclicGroup::identity())) == (corner, CyclicGroup::identity())
+    }
+
+    /// The edges and corners that are solved, as in [CubePermutation3::edge_solved] and [CubePermutation3::corner_solved].
+    pub fn solved_pieces(&self) -> (Vec<EdgeCubelet>, Vec<CornerCubelet>) {
+        (
+            EdgeCubelet::enumerate()
+                .into_iter()
+                .filter(|&edge| self.edge_solved(edge))
+                .collect(),
+            CornerCubelet::enumerate()
+                .into_iter()
+                .filter(|&corner| self.corner_solved(corner))
+                .collect(),
+        )
+    }
+
     pub fn from_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
         Self {
             centres: CentrePermutation::identity(),
@@ -503,7 +764,29 @@ impl CubePermutation3 {
         }
     }
 
+    /// Every move on a 3x3x3 cube is looked up in a table,
+    /// since graph generation in [crate::intuitive] performs the same few moves very many times.
+    ///
+    /// # Panics
+    /// Panics if the move turns layers that a 3x3x3 cube does not have; see [CubePermutation3::try_from_move].
     pub fn from_move(mv: Move) -> Self {
+        Self::try_from_move(mv).expect("invalid move on a 3x3x3 cube")
+    }
+
+    /// The permutation caused by a move, or `None` if the move turns layers that a 3x3x3 cube does not have,
+    /// such as a move written for a larger cube.
+    pub fn try_from_move(mv: Move) -> Option<Self> {
+        // The table holds every move of a 3x3x3 cube.
+        MOVES.get(&mv.key()).copied()
+    }
+
+    /// Computes the permutation caused by a move without using the table in [CubePermutation3::from_move].
+    /// Returns `None` if the move turns a layer past depth 2, or no layers at all.
+    pub(crate) fn compute_move(mv: Move) -> Option<Self> {
+        if mv.start_depth >= mv.end_depth || mv.end_depth > 3 {
+            return None;
+        }
+
         // Construct the move from commuting slice moves.
         let mut g = Self::identity();
 
@@ -512,31 +795,219 @@ impl CubePermutation3 {
             Axis::RL => R,
             Axis::UD => U,
         };
-        let back = match mv.axis {
-            Axis::FB => B,
-            Axis::RL => L,
-            Axis::UD => D,
-        };
+        let back = front.opposite();
 
         for i in mv.start_depth..mv.end_depth {
             let h = match i {
                 0 => Self::from_face_turn(front, mv.rotation_type),
                 1 => Self::from_slice_turn(mv.axis, mv.rotation_type),
-                2 => Self::from_face_turn(back, mv.rotation_type.inverse()),
-                _ => panic!("invalid move on a 3x3x3 cube: {:?}", mv),
+                _ => Self::from_face_turn(back, mv.rotation_type.inverse()),
             };
             g = g.op(h);
         }
 
-        g
+        Some(g)
     }
 
-    pub fn from_move_sequence(moves: MoveSequence) -> Self {
+    /// The permutation caused by performing each move in turn,
+    /// or `None` if some move turns layers that a 3x3x3 cube does not have.
+    pub fn from_move_sequence(moves: MoveSequence) -> Option<Self> {
         let mut g = Self::identity();
         for mv in moves.moves.into_iter().rev() {
-            g = g.op(Self::from_move(mv));
+            g = g.op(Self::try_from_move(mv)?);
+        }
+        Some(g)
+    }
+
+    /// Returns true if this permutation can be reached from the solved state by some sequence of moves.
+    ///
+    /// The centres must be arranged as they would be after rotating the whole cube,
+    /// and the orientations of the edges and of the corners must each sum to zero.
+    /// Each face turn is an odd permutation of both the edges and the corners,
+    /// and each slice turn is an odd permutation of both the edges and the centres,
+    /// so the signs of the three permutations must multiply to one.
+    pub fn is_solvable(&self) -> bool {
+        // Slice turns move the centres in the same way as rotations of the whole cube.
+        ROTATIONS
+            .iter()
+            .any(|(_, rotation)| rotation.centres == self.centres)
+            && self.centres.sign() * self.edges.sign() * self.corners.sign() == 1
+            && self.edges.total_orientation() == CyclicGroup::identity()
+            && self.corners.total_orientation() == CyclicGroup::identity()
+    }
+
+    /// Returns a permutation chosen uniformly at random from all permutations
+    /// that can be reached from the solved state, using the given source of randomness.
+    /// A seeded generator always produces the same sequence of permutations.
+    #[cfg(feature = "rand")]
+    pub fn random_solvable<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+        use rand::seq::SliceRandom;
+
+        let mut edges = EdgeCubelet::enumerate();
+        edges.shuffle(rng);
+        let mut corners = CornerCubelet::enumerate();
+        corners.shuffle(rng);
+
+        // The edge and corner permutations must have the same parity.
+        // Swapping two corners pairs up the odd and even corner permutations,
+        // so the result is still uniformly distributed.
+        if SymmetricGroup::new_unchecked(edges).sign()
+            != SymmetricGroup::new_unchecked(corners).sign()
+        {
+            corners.swap(0, 1);
+        }
+
+        let edges = edges
+            .into_iter()
+            .zip(random_orientations::<R, 2>(rng, EdgeCubelet::N))
+            .collect::<Vec<_>>();
+        let corners = corners
+            .into_iter()
+            .zip(random_orientations::<R, 3>(rng, CornerCubelet::N))
+            .collect::<Vec<_>>();
+
+        CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            EdgePermutation::new_unchecked(edges.try_into().unwrap()),
+            CornerPermutation::new_unchecked(corners.try_into().unwrap()),
+        )
+    }
+
+    /// The 24 rotations of the whole cube, each given as a sequence of at most two rotations
+    /// together with the permutation that it causes.
+    pub fn rotations() -> &'static [(MoveSequence, CubePermutation3)] {
+        &ROTATIONS
+    }
+
+    /// Returns true if every piece is in its original position and orientation.
+    pub fn is_solved(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Returns true if this permutation is a rotation of the whole cube,
+    /// so every face is a single colour, but the cube may not be in its original orientation.
+    pub fn is_solved_up_to_rotation(&self) -> bool {
+        ROTATIONS.iter().any(|(_, rotation)| rotation == self)
+    }
+
+    /// Returns the order of this permutation.
+    /// This agrees with [Group::order], but is computed from the cycles of each kind of piece.
+    pub fn cycle_order(&self) -> usize {
+        lcm(
+            self.centres.cycle_order(),
+            lcm(self.edges.cycle_order(), self.corners.cycle_order()),
+        )
+    }
+
+    /// Renders this permutation onto the stickers of a 3x3x3 cube.
+    pub fn to_cube(self) -> Cube<3> {
+        Cube::from_permutation(self)
+    }
+
+    /// Writes this permutation as a 54 character facelet string, with the faces in the order U R F D L B,
+    /// as used by Kociemba's solver. It can be read back with [Cube::from_facelets] and [Cube::to_permutation].
+    pub fn to_facelets(self) -> String {
+        self.to_cube().to_facelets()
+    }
+
+    /// Reflects this permutation across the plane between the R and L faces, swapping the R and L sides of the cube.
+    /// If a move sequence performs this permutation, then mirroring each move with [MoveSequence::mirror_lr]
+    /// gives a move sequence that performs the reflected permutation.
+    ///
+    /// The reflection reverses the direction of every twist, so corner orientations are negated.
+    /// No edge has its key sticker on the R or L face, so edge orientations are unchanged.
+    pub fn mirror_lr(&self) -> Self {
+        Self {
+            centres: CentrePermutation::new_unchecked(CentreCubelet::enumerate().map(|centre| {
+                let target = self.centres.act(&CentreCubelet(mirror_face(centre.0)));
+                CentreCubelet(mirror_face(target.0))
+            })),
+            edges: EdgePermutation::new_unchecked(EdgeCubelet::enumerate().map(|edge| {
+                let (target, orientation) = self
+                    .edges
+                    .act(&(EdgeCubelet(mirror_edge(edge.0)), CyclicGroup::identity()));
+                (EdgeCubelet(mirror_edge(target.0)), orientation)
+            })),
+            corners: CornerPermutation::new_unchecked(CornerCubelet::enumerate().map(|corner| {
+                let (target, orientation) = self.corners.act(&(
+                    CornerCubelet(mirror_corner(corner.0)),
+                    CyclicGroup::identity(),
+                ));
+                (
+                    CornerCubelet(mirror_corner(target.0)),
+                    orientation.inverse(),
+                )
+            })),
+        }
+    }
+
+    /// Packs this permutation into 16 bytes, so that two permutations are equal exactly when their encodings are.
+    ///
+    /// Each centre takes 3 bits for its target, each edge takes 4 bits for its target and 1 for its orientation,
+    /// and each corner takes 3 bits for its target and 2 for its orientation.
+    /// These 118 bits are stored in little-endian order, and the remaining high bits are zero.
+    pub fn encode(&self) -> [u8; 16] {
+        let mut bits = 0u128;
+        let mut offset = 0;
+        let mut push = |value: usize, width: u32| {
+            bits |= (value as u128) << offset;
+            offset += width;
+        };
+        for centre in CentreCubelet::enumerate() {
+            push(self.centres.act(&centre).index(), 3);
+        }
+        for edge in EdgeCubelet::enumerate() {
+            let (target, orientation) = self.edges.act(&(edge, CyclicGroup::identity()));
+            push(target.index(), 4);
+            push(orientation.get_value() as usize, 1);
+        }
+        for corner in CornerCubelet::enumerate() {
+            let (target, orientation) = self.corners.act(&(corner, CyclicGroup::identity()));
+            push(target.index(), 3);
+            push(orientation.get_value() as usize, 2);
+        }
+        bits.to_le_bytes()
+    }
+
+    /// Reverses [CubePermutation3::encode].
+    /// Returns `None` if the bytes are not the encoding of any permutation.
+    /// As with [CubePermutation3::new_unchecked], the permutation is not checked to be solvable.
+    pub fn decode(bytes: [u8; 16]) -> Option<Self> {
+        let bits = u128::from_le_bytes(bytes);
+        let mut offset = 0;
+        let mut pop = |width: u32| {
+            let value = (bits >> offset) as usize & ((1 << width) - 1);
+            offset += width;
+            value
+        };
+        let centres = CentreCubelet::enumerate().map(|_| pop(3));
+        let edges = EdgeCubelet::enumerate().map(|_| (pop(4), pop(1)));
+        let corners = CornerCubelet::enumerate().map(|_| (pop(3), pop(2)));
+
+        if bits >> offset != 0
+            || !is_permutation(centres.iter().copied(), CentreCubelet::N)
+            || !is_permutation(edges.iter().map(|&(target, _)| target), EdgeCubelet::N)
+            || !is_permutation(corners.iter().map(|&(target, _)| target), CornerCubelet::N)
+            || corners.iter().any(|&(_, orientation)| orientation >= 3)
+        {
+            return None;
         }
-        g
+
+        Some(Self {
+            centres: CentrePermutation::new_unchecked(centres.map(CentreCubelet::from_index)),
+            edges: EdgePermutation::new_unchecked(edges.map(|(target, orientation)| {
+                (
+                    EdgeCubelet::from_index(target),
+                    CyclicGroup::new(orientation as u8),
+                )
+            })),
+            corners: CornerPermutation::new_unchecked(corners.map(|(target, orientation)| {
+                (
+                    CornerCubelet::from_index(target),
+                    CyclicGroup::new(orientation as u8),
+                )
+            })),
+        })
     }
 
     /// Get a reference to the cube permutation's centres.
@@ -555,9 +1026,138 @@ impl CubePermutation3 {
     }
 }
 
+/// Chooses `count` orientations uniformly at random, subject to their sum being zero.
+#[cfg(feature = "rand")]
+fn random_orientations<R: rand::Rng + ?Sized, const K: u8>(
+    rng: &mut R,
+    count: usize,
+) -> Vec<CyclicGroup<K>> {
+    let mut orientations = (1..count)
+        .map(|_| CyclicGroup::new(rng.gen_range(0..K)))
+        .collect::<Vec<_>>();
+    let sum = orientations
+        .iter()
+        .fold(CyclicGroup::identity(), |sum, &orientation| {
+            sum.op(orientation)
+        });
+    orientations.push(sum.inverse());
+    orientations
+}
+
+/// Represents a permutation of a 2x2x2 cube, which consists only of corners.
+///
+/// A 2x2x2 cube has no centres to fix its orientation in space,
+/// so many permutations represent the same state of the cube up to rotation.
+/// Solvers should treat the BDL corner as fixed, turning only the F, R and U faces,
+/// so that the solved state is the identity.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubePermutation2 {
+    corners: CornerPermutation,
+}
+
+impl Magma for CubePermutation2 {
+    fn op(self, other: Self) -> Self {
+        Self {
+            corners: self.corners.op(other.corners),
+        }
+    }
+}
+
+impl Semigroup for CubePermutation2 {}
+
+impl InverseSemigroup for CubePermutation2 {
+    fn inverse(&self) -> Self {
+        Self {
+            corners: self.corners.inverse(),
+        }
+    }
+}
+
+impl Unital for CubePermutation2 {
+    fn identity() -> Self {
+        Self {
+            corners: CornerPermutation::identity(),
+        }
+    }
+}
+
+impl Display for CubePermutation2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.corners)
+    }
+}
+
+impl CubePermutation2 {
+    /// Does not check that the resulting permutation can be reached from the solved state.
+    pub fn new_unchecked(corners: CornerPermutation) -> Self {
+        Self { corners }
+    }
+
+    pub fn from_face_turn(face: FaceType, rotation_type: RotationType) -> Self {
+        Self {
+            corners: CornerPermutation::from_face_turn(face, rotation_type),
+        }
+    }
+
+    /// Wide moves and rotations must be sized for a 2x2x2 cube, for instance using [Move::from_str_sized].
+    ///
+    /// # Panics
+    /// Panics if the move turns layers that a 2x2x2 cube does not have; see [CubePermutation2::try_from_move].
+    pub fn from_move(mv: Move) -> Self {
+        Self::try_from_move(mv).expect("invalid move on a 2x2x2 cube")
+    }
+
+    /// The permutation caused by a move, or `None` if the move turns layers that a 2x2x2 cube does not have.
+    pub fn try_from_move(mv: Move) -> Option<Self> {
+        let front = match mv.axis {
+            Axis::FB => F,
+            Axis::RL => R,
+            Axis::UD => U,
+        };
+
+        let mut g = Self::identity();
+        for i in mv.start_depth..mv.end_depth {
+            let h = match i {
+                0 => Self::from_face_turn(front, mv.rotation_type),
+                1 => Self::from_face_turn(front.opposite(), mv.rotation_type.inverse()),
+                _ => return None,
+            };
+            g = g.op(h);
+        }
+        Some(g)
+    }
+
+    /// The permutation caused by performing each move in turn,
+    /// or `None` if some move turns layers that a 2x2x2 cube does not have.
+    pub fn from_move_sequence(moves: MoveSequence) -> Option<Self> {
+        let mut g = Self::identity();
+        for mv in moves.moves.into_iter().rev() {
+            g = g.op(Self::try_from_move(mv)?);
+        }
+        Some(g)
+    }
+
+    /// Get a reference to the cube permutation's corners.
+    pub fn corners(&self) -> &CornerPermutation {
+        &self.corners
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
     use super::*;
+    use crate::scramble::random_moves;
+
+    /// The moves used to generate scrambles, including some slice moves, wide moves and rotations.
+    fn scramble_moves() -> Vec<Move> {
+        "F F' F2 R R' R2 U U' U2 B B' B2 L L' L2 D D' D2 M E' S2 Rw y"
+            .split(' ')
+            .map(|mv| mv.parse().unwrap())
+            .collect()
+    }
 
     #[test]
     fn group_operation() {
@@ -644,6 +1244,19 @@ mod tests {
         assert_eq!(turn_f2, turn_f.op(turn_f));
         assert_eq!(turn_f.inverse(), turn_f.op(turn_f).op(turn_f));
 
+        let turn_u = EdgePermutation::from_face_turn(U, RotationType::Normal);
+        assert_eq!(
+            turn_u
+                .orbit(&(EdgeCubelet(UR), CyclicGroup::identity()))
+                .into_iter()
+                .map(|(edge, _)| edge.0)
+                .collect::<Vec<_>>(),
+            [UR, UF, UL, UB]
+        );
+        // The orbit follows each piece to where the turn moves it, like the cycle notation.
+        assert_eq!(turn_u.cycles(), "(UR UF UL UB)");
+        assert_eq!(turn_u.orbit(&(EdgeCubelet(DR), CyclicGroup::new(1))).len(), 1);
+
         let turn_r = EdgePermutation::from_face_turn(R, RotationType::Normal);
 
         // Order is reversed to speedcubing notation!
@@ -688,6 +1301,41 @@ mod tests {
         assert_eq!(fr.order(), 7);
     }
 
+    #[test]
+    fn cached_face_turns() {
+        for face in FaceType::enumerate() {
+            for rotation_type in ROTATION_TYPES {
+                assert_eq!(
+                    EdgePermutation::from_face_turn(face, rotation_type),
+                    EdgePermutation::compute_face_turn(face, rotation_type)
+                );
+                assert_eq!(
+                    CornerPermutation::from_face_turn(face, rotation_type),
+                    CornerPermutation::compute_face_turn(face, rotation_type)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cached_moves() {
+        assert_eq!(MOVES.len(), 54);
+        for (&(axis, rotation_type, start_depth, end_depth), &permutation) in MOVES.iter() {
+            let mv = Move::new(axis, rotation_type, start_depth, end_depth);
+            assert_eq!(
+                Some(permutation),
+                CubePermutation3::compute_move(mv),
+                "{}",
+                mv
+            );
+        }
+        // Slice moves, wide moves, and rotations are all cached.
+        for mv in ["R", "M'", "r2", "Lw'", "x", "E2", "S", "b", "D'", "U2'"] {
+            let key = mv.parse::<Move>().unwrap().key();
+            assert!(MOVES.contains_key(&key), "{}", mv);
+        }
+    }
+
     #[test]
     fn u_perm() {
         // R' U R' U' R' U' R' U R U R2 is a U permutation.
@@ -711,6 +1359,7 @@ mod tests {
         // Thus, it should have order 3.
         assert_eq!(operation.order(), 3);
         // It should also be the 3-cycle (UR UL UB).
+        assert_eq!(operation.cycles(), "(UR UL UB)");
         assert_eq!(
             operation.act(&(EdgeCubelet(UR), CyclicGroup::new(0))),
             (EdgeCubelet(UL), CyclicGroup::new(0))
@@ -723,6 +1372,36 @@ mod tests {
             operation.act(&(EdgeCubelet(UB), CyclicGroup::new(0))),
             (EdgeCubelet(UR), CyclicGroup::new(0))
         );
+        // Writing the cycle out by hand gives the same map.
+        assert_eq!(
+            EdgePermutation::from_cycles(&[&[
+                (EdgeCubelet(UR), CyclicGroup::new(0)),
+                (EdgeCubelet(UL), CyclicGroup::new(0)),
+                (EdgeCubelet(UB), CyclicGroup::new(0)),
+            ]]),
+            Some(EdgePermutation::new_unchecked([
+                (EdgeCubelet(UL), CyclicGroup::new(0)),
+                (EdgeCubelet(UF), CyclicGroup::new(0)),
+                (EdgeCubelet(UB), CyclicGroup::new(0)),
+                (EdgeCubelet(UR), CyclicGroup::new(0)),
+                (EdgeCubelet(DR), CyclicGroup::new(0)),
+                (EdgeCubelet(DF), CyclicGroup::new(0)),
+                (EdgeCubelet(DL), CyclicGroup::new(0)),
+                (EdgeCubelet(DB), CyclicGroup::new(0)),
+                (EdgeCubelet(FR), CyclicGroup::new(0)),
+                (EdgeCubelet(FL), CyclicGroup::new(0)),
+                (EdgeCubelet(BR), CyclicGroup::new(0)),
+                (EdgeCubelet(BL), CyclicGroup::new(0)),
+            ]))
+        );
+        assert_eq!(
+            EdgePermutation::from_cycles(&[&[
+                (EdgeCubelet(UR), CyclicGroup::new(0)),
+                (EdgeCubelet(UL), CyclicGroup::new(0)),
+                (EdgeCubelet(UB), CyclicGroup::new(0)),
+            ]]),
+            Some(operation)
+        );
     }
 
     #[test]
@@ -758,6 +1437,7 @@ mod tests {
             operation.act(&(CornerCubelet(BUL), CyclicGroup::new(0))),
             (CornerCubelet(BDL), CyclicGroup::new(2))
         );
+        assert_eq!(operation.cycles(), "(BUL BDL BDR)");
     }
 
     #[test]
@@ -766,6 +1446,177 @@ mod tests {
         let u = CubePermutation3::from_face_turn(U, RotationType::Normal);
         let h = m2.op(u).op(m2).op(u).op(u).op(m2).op(u).op(m2);
         assert_eq!(h.order(), 2);
+        assert_eq!(h.edges().cycles(), "(UR UL)(UF UB)");
+        assert_eq!(h.corners().cycles(), "()");
+    }
+
+    #[test]
+    fn order() {
+        for (moves, order) in [
+            ("R U", 105),
+            ("R U R' U'", 6),
+            ("R F", 105),
+            ("R U2 D' B D'", 1260),
+        ] {
+            let g = CubePermutation3::from_move_sequence(moves.parse().unwrap()).unwrap();
+            assert_eq!(g.cycle_order(), order);
+            assert_eq!(g.order(), order);
+        }
+    }
+
+    #[test]
+    fn commutators() {
+        let r = CubePermutation3::from_face_turn(R, RotationType::Normal);
+        let u = CubePermutation3::from_face_turn(U, RotationType::Normal);
+        let sexy = r.commutator(&u);
+        assert_eq!(
+            sexy,
+            CubePermutation3::from_move_sequence("R U R' U'".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            sexy,
+            CubePermutation3::from_move_sequence("[R, U]".parse().unwrap()).unwrap()
+        );
+        assert_eq!(
+            u.conjugate(&r),
+            CubePermutation3::from_move_sequence("[R: U]".parse().unwrap()).unwrap()
+        );
+        // A commutator of two face turns only affects the pieces near where the faces meet.
+        assert_eq!(sexy.edges().cycles(), "(UR UB FR)");
+        assert_eq!(sexy.corners().cycles(), "(FUR FDR+)(BUR BUL-)");
+        assert_eq!(
+            u.conjugate(&r),
+            CubePermutation3::from_move_sequence("R U R'".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn repeated_groups() {
+        let sexy = CubePermutation3::from_move_sequence("R U R' U'".parse().unwrap()).unwrap();
+        let sexy6 = CubePermutation3::from_move_sequence("(R U R' U')6".parse().unwrap()).unwrap();
+        assert_eq!(sexy6, CubePermutation3::identity());
+        let sexy5 = CubePermutation3::from_move_sequence("(R U R' U')5".parse().unwrap()).unwrap();
+        assert_eq!(sexy5, sexy.inverse());
+        assert_eq!(
+            sexy5,
+            CubePermutation3::from_move_sequence("(R U R' U')'".parse().unwrap()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let scramble = "U2 B D' B U2 L F' D B' U2 D R' U2 B R2 D' B' D2 L B2 F2 U D2 F B2 x y"
+            .parse::<MoveSequence>()
+            .unwrap();
+        let permutation = CubePermutation3::from_move_sequence(scramble).unwrap();
+        let json = serde_json::to_string(&permutation).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CubePermutation3>(&json).unwrap(),
+            permutation
+        );
+
+        let json = serde_json::to_string(&CubePermutation3::identity()).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"centres":[0,1,2,3,4,5],"#,
+                r#""edges":[[0,0],[1,0],[2,0],[3,0],[4,0],[5,0],[6,0],[7,0],[8,0],[9,0],[10,0],[11,0]],"#,
+                r#""corners":[[0,0],[1,0],[2,0],[3,0],[4,0],[5,0],[6,0],[7,0]]}"#
+            )
+        );
+
+        // Unsolvable states can still be read.
+        let twisted = json.replace(r#""corners":[[0,0]"#, r#""corners":[[0,1]"#);
+        let twisted = serde_json::from_str::<CubePermutation3>(&twisted).unwrap();
+        assert!(!twisted.is_solvable());
+    }
+
+    #[test]
+    fn facelets() {
+        assert_eq!(
+            CubePermutation3::identity().to_facelets(),
+            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB"
+        );
+        assert_eq!(
+            CubePermutation3::from_move("U".parse().unwrap()).to_facelets(),
+            "UUUUUUUUUBBBRRRRRRRRRFFFFFFDDDDDDDDDFFFLLLLLLLLLBBBBBB"
+        );
+        for scramble in [
+            "R U2 F' L D B2 R' U F2 D' L2 B",
+            "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2",
+            "M E S x y",
+        ] {
+            let permutation =
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+            let facelets = permutation.to_facelets();
+            assert_eq!(facelets.len(), 54);
+            assert_eq!(
+                Cube::<3>::from_facelets(&facelets)
+                    .unwrap()
+                    .to_permutation(),
+                Some(permutation),
+                "{}",
+                scramble
+            );
+        }
+    }
+
+    #[test]
+    fn solved_pieces() {
+        let (edges, corners) = CubePermutation3::identity().solved_pieces();
+        assert_eq!(edges, EdgeCubelet::enumerate());
+        assert_eq!(corners, CornerCubelet::enumerate());
+
+        let r = CubePermutation3::from_move("R".parse().unwrap());
+        let (edges, corners) = r.solved_pieces();
+        assert_eq!(edges.len(), 8);
+        assert_eq!(corners.len(), 4);
+        for edge in [UR, DR, FR, BR] {
+            assert!(!r.edge_solved(EdgeCubelet(edge)));
+            assert!(!edges.contains(&EdgeCubelet(edge)));
+        }
+        for corner in [FUR, BUR, BDR, FDR] {
+            assert!(!r.corner_solved(CornerCubelet(corner)));
+            assert!(!corners.contains(&CornerCubelet(corner)));
+        }
+        assert!(r.edge_solved(EdgeCubelet(UL)));
+        assert!(r.corner_solved(CornerCubelet(FUL)));
+
+        // A corner that is in its home position but twisted is not solved.
+        let twist = "R' D' R D R' D' R D";
+        let twisted = CubePermutation3::from_move_sequence(
+            format!("{0} U {0} {0} U'", twist).parse().unwrap(),
+        )
+        .unwrap();
+        let (edges, corners) = twisted.solved_pieces();
+        assert_eq!(edges.len(), 12);
+        assert_eq!(corners.len(), 6);
+        assert_eq!(
+            twisted
+                .corners()
+                .act(&(CornerCubelet(FUR), CyclicGroup::identity()))
+                .0,
+            CornerCubelet(FUR)
+        );
+        assert!(!twisted.corner_solved(CornerCubelet(FUR)));
+    }
+
+    #[test]
+    fn summary() {
+        assert_eq!(CubePermutation3::identity().summary(), "()");
+        let t_perm = CubePermutation3::from_move_sequence(
+            "R U R' U' R' F R2 U' R' U' R U R' F'".parse().unwrap(),
+        )
+        .unwrap();
+        // The T permutation swaps two edges and two corners.
+        assert_eq!(t_perm.summary(), "(UR UL) (FUR BUR)");
+        assert_eq!(format!("{:#}", t_perm), t_perm.summary());
+        let sexy = CubePermutation3::from_move_sequence("R U R' U'".parse().unwrap()).unwrap();
+        assert_eq!(sexy.summary(), "(UR UB FR) (FUR FDR+)(BUR BUL-)");
+        // The centres are written first.
+        let m2 = CubePermutation3::from_move("M2".parse().unwrap());
+        assert_eq!(m2.summary(), "(F B)(U D) (UF DB)(UB DF)");
     }
 
     #[test]
@@ -775,7 +1626,464 @@ mod tests {
         let superflip = "U R2 F B R B2 R U2 L B2 R U' D' R2 F R' L B2 U2 F2"
             .parse::<MoveSequence>()
             .unwrap();
-        let g = CubePermutation3::from_move_sequence(superflip);
+        let g = CubePermutation3::from_move_sequence(superflip).unwrap();
         assert_eq!(g.order(), 2);
+        assert_eq!(Group::order(&g), 2);
+        assert_eq!(
+            g.edges().cycles(),
+            "(UR+)(UF+)(UL+)(UB+)(DR+)(DF+)(DL+)(DB+)(FR+)(FL+)(BR+)(BL+)"
+        );
+    }
+
+    #[test]
+    fn rotations() {
+        let x = CubePermutation3::from_move("x".parse().unwrap());
+        let x_prime = CubePermutation3::from_move("x'".parse().unwrap());
+        assert_eq!(x.op(x_prime), CubePermutation3::identity());
+        assert_eq!(x.order(), 4);
+
+        // `x` turns the whole cube in the same direction as `R`, so the F centre moves to U.
+        assert_eq!(x.centres().act(&CentreCubelet(F)), CentreCubelet(U));
+        assert_eq!(
+            x.corners()
+                .act(&(CornerCubelet(FUR), CyclicGroup::new(0)))
+                .0,
+            CornerCubelet(BUR)
+        );
+
+        // After each successive `y`, the centre in the F position is the one that was previously
+        // on R, then B, then L.
+        let y = CubePermutation3::from_move("y".parse().unwrap());
+        let mut g = CubePermutation3::identity();
+        for expected in [R, B, L, F] {
+            g = y.op(g);
+            assert_eq!(
+                g.centres().unact(&CentreCubelet(F)),
+                CentreCubelet(expected)
+            );
+        }
+
+        let z = CubePermutation3::from_move("z".parse().unwrap());
+        assert_eq!(z.centres().act(&CentreCubelet(U)), CentreCubelet(R));
+    }
+
+    #[test]
+    fn move_sequence_inverse() {
+        let seq = "R U2 M' F' x".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.inverse().to_string(), "x' F M U2 R'");
+
+        let mut rng = StdRng::seed_from_u64(12345);
+        for _ in 0..50 {
+            let scramble = random_moves(&mut rng, &scramble_moves(), 25);
+            let g = CubePermutation3::from_move_sequence(scramble.clone()).unwrap();
+            let g_inverse = CubePermutation3::from_move_sequence(scramble.inverse()).unwrap();
+            assert_eq!(g_inverse, g.inverse());
+            assert_eq!(g_inverse.op(g), CubePermutation3::identity());
+        }
+    }
+
+    #[test]
+    fn to_cube() {
+        assert_eq!(CubePermutation3::identity().to_cube(), Cube::new());
+        assert_eq!(
+            Cube::<3>::new().to_permutation(),
+            Some(CubePermutation3::identity())
+        );
+
+        // Each kind of move should agree with the sticker model.
+        for mv in [
+            "F", "R'", "U2", "B", "L'", "D2", "M", "E'", "S2", "Fw", "Lw'", "x", "y2", "z'",
+        ] {
+            let mv = mv.parse::<Move>().unwrap();
+            let g = CubePermutation3::from_move(mv);
+            assert_eq!(g.to_cube(), Cube::new().perform(mv), "{}", mv);
+            assert_eq!(g.to_cube().to_permutation(), Some(g));
+        }
+
+        // A wide move over all three layers is a rotation.
+        let wide = Move::from_str_sized("3Rw", 3).unwrap();
+        assert_eq!(
+            CubePermutation3::try_from_move(wide),
+            Some(CubePermutation3::from_move("x".parse().unwrap()))
+        );
+
+        // Moves that turn layers a 3x3x3 cube does not have are rejected.
+        for mv in ["x", "4Rw", "4Uw'", "4R"] {
+            let mv = Move::from_str_sized(mv, 4).unwrap();
+            assert_eq!(CubePermutation3::try_from_move(mv), None, "{}", mv);
+        }
+        let seq = MoveSequence {
+            moves: vec![
+                "R".parse().unwrap(),
+                Move::from_str_sized("4Rw", 4).unwrap(),
+            ],
+        };
+        assert_eq!(CubePermutation3::from_move_sequence(seq), None);
+
+        let mut rng = StdRng::seed_from_u64(54321);
+        for _ in 0..50 {
+            let scramble = random_moves(&mut rng, &scramble_moves(), 25);
+            let g = CubePermutation3::from_move_sequence(scramble.clone()).unwrap();
+            let cube = Cube::new().perform_sequence(&scramble);
+            assert_eq!(g.to_cube(), cube);
+            assert_eq!(cube.to_permutation(), Some(g));
+        }
+    }
+
+    #[test]
+    fn sticker_model_fuzz() {
+        use rand::Rng;
+
+        // Every move on a 3x3x3 cube, including the slice and wide moves.
+        let moves = [Axis::FB, Axis::RL, Axis::UD]
+            .iter()
+            .flat_map(|&axis| {
+                [(0, 1), (1, 2), (2, 3), (0, 2), (1, 3), (0, 3)]
+                    .iter()
+                    .flat_map(move |&(start_depth, end_depth)| {
+                        [
+                            RotationType::Normal,
+                            RotationType::Double,
+                            RotationType::Inverse,
+                        ]
+                        .iter()
+                        .map(move |&rotation_type| {
+                            Move::new(axis, rotation_type, start_depth, end_depth)
+                        })
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let mut rng = StdRng::seed_from_u64(1314);
+        for _ in 0..2000 {
+            let scramble = MoveSequence {
+                moves: (0..rng.gen_range(1..30))
+                    .map(|_| moves[rng.gen_range(0..moves.len())])
+                    .collect(),
+            };
+            let g = CubePermutation3::from_move_sequence(scramble.clone()).unwrap();
+            let cube = Cube::new().perform_sequence(&scramble);
+            assert_eq!(g.to_cube(), cube, "{}", scramble);
+            assert_eq!(cube.to_permutation(), Some(g), "{}", scramble);
+        }
+    }
+
+    #[test]
+    fn cubelets_from_faces() {
+        assert_eq!(
+            EdgeCubelet::from_faces(U, F),
+            Some((EdgeCubelet(UF), CyclicGroup::new(0)))
+        );
+        assert_eq!(
+            EdgeCubelet::from_faces(F, R),
+            Some((EdgeCubelet(FR), CyclicGroup::new(0)))
+        );
+        // Giving the key sticker's face second flips the edge.
+        assert_eq!(
+            EdgeCubelet::from_faces(F, U),
+            Some((EdgeCubelet(UF), CyclicGroup::new(1)))
+        );
+        assert_eq!(
+            EdgeCubelet::from_faces(L, D),
+            Some((EdgeCubelet(DL), CyclicGroup::new(1)))
+        );
+        assert_eq!(EdgeCubelet::from_faces(U, D), None);
+        assert_eq!(EdgeCubelet::from_faces(R, R), None);
+
+        assert_eq!(
+            CornerCubelet::from_faces(U, R, F),
+            Some((CornerCubelet(FUR), CyclicGroup::new(0)))
+        );
+        // The U or D face is counted from the start, so giving it second or third twists the corner.
+        assert_eq!(
+            CornerCubelet::from_faces(F, U, R),
+            Some((CornerCubelet(FUR), CyclicGroup::new(1)))
+        );
+        assert_eq!(
+            CornerCubelet::from_faces(R, F, U),
+            Some((CornerCubelet(FUR), CyclicGroup::new(2)))
+        );
+        assert_eq!(
+            CornerCubelet::from_faces(L, U, F),
+            Some((CornerCubelet(FUL), CyclicGroup::new(1)))
+        );
+        // Anticlockwise and impossible sets of faces are not corners.
+        assert_eq!(CornerCubelet::from_faces(U, F, R), None);
+        assert_eq!(CornerCubelet::from_faces(U, D, R), None);
+    }
+
+    #[test]
+    fn encoding() {
+        use rand::Rng;
+        use std::collections::HashSet;
+
+        let moves = "F R U B L D M E S x y z"
+            .split(' ')
+            .map(|mv| mv.parse::<Move>().unwrap())
+            .collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(1327);
+        let mut permutations = HashSet::new();
+        let mut encodings = HashSet::new();
+        for _ in 0..1000 {
+            let scramble = MoveSequence {
+                moves: (0..rng.gen_range(0..30))
+                    .map(|_| moves[rng.gen_range(0..moves.len())])
+                    .collect(),
+            };
+            let g = CubePermutation3::from_move_sequence(scramble.clone()).unwrap();
+            assert_eq!(
+                CubePermutation3::decode(g.encode()),
+                Some(g),
+                "{}",
+                scramble
+            );
+            permutations.insert(g);
+            encodings.insert(g.encode());
+        }
+        // Distinct permutations have distinct encodings.
+        assert!(permutations.len() > 900);
+        assert_eq!(permutations.len(), encodings.len());
+
+        assert_eq!(CubePermutation3::identity().encode()[15], 0);
+        assert_eq!(CubePermutation3::decode([0xff; 16]), None);
+        // The F and R centres cannot both move to R.
+        let mut bytes = CubePermutation3::identity().encode();
+        bytes[0] |= 0b001;
+        assert_eq!(CubePermutation3::decode(bytes), None);
+    }
+
+    #[test]
+    fn is_solved() {
+        assert!(CubePermutation3::identity().is_solved());
+        assert!(CubePermutation3::identity().is_solved_up_to_rotation());
+        assert_eq!(ROTATIONS.len(), 24);
+        for (i, (_, rotation)) in ROTATIONS.iter().enumerate() {
+            assert!(!ROTATIONS[..i].iter().any(|(_, other)| other == rotation));
+        }
+
+        let y = CubePermutation3::from_move("y".parse().unwrap());
+        assert!(!y.is_solved());
+        assert!(y.is_solved_up_to_rotation());
+        let rotated =
+            CubePermutation3::from_move_sequence("x2 z y' R M' L'".parse().unwrap()).unwrap();
+        assert!(rotated.is_solved_up_to_rotation());
+
+        let scramble = CubePermutation3::from_move_sequence("R U R' U'".parse().unwrap()).unwrap();
+        assert!(!scramble.is_solved());
+        assert!(!scramble.is_solved_up_to_rotation());
+        assert!(!y.op(scramble).is_solved_up_to_rotation());
+    }
+
+    #[test]
+    fn random_solvable() {
+        let mut rng = StdRng::seed_from_u64(1310);
+        let states = (0..1000)
+            .map(|_| CubePermutation3::random_solvable(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(states.iter().all(|state| state.is_solvable()));
+
+        let mut rng = StdRng::seed_from_u64(1310);
+        assert!(states
+            .iter()
+            .all(|&state| CubePermutation3::random_solvable(&mut rng) == state));
+        let mut rng = StdRng::seed_from_u64(1311);
+        assert_ne!(CubePermutation3::random_solvable(&mut rng), states[0]);
+    }
+
+    #[test]
+    fn is_solvable() {
+        assert!(CubePermutation3::identity().is_solvable());
+        let scramble = "R U2 F' L D B2 R' U F2 D' L2 B M E' S x y2"
+            .parse::<MoveSequence>()
+            .unwrap();
+        assert!(CubePermutation3::from_move_sequence(scramble)
+            .unwrap()
+            .is_solvable());
+
+        let flipped_edge = EdgePermutation::new_unchecked(
+            EdgeCubelet::enumerate().map(|e| (e, CyclicGroup::new((e == EdgeCubelet(UF)) as u8))),
+        );
+        assert!(!CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            flipped_edge,
+            CornerPermutation::identity()
+        )
+        .is_solvable());
+
+        let twisted_corner = CornerPermutation::new_unchecked(
+            CornerCubelet::enumerate()
+                .map(|c| (c, CyclicGroup::new((c == CornerCubelet(FUR)) as u8))),
+        );
+        assert!(!CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            EdgePermutation::identity(),
+            twisted_corner
+        )
+        .is_solvable());
+
+        let mut edges = EdgeCubelet::enumerate().map(|e| (e, CyclicGroup::identity()));
+        edges.swap(0, 1);
+        let mut corners = CornerCubelet::enumerate().map(|c| (c, CyclicGroup::identity()));
+        corners.swap(0, 1);
+        let swapped_edges = EdgePermutation::new_unchecked(edges);
+        let swapped_corners = CornerPermutation::new_unchecked(corners);
+        assert!(CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            swapped_edges,
+            swapped_corners
+        )
+        .is_solvable());
+        assert!(!CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            swapped_edges,
+            CornerPermutation::identity()
+        )
+        .is_solvable());
+
+        // Swapping two opposite centres is a reflection, not a rotation.
+        let swapped_centres = CentrePermutation::new_unchecked([
+            CentreCubelet(F),
+            CentreCubelet(R),
+            CentreCubelet(D),
+            CentreCubelet(B),
+            CentreCubelet(L),
+            CentreCubelet(U),
+        ]);
+        assert!(!CubePermutation3::new_unchecked(
+            swapped_centres,
+            EdgePermutation::identity(),
+            CornerPermutation::identity()
+        )
+        .is_solvable());
+    }
+
+    #[test]
+    fn mirror_lr() {
+        let moves = [
+            "F", "R'", "U2", "B", "L", "D'", "M", "E'", "S2", "Rw", "l'", "x", "y'", "z2",
+        ];
+        for mv in moves {
+            let mv = mv.parse::<Move>().unwrap();
+            assert_eq!(
+                CubePermutation3::from_move(mv).mirror_lr(),
+                CubePermutation3::from_move(mv.mirror_lr(3).unwrap()),
+                "{}",
+                mv
+            );
+        }
+
+        let seq = "R U R' U' M x".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.mirror_lr(3).unwrap().to_string(), "L' U' L U M x");
+        let g = CubePermutation3::from_move_sequence(seq.clone()).unwrap();
+        assert_eq!(
+            g.mirror_lr(),
+            CubePermutation3::from_move_sequence(seq.mirror_lr(3).unwrap()).unwrap()
+        );
+
+        // Double turns change direction as seen from their own face.
+        let seq = "R2 U2' M2".parse::<MoveSequence>().unwrap();
+        assert_eq!(seq.mirror_lr(3).unwrap().to_string(), "L2' U2 M2'");
+        assert_eq!(
+            seq.mirror_lr(3).unwrap().mirror_lr(3).unwrap().to_string(),
+            "R2 U2' M2"
+        );
+        // Layers that do not exist on the cube cannot be reflected.
+        assert_eq!("3Rw".parse::<Move>().unwrap().mirror_lr(2), None);
+        assert_eq!("3Uw".parse::<Move>().unwrap().mirror_lr(2), None);
+        assert_eq!(g.mirror_lr().mirror_lr(), g);
+        assert_ne!(g.mirror_lr(), g);
+    }
+
+    #[test]
+    fn pocket_cube() {
+        fn parse(seq: &str) -> MoveSequence {
+            MoveSequence {
+                moves: seq
+                    .split(' ')
+                    .map(|mv| Move::from_str_sized(mv, 2).unwrap())
+                    .collect(),
+            }
+        }
+
+        // A corner commutator cycles three corners and leaves the rest in place.
+        let seq = parse("R U R' D R U' R' D'");
+        let permutation = CubePermutation2::from_move_sequence(seq.clone()).unwrap();
+        assert_eq!(
+            permutation.corners(),
+            CubePermutation3::from_move_sequence("R U R' D R U' R' D'".parse().unwrap())
+                .unwrap()
+                .corners()
+        );
+        let moved = CornerCubelet::enumerate()
+            .into_iter()
+            .filter(|&corner| {
+                permutation
+                    .corners()
+                    .act(&(corner, CyclicGroup::identity()))
+                    != (corner, CyclicGroup::identity())
+            })
+            .count();
+        assert_eq!(moved, 3);
+        assert_eq!(permutation.order(), 3);
+
+        let sune = CubePermutation2::from_move_sequence(parse("R U R' U R U2 R'")).unwrap();
+        assert_eq!(sune.order(), 6);
+
+        // Turning the L layer and then rotating the whole cube has the same effect as turning the R layer.
+        assert_eq!(
+            CubePermutation2::from_move_sequence(parse("L x")),
+            Some(CubePermutation2::from_face_turn(R, RotationType::Normal))
+        );
+
+        // A move sized for a 3x3x3 cube turns a layer that a 2x2x2 cube does not have.
+        let deep = "3Rw".parse::<Move>().unwrap();
+        assert_eq!(CubePermutation2::try_from_move(deep), None);
+        assert_eq!(
+            CubePermutation2::from_move_sequence(MoveSequence { moves: vec![deep] }),
+            None
+        );
+    }
+
+    #[test]
+    fn supercube_centres() {
+        let f = SupercubeCentrePermutation::from_face_turn(F, RotationType::Normal);
+        assert_eq!(
+            f.act(&(CentreCubelet(F), CyclicGroup::identity())),
+            (CentreCubelet(F), CyclicGroup::new(1))
+        );
+        assert_eq!(
+            f.act(&(CentreCubelet(U), CyclicGroup::identity())),
+            (CentreCubelet(U), CyclicGroup::identity())
+        );
+        assert_eq!(f.op(f).op(f).op(f), SupercubeCentrePermutation::identity());
+        assert_eq!(
+            f.op(f),
+            SupercubeCentrePermutation::from_face_turn(F, RotationType::Double)
+        );
+
+        for axis in [Axis::FB, Axis::RL, Axis::UD] {
+            let s = SupercubeCentrePermutation::from_normal_slice_turn(axis);
+            let unoriented = CentrePermutation::from_normal_slice_turn(axis);
+            for centre in CentreCubelet::enumerate() {
+                assert_eq!(
+                    s.act(&(centre, CyclicGroup::identity())).0,
+                    unoriented.act(&centre)
+                );
+            }
+            assert_eq!(s.op(s).op(s).op(s), SupercubeCentrePermutation::identity());
+        }
+
+        // An M' slice moves the F centre to the U face, where it points towards B.
+        let m = SupercubeCentrePermutation::from_normal_slice_turn(Axis::RL);
+        assert_eq!(
+            m.act(&(CentreCubelet(F), CyclicGroup::identity())),
+            (CentreCubelet(U), CyclicGroup::new(2))
+        );
+
+        // R U R' U' turns each of the R and U centres back and forth, restoring their orientation.
+        let r = SupercubeCentrePermutation::from_face_turn(R, RotationType::Normal);
+        let u = SupercubeCentrePermutation::from_face_turn(U, RotationType::Normal);
+        let sexy = u.inverse().op(r.inverse()).op(u).op(r);
+        assert_eq!(sexy, SupercubeCentrePermutation::identity());
     }
 }
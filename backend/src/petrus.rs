@@ -0,0 +1,254 @@
+use crate::{
+    cfop::{self, edges_signature, pair_signature, slot_inserts, BL_SLOT},
+    cube::{
+        EdgeType, MoveSequence,
+        {CornerType::*, EdgeType::*},
+    },
+    group::{CyclicGroup, GroupAction, Magma, Unital},
+    intuitive::{SequenceGraph, SequenceSolver},
+    metric,
+    permute::{CornerCubelet, CubePermutation3, EdgeCubelet},
+    solve::{move_sequence_to_intuitive_action, run_method, Action, Method, Step},
+};
+
+type PetrusEdgeSignature = (EdgeCubelet, CyclicGroup<2>);
+type PetrusEdgesSignature = [(EdgeCubelet, CyclicGroup<2>); 2];
+type PetrusPairSignature = (
+    (EdgeCubelet, CyclicGroup<2>),
+    (CornerCubelet, CyclicGroup<3>),
+);
+type PetrusExtensionSignature = (PetrusEdgesSignature, (CornerCubelet, CyclicGroup<3>));
+type PetrusEoSignature = [CyclicGroup<2>; 7];
+
+/// The edges outside the 2x2x3 block, which are oriented so that the rest of the cube can be solved with R and U.
+const UNSOLVED_EDGES: [EdgeType; 7] = [UF, UR, UB, UL, DR, FR, BR];
+
+fn extension_signature(cube: CubePermutation3) -> PetrusExtensionSignature {
+    (
+        edges_signature(cube, [DF, FL]),
+        cube.corners()
+            .act(&(CornerCubelet(FDL), CyclicGroup::identity())),
+    )
+}
+
+fn eo_signature(cube: CubePermutation3) -> PetrusEoSignature {
+    // As in OLL, only the orientation of the piece in each position matters.
+    UNSOLVED_EDGES.map(|edge| {
+        cube.edges()
+            .unact(&(EdgeCubelet(edge), CyclicGroup::identity()))
+            .1
+    })
+}
+
+lazy_static::lazy_static! {
+    /// Solving the whole 2x2x2 block at once needs a very large table,
+    /// so the DL and DB edges are solved first.
+    static ref PETRUS_BLOCK_EDGES: SequenceSolver<PetrusEdgesSignature> = {
+        let gen_set = vec!["F", "R", "U", "B", "L", "D"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_block1", gen_set, |cube| {
+            edges_signature(cube, [DL, DB])
+        });
+        graph.search(edges_signature(CubePermutation3::identity(), [DL, DB]), metric::etm)
+    };
+
+    /// The L and B faces are only turned in inserts, which put the DL and DB edges back.
+    static ref PETRUS_BLOCK_PAIR: SequenceSolver<PetrusPairSignature> = {
+        let gen_set = ["U", "R", "F"]
+            .into_iter()
+            .map(|x| x.to_string())
+            .chain(slot_inserts(BL_SLOT))
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_block2", gen_set, |cube| {
+            pair_signature(cube, BL, BDL)
+        });
+        graph.search(pair_signature(CubePermutation3::identity(), BL, BDL), metric::etm)
+    };
+
+    /// The R, U, and F faces do not touch the 2x2x2 block.
+    static ref PETRUS_EXTENSION: SequenceSolver<PetrusExtensionSignature> = {
+        let gen_set = vec!["R", "U", "F"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_extension", gen_set, extension_signature);
+        graph.search(extension_signature(CubePermutation3::identity()), metric::etm)
+    };
+
+    /// Each F turn is undone before the next R or U turn could move the 2x2x3 block out of the way,
+    /// so the block is preserved.
+    static ref PETRUS_EO: SequenceSolver<PetrusEoSignature> = {
+        let gen_set = vec!["R", "U", "F R F'", "F R2 F'", "F R' F'", "F' U F", "F' U2 F", "F' U' F"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_eo", gen_set, eo_signature);
+        graph.search(eo_signature(CubePermutation3::identity()), metric::etm)
+    };
+
+    /// Once every edge is oriented, the first two layers can be finished with R and U alone.
+    static ref PETRUS_DR_EDGE: SequenceSolver<PetrusEdgeSignature> = {
+        let gen_set = vec!["R", "U"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_dr", gen_set, |cube| {
+            cube.edges()
+                .act(&(EdgeCubelet(DR), CyclicGroup::identity()))
+        });
+        graph.search((EdgeCubelet(DR), CyclicGroup::identity()), metric::etm)
+    };
+
+    static ref PETRUS_FRONT_PAIR: SequenceSolver<PetrusPairSignature> = {
+        let gen_set = vec!["U", "R U R'", "R U2 R'", "R U' R'", "R' U R", "R' U2 R", "R' U' R"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_fr", gen_set, |cube| pair_signature(cube, FR, FDR));
+        graph.search(pair_signature(CubePermutation3::identity(), FR, FDR), metric::etm)
+    };
+
+    static ref PETRUS_BACK_PAIR: SequenceSolver<PetrusPairSignature> = {
+        let gen_set = vec!["U", "R' U R", "R' U2 R", "R' U' R"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("petrus_br", gen_set, |cube| pair_signature(cube, BR, BDR));
+        graph.search(pair_signature(CubePermutation3::identity(), BR, BDR), metric::etm)
+    };
+}
+
+/// Solves the 2x2x2 block made of the BDL corner and the DL, DB, and BL edges.
+pub fn block(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let edges = PETRUS_BLOCK_EDGES.solve(&edges_signature(permutation, [DL, DB]))?;
+    let permutation = CubePermutation3::from_move_sequence(edges.clone())?.op(permutation);
+    let pair = PETRUS_BLOCK_PAIR.solve(&pair_signature(permutation, BL, BDL))?;
+    Some(pair.clone().op(edges.clone()))
+}
+
+pub fn block_action(permutation: CubePermutation3) -> Option<Action> {
+    block(permutation).map(|seq| move_sequence_to_intuitive_action("2x2x2 block", seq))
+}
+
+/// Extends the 2x2x2 block to a 2x2x3 block on the left, by solving the FDL corner and the DF and FL edges.
+pub fn extension(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    PETRUS_EXTENSION.solve(&extension_signature(permutation))
+}
+
+pub fn extension_action(permutation: CubePermutation3) -> Option<Action> {
+    extension(permutation).map(|seq| move_sequence_to_intuitive_action("2x2x3 block", seq.clone()))
+}
+
+/// Orients the edges outside the 2x2x3 block, so that they can be solved without turning F or B.
+pub fn edge_orientation(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    PETRUS_EO.solve(&eo_signature(permutation))
+}
+
+pub fn edge_orientation_action(permutation: CubePermutation3) -> Option<Action> {
+    edge_orientation(permutation)
+        .map(|seq| move_sequence_to_intuitive_action("Edge orientation", seq.clone()))
+}
+
+/// Finishes the first two layers using only R and U, once the edges are oriented.
+pub fn first_two_layers(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let edge = PETRUS_DR_EDGE.solve(
+        &permutation
+            .edges()
+            .act(&(EdgeCubelet(DR), CyclicGroup::identity())),
+    )?;
+    let permutation = CubePermutation3::from_move_sequence(edge.clone())?.op(permutation);
+    let front = PETRUS_FRONT_PAIR.solve(&pair_signature(permutation, FR, FDR))?;
+    let permutation = CubePermutation3::from_move_sequence(front.clone())?.op(permutation);
+    let back = PETRUS_BACK_PAIR.solve(&pair_signature(permutation, BR, BDR))?;
+    Some(back.clone().op(front.clone()).op(edge.clone()))
+}
+
+pub fn first_two_layers_action(permutation: CubePermutation3) -> Option<Action> {
+    first_two_layers(permutation)
+        .map(|seq| move_sequence_to_intuitive_action("First two layers", seq))
+}
+
+/// The steps of the Petrus method, in the order they are performed.
+/// The last layer is solved with the same algorithms as in CFOP.
+const STEPS: [Step; 6] = [
+    ("2x2x2 block", block_action, None),
+    ("2x2x3 block", extension_action, None),
+    ("Edge orientation", edge_orientation_action, None),
+    ("First two layers", first_two_layers_action, None),
+    ("OLL", cfop::oll_action, None),
+    ("PLL", cfop::pll_action, None),
+];
+
+/// The Petrus method: a 2x2x2 block grown to 2x2x3, then edge orientation, the first two layers and the last layer.
+pub struct Petrus;
+
+impl Method for Petrus {
+    fn name(&self) -> &'static str {
+        "Petrus method"
+    }
+
+    fn steps(&self) -> &'static [Step] {
+        &STEPS
+    }
+}
+
+pub fn solve(permutation: CubePermutation3) -> Option<Action> {
+    run_method(&Petrus, permutation)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::solve::ActionSteps;
+
+    use super::*;
+
+    #[test]
+    fn petrus_solve() {
+        let mut rng = StdRng::seed_from_u64(97531);
+        for _ in 0..5 {
+            let permutation = CubePermutation3::random_solvable(&mut rng);
+            let solution = solve(permutation).unwrap();
+            let final_permutation =
+                CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                    .unwrap()
+                    .op(permutation);
+            assert_eq!(
+                final_permutation,
+                CubePermutation3::identity(),
+                "{}",
+                permutation
+            );
+
+            assert_eq!(
+                solution
+                    .step_breakdown()
+                    .into_iter()
+                    .map(|(step, _, _)| step)
+                    .collect::<Vec<_>>(),
+                STEPS.map(|(step, _, _)| step)
+            );
+
+            // After edge orientation, only R and U are needed until the last layer.
+            if let ActionSteps::Sequence { actions } = &solution.steps {
+                assert!(actions[3]
+                    .steps
+                    .move_sequence()
+                    .moves
+                    .iter()
+                    .all(|mv| matches!(mv.to_string().chars().next(), Some('R' | 'U'))));
+            }
+        }
+    }
+}
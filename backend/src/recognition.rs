@@ -0,0 +1,167 @@
+//! Recognises which named last layer case a cube is in, for example to drill a case in a trainer.
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    cfop::{
+        oll_signature, pll_signature, CfopOllSignature, CfopPllSignature, OLL_ALGORITHMS,
+        PLL_ALGORITHMS, U_CORNERS, U_EDGES,
+    },
+    cube::{CornerType, EdgeType},
+    group::{CyclicGroup, Enumerable, GroupAction, InverseSemigroup, Magma, Unital},
+    patterns::from_algorithm,
+    permute::{CentrePermutation, CornerCubelet, CubePermutation3, EdgeCubelet},
+};
+
+/// The 21 PLL cases, named as usual.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PllCase {
+    Aa,
+    Ab,
+    E,
+    F,
+    Ga,
+    Gb,
+    Gc,
+    Gd,
+    H,
+    Ja,
+    Jb,
+    Na,
+    Nb,
+    Ra,
+    Rb,
+    T,
+    Ua,
+    Ub,
+    V,
+    Y,
+    Z,
+}
+
+impl Display for PllCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} perm", self)
+    }
+}
+
+/// Every state that differs from the given case only by turning the U layer before and after it.
+/// This also covers viewing the case from each side.
+fn auf_variants(permutation: CubePermutation3) -> impl Iterator<Item = CubePermutation3> {
+    let u = CubePermutation3::from_move("U".parse().unwrap());
+    let aufs = std::iter::successors(Some(CubePermutation3::identity()), move |auf| {
+        Some(u.op(*auf))
+    })
+    .take(4)
+    .collect::<Vec<_>>();
+    aufs.clone().into_iter().flat_map(move |before| {
+        aufs.clone()
+            .into_iter()
+            .map(move |after| after.op(permutation).op(before))
+    })
+}
+
+lazy_static::lazy_static! {
+    /// Each algorithm solves the case given by its inverse.
+    /// The signature of every variant of the case is stored, so that a case is recognised from any angle.
+    static ref OLL_CASES: HashMap<CfopOllSignature, u8> = OLL_ALGORITHMS
+        .into_iter()
+        .zip(1..)
+        .flat_map(|(alg, number)| {
+            let case = from_algorithm(alg).inverse();
+            auf_variants(case).map(move |variant| (oll_signature(variant), number))
+        })
+        .collect();
+
+    static ref PLL_CASES: HashMap<CfopPllSignature, PllCase> = PLL_ALGORITHMS
+        .into_iter()
+        .flat_map(|(pll, alg)| {
+            let case = from_algorithm(alg).inverse();
+            auf_variants(case).map(move |variant| (pll_signature(variant), pll))
+        })
+        .collect();
+}
+
+/// Whether every piece outside the U layer is solved, including the centres.
+fn is_first_two_layers_solved(permutation: &CubePermutation3) -> bool {
+    *permutation.centres() == CentrePermutation::identity()
+        && EdgeType::enumerate()
+            .into_iter()
+            .filter(|edge| !U_EDGES.contains(edge))
+            .all(|edge| {
+                let piece = (EdgeCubelet(edge), CyclicGroup::identity());
+                permutation.edges().act(&piece) == piece
+            })
+        && CornerType::enumerate()
+            .into_iter()
+            .filter(|corner| !U_CORNERS.contains(corner))
+            .all(|corner| {
+                let piece = (CornerCubelet(corner), CyclicGroup::identity());
+                permutation.corners().act(&piece) == piece
+            })
+}
+
+/// The number of the OLL case that the last layer is in, from 1 to 57.
+/// Returns `None` if the first two layers are not solved, or if the last layer is already oriented.
+pub fn oll_case(permutation: &CubePermutation3) -> Option<u8> {
+    if !is_first_two_layers_solved(permutation) {
+        return None;
+    }
+    OLL_CASES.get(&oll_signature(*permutation)).copied()
+}
+
+/// The PLL case that the last layer is in.
+/// Returns `None` if the first two layers are not solved, if the last layer is not oriented,
+/// or if the last layer is already solved apart from turning the U layer.
+pub fn pll_case(permutation: &CubePermutation3) -> Option<PllCase> {
+    if !is_first_two_layers_solved(permutation)
+        || oll_signature(*permutation) != oll_signature(CubePermutation3::identity())
+    {
+        return None;
+    }
+    PLL_CASES.get(&pll_signature(*permutation)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(alg: &str) -> CubePermutation3 {
+        from_algorithm(alg).inverse()
+    }
+
+    #[test]
+    fn known_cases() {
+        // The T permutation and the Sune, set up from any angle and with any turn of the U layer.
+        for (before, after) in [("", ""), ("U", "U2"), ("U'", ""), ("y", "y'"), ("U2", "U'")] {
+            let setup =
+                |alg: &str| case(&format!("{} {} {}", after, alg, before).replace("  ", " "));
+            assert_eq!(
+                pll_case(&setup("R U R' U' R' F R2 U' R' U' R U R' F'")),
+                Some(PllCase::T)
+            );
+            assert_eq!(oll_case(&setup("R U R' U R U2 R'")), Some(27));
+            assert_eq!(oll_case(&setup("R U2 R' U' R U' R'")), Some(26));
+        }
+        assert_eq!(PllCase::T.to_string(), "T perm");
+
+        // Every case is recognised as the case of its own algorithm, so no two cases are confused.
+        for (number, alg) in (1..).zip(OLL_ALGORITHMS) {
+            assert_eq!(oll_case(&case(alg)), Some(number), "{}", alg);
+        }
+        // Together with the oriented last layer, the cases account for all 8 * 27 orientations.
+        assert_eq!(OLL_CASES.len(), 8 * 27 - 1);
+        for (pll, alg) in PLL_ALGORITHMS {
+            assert_eq!(pll_case(&case(alg)), Some(pll), "{}", alg);
+        }
+        // Together with the solved last layer, the cases account for all 4! * 4! / 2 permutations,
+        // as each solved last layer has 4 variants.
+        assert_eq!(PLL_CASES.len(), 24 * 24 / 2 - 4);
+
+        // Solved and unsolved first two layers have no case.
+        assert_eq!(oll_case(&CubePermutation3::identity()), None);
+        assert_eq!(pll_case(&case("U")), None);
+        assert_eq!(oll_case(&case("R")), None);
+        assert_eq!(pll_case(&case("R U R' U R U2 R'")), None);
+    }
+}
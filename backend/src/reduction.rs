@@ -0,0 +1,704 @@
+use std::collections::{hash_map::Entry, HashMap, VecDeque};
+
+use crate::{
+    cube::{Axis, Colour, Cube, FaceType, Move, MoveSequence, RotationType},
+    group::*,
+    permute::{CentrePermutation, CubePermutation3},
+    roux,
+};
+
+/// The index of a sticker: `face * N * N + row * N + col`.
+type Sticker = usize;
+/// The index of a piece in [Reducer::pieces].
+type Piece = usize;
+/// The index of a move in [Reducer::moves].
+type MoveIndex = usize;
+
+/// Precomputed tables for reducing an NxN cube to a 3x3x3 cube.
+/// Building the tables takes a moment, so the same reducer should be used to solve many cubes.
+///
+/// The centres are solved first, then the edges are paired.
+/// Both steps are performed with 3-cycles of pieces, which are found by searching for commutators that affect
+/// only three pieces, and then conjugating them by setup moves until every 3-cycle has been found.
+/// The remaining pieces are then read as a 3x3x3 cube and solved with the Roux method.
+pub struct Reducer<const N: usize> {
+    /// Every turn of a single layer of the cube.
+    moves: Vec<Move>,
+    /// `inverses[i]` is the index of the inverse of `moves[i]`.
+    inverses: Vec<MoveIndex>,
+    /// `destinations[i][sticker]` is the position that the sticker is moved to by `moves[i]`.
+    destinations: Vec<Vec<Sticker>>,
+    /// The stickers of each piece.
+    /// For the pieces in `centre_orbits` and `wing_orbits`, every move preserves the order of the stickers.
+    pieces: Vec<Vec<Sticker>>,
+    /// The piece that each sticker belongs to.
+    piece_of: Vec<Piece>,
+    /// The centre pieces that can be moved into the positions of each other, excluding the fixed centres of odd cubes.
+    /// Each orbit is sorted by face.
+    centre_orbits: Vec<Vec<Piece>>,
+    /// The edge pieces that can be moved into the positions of each other, excluding the middle edges of odd cubes.
+    wing_orbits: Vec<Vec<Piece>>,
+    /// Move sequences that move the first piece into the position of the second,
+    /// the second into the position of the third, and the third into the position of the first,
+    /// leaving every other piece in place.
+    /// The smallest piece is listed first.
+    cycles: HashMap<[Piece; 3], Vec<MoveIndex>>,
+}
+
+impl<const N: usize> Reducer<N> {
+    /// Whether the cube has an odd number of layers, so that it has fixed centres and middle edges.
+    const ODD: bool = N % 2 == 1;
+
+    pub fn new() -> Self {
+        assert!(N >= 3, "only cubes of size 3 or more can be reduced");
+
+        let mut moves = Vec::new();
+        for axis in [Axis::FB, Axis::RL, Axis::UD] {
+            for start_depth in 0..N {
+                for rotation_type in [
+                    RotationType::Normal,
+                    RotationType::Double,
+                    RotationType::Inverse,
+                ] {
+                    moves.push(Move::new(axis, rotation_type, start_depth, start_depth + 1));
+                }
+            }
+        }
+        let inverses = moves
+            .iter()
+            .map(|mv| {
+                moves
+                    .iter()
+                    .position(|&other| other.key() == mv.inverse().key())
+                    .unwrap()
+            })
+            .collect();
+        let destinations = moves.iter().map(|&mv| Self::destinations(mv)).collect();
+
+        let mut reducer = Self {
+            moves,
+            inverses,
+            destinations,
+            pieces: Vec::new(),
+            piece_of: Vec::new(),
+            centre_orbits: Vec::new(),
+            wing_orbits: Vec::new(),
+            cycles: HashMap::new(),
+        };
+        reducer.find_pieces();
+        reducer.find_cycles();
+        reducer
+    }
+
+    fn sticker_index(face: FaceType, row: usize, col: usize) -> Sticker {
+        face.index() * N * N + row * N + col
+    }
+
+    fn sticker_face(sticker: Sticker) -> FaceType {
+        FaceType::from_index(sticker / (N * N))
+    }
+
+    /// Reads the colour of each sticker of the cube.
+    fn stickers(cube: &Cube<N>) -> Vec<Colour> {
+        let mut stickers = Vec::with_capacity(6 * N * N);
+        for face in FaceType::enumerate() {
+            for row in 0..N {
+                for col in 0..N {
+                    stickers.push(cube.face(face)[(row, col)]);
+                }
+            }
+        }
+        stickers
+    }
+
+    /// Works out where each sticker is moved by the given move.
+    /// Since there are only six colours, we label each sticker by the digits of its index in base six,
+    /// and perform the move once for each digit.
+    fn destinations(mv: Move) -> Vec<Sticker> {
+        let count = 6 * N * N;
+        let mut sources = vec![0; count];
+        let mut place = 1;
+        while place < count {
+            let cube = Cube::<N>::from_stickers(|face, row, col| {
+                let digit = Self::sticker_index(face, row, col) / place % 6;
+                FaceType::from_index(digit).into()
+            })
+            .perform(mv);
+            for (sticker, colour) in Self::stickers(&cube).into_iter().enumerate() {
+                sources[sticker] += FaceType::from(colour).index() * place;
+            }
+            place *= 6;
+        }
+
+        let mut destinations = vec![0; count];
+        for (sticker, source) in sources.into_iter().enumerate() {
+            destinations[source] = sticker;
+        }
+        destinations
+    }
+
+    /// The position of the piece that the sticker lies on, in coordinates running from L to R, D to U, and B to F.
+    fn position(face: FaceType, row: usize, col: usize) -> [usize; 3] {
+        let n = N - 1;
+        match face {
+            FaceType::F => [col, n - row, n],
+            FaceType::R => [n, n - row, n - col],
+            FaceType::U => [col, n, row],
+            FaceType::B => [n - col, n - row, 0],
+            FaceType::L => [0, n - row, col],
+            FaceType::D => [col, 0, n - row],
+        }
+    }
+
+    /// Groups the stickers into pieces, and the pieces into orbits.
+    fn find_pieces(&mut self) {
+        let mut positions = HashMap::new();
+        for face in FaceType::enumerate() {
+            for row in 0..N {
+                for col in 0..N {
+                    let piece = *positions
+                        .entry(Self::position(face, row, col))
+                        .or_insert_with(|| {
+                            self.pieces.push(Vec::new());
+                            self.pieces.len() - 1
+                        });
+                    self.pieces[piece].push(Self::sticker_index(face, row, col));
+                    self.piece_of.push(piece);
+                }
+            }
+        }
+
+        // Search for the orbits of each piece.
+        // The stickers of each piece are reordered to match the piece they were reached from,
+        // so that moving pieces around their orbit preserves the order of their stickers.
+        let mut visited = vec![false; self.pieces.len()];
+        for root in 0..self.pieces.len() {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut orbit = vec![root];
+            let mut i = 0;
+            while i < orbit.len() {
+                let piece = orbit[i];
+                for destinations in &self.destinations {
+                    let stickers = self.pieces[piece]
+                        .iter()
+                        .map(|&sticker| destinations[sticker])
+                        .collect::<Vec<_>>();
+                    let target = self.piece_of[stickers[0]];
+                    if !visited[target] {
+                        visited[target] = true;
+                        self.pieces[target] = stickers;
+                        orbit.push(target);
+                    }
+                }
+                i += 1;
+            }
+
+            match (self.pieces[root].len(), orbit.len()) {
+                (1, 24) => {
+                    orbit.sort_by_key(|&piece| self.pieces[piece][0]);
+                    self.centre_orbits.push(orbit);
+                }
+                (2, 24) => self.wing_orbits.push(orbit),
+                _ => {}
+            }
+        }
+    }
+
+    /// The piece that `piece` is moved to by the given move.
+    fn piece_destination(&self, mv: MoveIndex, piece: Piece) -> Piece {
+        self.piece_of[self.destinations[mv][self.pieces[piece][0]]]
+    }
+
+    /// Writes the cycle with its smallest piece first.
+    fn canonical_cycle([a, b, c]: [Piece; 3]) -> [Piece; 3] {
+        if a < b && a < c {
+            [a, b, c]
+        } else if b < c {
+            [b, c, a]
+        } else {
+            [c, a, b]
+        }
+    }
+
+    /// Finds every 3-cycle of pieces in the orbits that must be solved.
+    /// Commutators of the form `[A, X Y X']` are searched for 3-cycles, where `A` turns an inner layer,
+    /// `X` turns an outer layer, and `Y` turns any layer.
+    /// Every other 3-cycle is then found by conjugating these by more and more setup moves.
+    fn find_cycles(&mut self) {
+        let orbit_of = {
+            let mut orbit_of = vec![None; self.pieces.len()];
+            for (i, orbit) in self
+                .centre_orbits
+                .iter()
+                .chain(&self.wing_orbits)
+                .enumerate()
+            {
+                for &piece in orbit {
+                    orbit_of[piece] = Some(i);
+                }
+            }
+            orbit_of
+        };
+        if orbit_of.iter().all(Option::is_none) {
+            return;
+        }
+
+        let count = 6 * N * N;
+        let sequence_destinations = |sequence: &[MoveIndex]| {
+            let mut result = (0..count).collect::<Vec<_>>();
+            for &mv in sequence {
+                for destination in &mut result {
+                    *destination = self.destinations[mv][*destination];
+                }
+            }
+            result
+        };
+
+        let inner_moves = (0..self.moves.len())
+            .filter(|&mv| self.moves[mv].start_depth != 0 && self.moves[mv].end_depth != N)
+            .collect::<Vec<_>>();
+        let outer_moves = (0..self.moves.len())
+            .filter(|&mv| self.moves[mv].start_depth == 0 || self.moves[mv].end_depth == N)
+            .collect::<Vec<_>>();
+
+        let mut queue = VecDeque::new();
+        for &x in &outer_moves {
+            for y in 0..self.moves.len() {
+                let b = [x, y, self.inverses[x]];
+                let b_inverse = [x, self.inverses[y], self.inverses[x]];
+                for &a in &inner_moves {
+                    let sequence = [&[a][..], &b, &[self.inverses[a]], &b_inverse].concat();
+                    let destinations = sequence_destinations(&sequence);
+                    let moved = (0..self.pieces.len())
+                        .filter(|&piece| {
+                            self.pieces[piece]
+                                .iter()
+                                .any(|&sticker| destinations[sticker] != sticker)
+                        })
+                        .collect::<Vec<_>>();
+                    if moved.len() != 3 || orbit_of[moved[0]].is_none() {
+                        continue;
+                    }
+                    let a = moved[0];
+                    let b = self.piece_of[destinations[self.pieces[a][0]]];
+                    let c = self.piece_of[destinations[self.pieces[b][0]]];
+                    if c == a || self.piece_of[destinations[self.pieces[c][0]]] != a {
+                        continue;
+                    }
+                    let cycle = Self::canonical_cycle([a, b, c]);
+                    if let Entry::Vacant(entry) = self.cycles.entry(cycle) {
+                        entry.insert(sequence);
+                        queue.push_back(cycle);
+                    }
+                }
+            }
+        }
+
+        // Conjugating a cycle by a move `m` gives the sequence `m cycle m'`,
+        // which cycles the pieces that `m'` moves into the original cycle.
+        while let Some(cycle) = queue.pop_front() {
+            for mv in 0..self.moves.len() {
+                let inverse = self.inverses[mv];
+                let conjugate = Self::canonical_cycle(
+                    cycle.map(|piece| self.piece_destination(inverse, piece)),
+                );
+                if !self.cycles.contains_key(&conjugate) {
+                    let sequence = [&[mv][..], &self.cycles[&cycle], &[inverse]].concat();
+                    self.cycles.insert(conjugate, sequence);
+                    queue.push_back(conjugate);
+                }
+            }
+        }
+    }
+
+    fn perform(&self, stickers: &mut [Colour], sequence: &[MoveIndex]) {
+        for &mv in sequence {
+            let mut result = stickers.to_vec();
+            for (sticker, &colour) in stickers.iter().enumerate() {
+                result[self.destinations[mv][sticker]] = colour;
+            }
+            stickers.copy_from_slice(&result);
+        }
+    }
+
+    /// The colour that each centre and edge sticker must have once the cube has been reduced.
+    /// On odd cubes, the centres must match the fixed centre of their face,
+    /// and the edges must match the middle edge they are paired with, which may not yet be solved.
+    /// On even cubes, the default colour scheme is used.
+    fn targets(stickers: &[Colour]) -> Vec<Colour> {
+        (0..6 * N * N)
+            .map(|sticker| {
+                let face = Self::sticker_face(sticker);
+                let (row, col) = (sticker / N % N, sticker % N);
+                if !Self::ODD {
+                    face.into()
+                } else if row == 0 || row == N - 1 {
+                    stickers[Self::sticker_index(face, row, N / 2)]
+                } else if col == 0 || col == N - 1 {
+                    stickers[Self::sticker_index(face, N / 2, col)]
+                } else {
+                    stickers[Self::sticker_index(face, N / 2, N / 2)]
+                }
+            })
+            .collect()
+    }
+
+    /// Solves each piece in the orbit in turn, using 3-cycles that do not disturb the pieces already solved.
+    /// Returns false if the last two pieces are swapped, which cannot be solved by 3-cycles.
+    fn solve_orbit(
+        &self,
+        stickers: &mut [Colour],
+        targets: &[Colour],
+        orbit: &[Piece],
+        solution: &mut Vec<MoveIndex>,
+    ) -> bool {
+        let is_at = |stickers: &[Colour], piece: Piece, target: Piece| {
+            self.pieces[piece]
+                .iter()
+                .zip(&self.pieces[target])
+                .all(|(&sticker, &target)| stickers[sticker] == targets[target])
+        };
+
+        for (i, &target) in orbit.iter().enumerate() {
+            if is_at(stickers, target, target) {
+                continue;
+            }
+            let unsolved = &orbit[i + 1..];
+            let mut best: Option<&Vec<MoveIndex>> = None;
+            for &source in unsolved {
+                if !is_at(stickers, source, target) {
+                    continue;
+                }
+                for &third in unsolved {
+                    if third == source {
+                        continue;
+                    }
+                    let sequence = &self.cycles[&Self::canonical_cycle([source, target, third])];
+                    match best {
+                        Some(best) if best.len() <= sequence.len() => {}
+                        _ => best = Some(sequence),
+                    }
+                }
+            }
+            let sequence = match best {
+                Some(sequence) => sequence,
+                None => return false,
+            };
+            self.perform(stickers, sequence);
+            solution.extend_from_slice(sequence);
+        }
+        true
+    }
+
+    /// Solves each orbit of centres in turn.
+    /// Returns `None` if the centres cannot be solved, which happens only if the cube has the wrong number
+    /// of centres of some colour, since centres of the same colour are interchangeable.
+    fn solve_centre_stickers(
+        &self,
+        stickers: &mut [Colour],
+        targets: &[Colour],
+        solution: &mut Vec<MoveIndex>,
+    ) -> Option<()> {
+        for orbit in &self.centre_orbits {
+            if !self.solve_orbit(stickers, targets, orbit, solution) {
+                return None;
+            }
+        }
+        Some(())
+    }
+
+    fn to_move_sequence(&self, solution: Vec<MoveIndex>) -> MoveSequence {
+        MoveSequence {
+            moves: solution.into_iter().map(|mv| self.moves[mv]).collect(),
+        }
+    }
+
+    /// Finds a move sequence that solves every centre, without moving any other piece.
+    /// On odd cubes, the centres are solved to match the fixed centres.
+    /// On even cubes, the centres are solved in the default colour scheme.
+    /// Returns `None` if the cube cannot be reached from the solved state.
+    pub fn solve_centres(&self, cube: &Cube<N>) -> Option<MoveSequence> {
+        let mut stickers = Self::stickers(cube);
+        let targets = Self::targets(&stickers);
+        let mut solution = Vec::new();
+        self.solve_centre_stickers(&mut stickers, &targets, &mut solution)?;
+        Some(self.to_move_sequence(solution))
+    }
+
+    /// Finds a move sequence that pairs the edges.
+    /// On odd cubes, each edge piece is matched to the middle edge between the same faces.
+    /// On even cubes, each edge piece is moved into its solved position.
+    /// Centres that have already been solved are left solved.
+    /// Returns `None` if the cube cannot be reached from the solved state.
+    pub fn pair_edges(&self, cube: &Cube<N>) -> Option<MoveSequence> {
+        let mut stickers = Self::stickers(cube);
+        let targets = Self::targets(&stickers);
+        let mut solution = Vec::new();
+        for orbit in &self.wing_orbits {
+            if self.solve_orbit(&mut stickers, &targets, orbit, &mut solution) {
+                continue;
+            }
+
+            // The pieces in the orbit are an odd permutation of their solved positions.
+            // Turning an inner layer that performs an odd permutation of the orbit fixes this,
+            // after which the centres must be solved again.
+            let parity = (0..self.moves.len()).find(|&mv| {
+                let mut visited = vec![false; orbit.len()];
+                let mut even_cycles = 0;
+                for start in 0..orbit.len() {
+                    let mut length = 0;
+                    let mut i = start;
+                    while !visited[i] {
+                        visited[i] = true;
+                        let piece = self.piece_destination(mv, orbit[i]);
+                        i = orbit.iter().position(|&other| other == piece).unwrap();
+                        length += 1;
+                    }
+                    if length > 0 && length % 2 == 0 {
+                        even_cycles += 1;
+                    }
+                }
+                even_cycles % 2 == 1
+            })?;
+            self.perform(&mut stickers, &[parity]);
+            solution.push(parity);
+            self.solve_centre_stickers(&mut stickers, &targets, &mut solution)?;
+            if !self.solve_orbit(&mut stickers, &targets, orbit, &mut solution) {
+                return None;
+            }
+        }
+        Some(self.to_move_sequence(solution))
+    }
+
+    /// Reads the corners, middle edges and centres of a reduced cube as a 3x3x3 cube.
+    fn reduced_permutation(cube: &Cube<N>) -> Option<CubePermutation3> {
+        let index = |i| match i {
+            0 => 0,
+            1 => N / 2,
+            _ => N - 1,
+        };
+        Cube::<3>::from_stickers(|face, row, col| cube.face(face)[(index(row), index(col))])
+            .to_permutation()
+    }
+
+    /// Converts a move on a 3x3x3 cube into the equivalent move on a reduced NxN cube,
+    /// where the middle slice of the 3x3x3 cube corresponds to every inner layer.
+    fn widen(mv: Move) -> Move {
+        let depth = |depth| match depth {
+            0 => 0,
+            1 => 1,
+            2 => N - 1,
+            _ => N,
+        };
+        Move {
+            start_depth: depth(mv.start_depth),
+            end_depth: depth(mv.end_depth),
+            ..mv
+        }
+    }
+
+    /// Solves the cube by solving the centres, pairing the edges, and then solving the rest as a 3x3x3 cube.
+    /// Returns `None` if the cube cannot be solved.
+    pub fn solve(&self, cube: &Cube<N>) -> Option<MoveSequence> {
+        let mut cube = cube.clone();
+        let mut solution = MoveSequence { moves: Vec::new() };
+        let reduce = |cube: &mut Cube<N>, solution: &mut MoveSequence| {
+            let centres = self.solve_centres(cube)?;
+            *cube = cube.clone().perform_sequence(&centres);
+            let edges = self.pair_edges(cube)?;
+            *cube = cube.clone().perform_sequence(&edges);
+            solution
+                .moves
+                .extend(centres.moves.into_iter().chain(edges.moves));
+            Some(())
+        };
+
+        reduce(&mut cube, &mut solution)?;
+        let mut permutation = Self::reduced_permutation(&cube)?;
+        if !permutation.is_solvable() && !Self::ODD {
+            // The corners of an even cube are an odd permutation, which a 3x3x3 cube cannot have.
+            // An outer layer turn fixes this, and does not change the parity of any orbit of edge pieces.
+            let mv = Move::new(Axis::UD, RotationType::Normal, 0, 1);
+            cube = cube.perform(mv);
+            solution.moves.push(mv);
+            reduce(&mut cube, &mut solution)?;
+            permutation = Self::reduced_permutation(&cube)?;
+        }
+
+        // The centres of odd cubes may have been turned by the scramble, so rotate them back into place.
+        let (rotation, rotation_permutation) =
+            CubePermutation3::rotations().iter().find(|(_, rotation)| {
+                *rotation.op(permutation).centres() == CentrePermutation::identity()
+            })?;
+        let action = roux::solve(rotation_permutation.op(permutation))?;
+        solution.moves.extend(
+            rotation
+                .moves
+                .iter()
+                .copied()
+                .chain(action.steps.move_sequence().moves)
+                .map(Self::widen),
+        );
+        Some(solution)
+    }
+}
+
+impl<const N: usize> Default for Reducer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Finds a move sequence that solves every centre of the cube. See [Reducer::solve_centres].
+/// The tables are built for each call, so use a [Reducer] directly to solve many cubes.
+pub fn solve_centres<const N: usize>(cube: &Cube<N>) -> Option<MoveSequence> {
+    Reducer::new().solve_centres(cube)
+}
+
+/// Finds a move sequence that pairs the edges of the cube. See [Reducer::pair_edges].
+/// The tables are built for each call, so use a [Reducer] directly to solve many cubes.
+pub fn pair_edges<const N: usize>(cube: &Cube<N>) -> Option<MoveSequence> {
+    Reducer::new().pair_edges(cube)
+}
+
+/// Solves the cube by reducing it to a 3x3x3 cube. See [Reducer::solve].
+/// The tables are built for each call, so use a [Reducer] directly to solve many cubes.
+pub fn solve<const N: usize>(cube: &Cube<N>) -> Option<MoveSequence> {
+    Reducer::new().solve(cube)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::scramble::random_moves;
+
+    /// Scrambles a cube with random turns of single layers.
+    fn scramble<const N: usize>(seed: u64, length: usize) -> Cube<N> {
+        let moves = [Axis::FB, Axis::RL, Axis::UD]
+            .into_iter()
+            .flat_map(|axis| {
+                [
+                    RotationType::Normal,
+                    RotationType::Double,
+                    RotationType::Inverse,
+                ]
+                .into_iter()
+                .flat_map(move |rotation_type| {
+                    (0..N).map(move |depth| Move::new(axis, rotation_type, depth, depth + 1))
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(seed);
+        Cube::new().perform_sequence(&random_moves(&mut rng, &moves, length))
+    }
+
+    #[test]
+    fn pieces() {
+        let reducer = Reducer::<5>::new();
+        // Each move takes the stickers of a piece onto the stickers of another piece, in the same order.
+        let orbits = [&reducer.centre_orbits[..], &reducer.wing_orbits].concat();
+        for destinations in &reducer.destinations {
+            for (i, piece) in reducer.pieces.iter().enumerate() {
+                let target = reducer.piece_of[destinations[piece[0]]];
+                let preserves_order = orbits.iter().any(|orbit| orbit.contains(&i));
+                for (j, &sticker) in piece.iter().enumerate() {
+                    assert_eq!(reducer.piece_of[destinations[sticker]], target);
+                    if preserves_order {
+                        assert_eq!(reducer.pieces[target][j], destinations[sticker]);
+                    }
+                }
+            }
+        }
+        assert_eq!(reducer.pieces.len(), 98);
+        // X-centres and T-centres.
+        assert_eq!(reducer.centre_orbits.len(), 2);
+        assert_eq!(reducer.wing_orbits.len(), 1);
+        // Every 3-cycle of each orbit has been found.
+        assert_eq!(reducer.cycles.len(), 3 * 24 * 23 * 22 / 3);
+
+        // Check that a few of the cycles really do move the pieces they claim to.
+        for (&[a, b, c], sequence) in reducer.cycles.iter().take(20) {
+            let mut destinations = (0..6 * 5 * 5).collect::<Vec<_>>();
+            for &mv in sequence {
+                for destination in &mut destinations {
+                    *destination = reducer.destinations[mv][*destination];
+                }
+            }
+            for piece in 0..reducer.pieces.len() {
+                let expected = match piece {
+                    _ if piece == a => b,
+                    _ if piece == b => c,
+                    _ if piece == c => a,
+                    _ => piece,
+                };
+                for (i, &sticker) in reducer.pieces[piece].iter().enumerate() {
+                    assert_eq!(destinations[sticker], reducer.pieces[expected][i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reduction() {
+        let cube = scramble::<4>(1, 60);
+        let reducer = Reducer::new();
+        let solution = reducer.solve_centres(&cube).unwrap();
+        let cube = cube.perform_sequence(&solution);
+        for face in FaceType::enumerate() {
+            let colour = Colour::from(face);
+            for (row, col) in [(1, 1), (1, 2), (2, 1), (2, 2)] {
+                assert_eq!(cube.face(face)[(row, col)], colour);
+            }
+        }
+        let solution = reducer.pair_edges(&cube).unwrap();
+        let cube = cube.perform_sequence(&solution);
+        for face in FaceType::enumerate() {
+            assert_eq!(cube.face(face)[(0, 1)], cube.face(face)[(0, 2)]);
+            assert_eq!(cube.face(face)[(1, 0)], cube.face(face)[(2, 0)]);
+            assert_eq!(cube.face(face)[(1, 1)], Colour::from(face));
+        }
+    }
+
+    #[test]
+    fn solve_big_cubes() {
+        let reducer = Reducer::<4>::new();
+        for seed in 0..4 {
+            let cube = scramble::<4>(seed, 60);
+            let solution = reducer.solve(&cube).unwrap();
+            assert!(cube.perform_sequence(&solution).is_solved());
+        }
+
+        let reducer = Reducer::<5>::new();
+        for seed in 0..4 {
+            let cube = scramble::<5>(seed, 80);
+            let solution = reducer.solve(&cube).unwrap();
+            assert!(cube.perform_sequence(&solution).is_solved());
+        }
+
+        let cube = scramble::<6>(0, 100);
+        let solution = solve(&cube).unwrap();
+        assert!(cube.perform_sequence(&solution).is_solved());
+    }
+
+    #[test]
+    fn unreachable_cubes() {
+        let reducer = Reducer::<4>::new();
+        // Every sticker is white, so only the U centres can be solved.
+        let white = Cube::<4>::from_stickers(|_, _, _| FaceType::U.into());
+        assert_eq!(reducer.solve_centres(&white), None);
+        assert_eq!(reducer.solve(&white), None);
+
+        // Swapping the colours of two centre stickers leaves too many centres of one colour.
+        let swapped = Cube::<4>::from_stickers(|face, row, col| match (face, row, col) {
+            (FaceType::F, 1, 1) => FaceType::U.into(),
+            (face, _, _) => face.into(),
+        });
+        assert_eq!(reducer.solve_centres(&swapped), None);
+        assert_eq!(solve(&swapped), None);
+    }
+}
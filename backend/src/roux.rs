@@ -1,13 +1,20 @@
+use wasm_bindgen::prelude::*;
+
 use crate::{
     algorithmic::AlgorithmicSolver,
     cube::{
-        Axis, FaceType, Move, MoveSequence,
+        Axis, CornerType, Cube, EdgeType, FaceType, Move, MoveSequence,
         {CornerType::*, EdgeType::*},
     },
-    group::{CyclicGroup, GroupAction, Magma, Unital},
-    intuitive::{SequenceGraph, SequenceSolver},
-    permute::{CentreCubelet, CornerCubelet, CubePermutation3, EdgeCubelet},
-    solve::{move_sequence_to_intuitive_action, Action, ActionReason, ActionSteps},
+    error::Error,
+    group::{CyclicGroup, GroupAction, InverseSemigroup, Magma, Unital},
+    intuitive::{gen_set, IndexedSequenceSolver, SequenceGraph, SequenceSolver},
+    metric,
+    permute::{CentreCubelet, CentrePermutation, CornerCubelet, CubePermutation3, EdgeCubelet},
+    solve::{
+        move_sequence_to_intuitive_action, run_method, try_run_method, Action, ActionReason,
+        ActionSteps, Method, Step,
+    },
 };
 
 type RouxEdgeSignature = (EdgeCubelet, CyclicGroup<2>);
@@ -20,77 +27,24 @@ type RouxEoSignature = ([CyclicGroup<2>; 6], bool);
 type RouxLrSignature = ([EdgeCubelet; 2], CornerCubelet);
 type RouxEolrSignature = ([CyclicGroup<2>; 6], [EdgeCubelet; 2], CornerCubelet, bool);
 type RouxL4eSignature = ([EdgeCubelet; 4], CentreCubelet);
+type RouxLseSignature = (
+    [(EdgeCubelet, CyclicGroup<2>); 6],
+    CentreCubelet,
+    CornerCubelet,
+);
 
 lazy_static::lazy_static! {
-    static ref ROUX_FIRST_EDGE: SequenceSolver<RouxEdgeSignature> = {
-        let gen_set = vec!["F", "R", "U", "B", "L", "D", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
+    static ref ROUX_FIRST_EDGE: SequenceSolver<RouxEdgeSignature> =
+        edge_stage("roux1e", gen_set(&["F", "R", "U", "B", "L", "D", "M"]), DL);
 
-        // Track all possible move sequences that influence a single edge (starting with the DL edge).
-        let graph = SequenceGraph::new("roux1e", gen_set, |cube| {
-            cube.edges()
-                .act(&(EdgeCubelet(DL), CyclicGroup::identity()))
-        });
-        graph.search((EdgeCubelet(DL), CyclicGroup::identity()), |seq| {
-            seq.moves.len() as u64
-        })
-    };
+    static ref ROUX_FIRST_PAIR: SequenceSolver<RouxPairSignature> =
+        pair_stage("roux1p", gen_set(&["F", "R", "U", "B", "M"]), FL, FDL);
 
-    static ref ROUX_FIRST_PAIR: SequenceSolver<RouxPairSignature> = {
-        let gen_set = vec!["F", "R", "U", "B", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
+    static ref ROUX_SECOND_PAIR: SequenceSolver<RouxPairSignature> =
+        pair_stage("roux2p", gen_set(&["R", "U", "B", "M"]), BL, BDL);
 
-        let graph = SequenceGraph::new("roux1p", gen_set, |cube| {
-            (
-                cube.edges()
-                    .act(&(EdgeCubelet(FL), CyclicGroup::identity())),
-                cube.corners()
-                    .act(&(CornerCubelet(FDL), CyclicGroup::identity()))
-            )
-        });
-        graph.search(((EdgeCubelet(FL), CyclicGroup::identity()), (CornerCubelet(FDL), CyclicGroup::identity())), |seq| {
-            seq.moves.len() as u64
-        })
-    };
-
-    static ref ROUX_SECOND_PAIR: SequenceSolver<RouxPairSignature> = {
-        let gen_set = vec!["R", "U", "B", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
-
-        let graph = SequenceGraph::new("roux2p", gen_set, |cube| {
-            (
-                cube.edges()
-                    .act(&(EdgeCubelet(BL), CyclicGroup::identity())),
-                cube.corners()
-                    .act(&(CornerCubelet(BDL), CyclicGroup::identity()))
-            )
-        });
-        graph.search(((EdgeCubelet(BL), CyclicGroup::identity()), (CornerCubelet(BDL), CyclicGroup::identity())), |seq| {
-            seq.moves.len() as u64
-        })
-    };
-
-    static ref ROUX_SECOND_EDGE: SequenceSolver<RouxEdgeSignature> = {
-        let gen_set = vec!["R", "U", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
-
-        // Track all possible move sequences that influence a single edge (starting with the DL edge).
-        let graph = SequenceGraph::new("roux2e", gen_set, |cube| {
-            cube.edges()
-                .act(&(EdgeCubelet(DR), CyclicGroup::identity()))
-        });
-        graph.search((EdgeCubelet(DR), CyclicGroup::identity()), |seq| {
-            seq.moves.len() as u64
-        })
-    };
+    static ref ROUX_SECOND_EDGE: SequenceSolver<RouxEdgeSignature> =
+        edge_stage("roux2e", gen_set(&["R", "U", "M"]), DR);
 
     static ref ROUX_THIRD_PAIR: SequenceSolver<RouxPairSignature> = {
         let gen_set = vec!["U", "M", "R U R'", "R U2 R'", "R U' R'", "R' U R", "R' U2 R", "R' U' R"]
@@ -98,17 +52,7 @@ lazy_static::lazy_static! {
             .map(|x| x.parse::<MoveSequence>().unwrap())
             .collect::<Vec<_>>();
 
-        let graph = SequenceGraph::new("roux3p", gen_set, |cube| {
-            (
-                cube.edges()
-                    .act(&(EdgeCubelet(FR), CyclicGroup::identity())),
-                cube.corners()
-                    .act(&(CornerCubelet(FDR), CyclicGroup::identity()))
-            )
-        });
-        graph.search(((EdgeCubelet(FR), CyclicGroup::identity()), (CornerCubelet(FDR), CyclicGroup::identity())), |seq| {
-            seq.moves.len() as u64
-        })
+        pair_stage("roux3p", gen_set, FR, FDR)
     };
 
     static ref ROUX_FOURTH_PAIR: SequenceSolver<RouxPairSignature> = {
@@ -117,17 +61,7 @@ lazy_static::lazy_static! {
             .map(|x| x.parse::<MoveSequence>().unwrap())
             .collect::<Vec<_>>();
 
-        let graph = SequenceGraph::new("roux4p", gen_set, |cube| {
-            (
-                cube.edges()
-                    .act(&(EdgeCubelet(BR), CyclicGroup::identity())),
-                cube.corners()
-                    .act(&(CornerCubelet(BDR), CyclicGroup::identity()))
-            )
-        });
-        graph.search(((EdgeCubelet(BR), CyclicGroup::identity()), (CornerCubelet(BDR), CyclicGroup::identity())), |seq| {
-            seq.moves.len() as u64
-        })
+        pair_stage("roux4p", gen_set, BR, BDR)
     };
 
     static ref CMLL: AlgorithmicSolver<RouxCmllSignature> = {
@@ -169,6 +103,13 @@ lazy_static::lazy_static! {
             "R U R' U' R' F R F' R U R' U R U2' R'",
             "R U R' U R' F R F' R U2' R'",
             "R U' L' U R' U' L",
+            // L
+            "F R U' R' U' R U R' F'",
+            "F R' F' R U R U' R'",
+            "R U2 R D R' U2 R D' R2'",
+            "R' U2 R' D' R U2 R' D R2",
+            "R U2 R' U' R U R' U' R U R' U' R U' R'",
+            "R U2 R2' F R F' R U2 R'",
             // Antisune
             "R' U' R U' R' U2' R",
             "R2 D R' U R D' R' U R' U' R U' R'",
@@ -179,6 +120,8 @@ lazy_static::lazy_static! {
             ]
             .into_iter()
             .map(|x| x.parse::<MoveSequence>().unwrap())
+            // CMLL skip: the corners might already be solved, up to AUF.
+            .chain(std::iter::once(MoveSequence { moves: Vec::new() }))
             .collect::<Vec<_>>();
 
         let pre_moves = vec!["U".parse::<MoveSequence>().unwrap()];
@@ -195,44 +138,18 @@ lazy_static::lazy_static! {
                 cube.corners()
                     .act(&(CornerCubelet(BUL), CyclicGroup::identity())),
             ]
-        }, |seq| {
-            seq.moves.len() as u64
-        })
+        }, metric::etm)
     };
 
-    static ref EO: SequenceSolver<RouxEoSignature> = {
-        let gen_set = vec!["U", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
+    static ref EO: IndexedSequenceSolver<RouxEoSignature> = {
+        let gen_set = gen_set(&["U", "M"]);
 
-        let graph = SequenceGraph::new("roux_eo", gen_set, |cube| {
-            let axis_swapped = matches!(cube.centres().act(&CentreCubelet(FaceType::U)).0, FaceType::F | FaceType::B);
-
-            ([
-                // Unact is used to get edge orientation: we don't care which edge is in this position,
-                // just how it is oriented relative to where the edge should belong.
-                cube.edges()
-                    .unact(&(EdgeCubelet(UF), CyclicGroup::identity())).1,
-                cube.edges()
-                    .unact(&(EdgeCubelet(UB), CyclicGroup::identity())).1,
-                cube.edges()
-                    .unact(&(EdgeCubelet(DB), CyclicGroup::identity())).1,
-                cube.edges()
-                    .unact(&(EdgeCubelet(DF), CyclicGroup::identity())).1,
-                cube.edges()
-                    .unact(&(EdgeCubelet(UL), CyclicGroup::identity())).1,
-                cube.edges()
-                    .unact(&(EdgeCubelet(UR), CyclicGroup::identity())).1,
-            ], axis_swapped)
-        });
-        graph.search(([CyclicGroup::identity(); 6], false), |seq| {
-            seq.moves.len() as u64
-        })
+        let graph = SequenceGraph::new("roux_eo", gen_set, eo_signature);
+        graph.search(([CyclicGroup::identity(); 6], false), metric::stm).into_indexed()
     };
 
     /// The cube is assumed to have U/D faces pointing on U/D (or swapped).
-    static ref LR: SequenceSolver<RouxLrSignature> = {
+    static ref LR: IndexedSequenceSolver<RouxLrSignature> = {
         let gen_set = vec![
             "U", "M2",
             "M U2 M", "M U2 M'", "M' U2 M'", "M' U2 M",
@@ -253,9 +170,7 @@ lazy_static::lazy_static! {
                     .act(&(CornerCubelet(FUL), CyclicGroup::identity())).0,
             )
         });
-        graph.search(([EdgeCubelet(UL), EdgeCubelet(UR)], CornerCubelet(FUL)), |seq| {
-            seq.moves.len() as u64
-        })
+        graph.search(([EdgeCubelet(UL), EdgeCubelet(UR)], CornerCubelet(FUL)), metric::stm).into_indexed()
     };
 
     /// The signature is
@@ -263,11 +178,8 @@ lazy_static::lazy_static! {
     /// - the position of edges UL UR,
     /// - the FUL corner position (used for AUF),
     /// - and whether the front face is the F/B colour (true) or not (false).
-    static ref EOLR: SequenceSolver<RouxEolrSignature> = {
-        let gen_set = vec!["U", "M"]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
+    static ref EOLR: IndexedSequenceSolver<RouxEolrSignature> = {
+        let gen_set = gen_set(&["U", "M"]);
 
         let graph = SequenceGraph::new("roux_eolr", gen_set, |cube| {
             (
@@ -303,71 +215,87 @@ lazy_static::lazy_static! {
             [EdgeCubelet(UL), EdgeCubelet(UR)],
             CornerCubelet(FUL),
             true,
-        ), |seq| {
-            seq.moves.len() as u64
-        })
+        ), metric::stm).into_indexed()
+    };
+
+    /// Solves the last six edges in one step, instead of orienting them, solving UL and UR, then the last four edges.
+    /// The signature is where each of the edges UF UB DB DF UL UR are and how they are oriented,
+    /// where the F centre is, and the FUL corner position (used for AUF).
+    /// As with the last four edges, states with an odd permutation of edges are never generated,
+    /// since they cannot be reached with `<M, U>`.
+    static ref LSE: SequenceSolver<RouxLseSignature> = {
+        let graph = SequenceGraph::new("roux_lse", gen_set(&["U", "M"]), lse_signature);
+        graph.search(lse_signature(CubePermutation3::identity()), metric::stm)
     };
 
     /// The signature is the last four edges' positions (UF UB DB DF), and the front-facing centre.
-    static ref L4E: SequenceSolver<RouxL4eSignature> = {
-        // There must be an even number of U2 mnves,
-        // otherwise LR is messed up.
-        // However, the L4E are actually unsolvable wih an odd amount of U2 moves, since
-        // a 2-cycle of edges is impossible excluding U2 moves!
-        // So somewhat surprisingly, this signature is sufficient.
-        let gen_set = vec![
-            "M", "U2", "E2 M' E2", "E2 M E2"
-        ]
-            .into_iter()
-            .map(|x| x.parse::<MoveSequence>().unwrap())
-            .collect::<Vec<_>>();
+    static ref L4E: IndexedSequenceSolver<RouxL4eSignature> = l4e_graph()
+        .search(l4e_signature(CubePermutation3::identity()), metric::stm)
+        .into_indexed();
+}
 
-        let graph = SequenceGraph::new("roux_l4e", gen_set, |cube| {
-            (
-                [
-                    cube.edges().act(&(EdgeCubelet(UF), CyclicGroup::identity())).0,
-                    cube.edges().act(&(EdgeCubelet(UB), CyclicGroup::identity())).0,
-                    cube.edges().act(&(EdgeCubelet(DB), CyclicGroup::identity())).0,
-                    cube.edges().act(&(EdgeCubelet(DF), CyclicGroup::identity())).0,
-                ],
-                cube.centres().act(&CentreCubelet(FaceType::F)),
-            )
-        });
-        graph.search((
-            [
-                EdgeCubelet(UF),
-                EdgeCubelet(UB),
-                EdgeCubelet(DB),
-                EdgeCubelet(DF),
-            ],
-            CentreCubelet(FaceType::F),
-        ), |seq| {
-            seq.moves.len() as u64
-        })
-    };
+fn edge_signature(permutation: CubePermutation3, edge: EdgeType) -> RouxEdgeSignature {
+    permutation
+        .edges()
+        .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
 }
 
-pub fn first_edge(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_FIRST_EDGE.solve(
-        &permutation
+fn pair_signature(
+    permutation: CubePermutation3,
+    edge: EdgeType,
+    corner: CornerType,
+) -> RouxPairSignature {
+    (
+        permutation
             .edges()
-            .act(&(EdgeCubelet(DL), CyclicGroup::identity())),
+            .act(&(EdgeCubelet(edge), CyclicGroup::identity())),
+        permutation
+            .corners()
+            .act(&(CornerCubelet(corner), CyclicGroup::identity())),
     )
 }
 
+/// Builds a solver that moves the given edge into its home position, oriented, using moves from `gen_set`.
+/// The solver for the first edge of the Roux method is `edge_stage("roux1e", gen_set(&["F", "R", "U", "B", "L", "D", "M"]), DL)`.
+pub fn edge_stage(
+    graph_name: &'static str,
+    gen_set: Vec<MoveSequence>,
+    edge: EdgeType,
+) -> SequenceSolver<RouxEdgeSignature> {
+    let graph = SequenceGraph::new(graph_name, gen_set, |cube| edge_signature(cube, edge));
+    graph.search(
+        edge_signature(CubePermutation3::identity(), edge),
+        metric::stm,
+    )
+}
+
+/// Builds a solver that moves the given edge and corner into their home positions, oriented, using moves from `gen_set`.
+/// The moves should not disturb any pieces that were solved before this pair.
+pub fn pair_stage(
+    graph_name: &'static str,
+    gen_set: Vec<MoveSequence>,
+    edge: EdgeType,
+    corner: CornerType,
+) -> SequenceSolver<RouxPairSignature> {
+    let graph = SequenceGraph::new(graph_name, gen_set, |cube| {
+        pair_signature(cube, edge, corner)
+    });
+    graph.search(
+        pair_signature(CubePermutation3::identity(), edge, corner),
+        metric::stm,
+    )
+}
+
+pub fn first_edge(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    ROUX_FIRST_EDGE.solve(&edge_signature(permutation, DL))
+}
+
 pub fn first_edge_action(permutation: CubePermutation3) -> Option<Action> {
     first_edge(permutation).map(|seq| move_sequence_to_intuitive_action("First edge", seq.clone()))
 }
 
 pub fn first_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_FIRST_PAIR.solve(&(
-        permutation
-            .edges()
-            .act(&(EdgeCubelet(FL), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(FDL), CyclicGroup::identity())),
-    ))
+    ROUX_FIRST_PAIR.solve(&pair_signature(permutation, FL, FDL))
 }
 
 pub fn first_pair_action(permutation: CubePermutation3) -> Option<Action> {
@@ -375,14 +303,7 @@ pub fn first_pair_action(permutation: CubePermutation3) -> Option<Action> {
 }
 
 pub fn second_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_SECOND_PAIR.solve(&(
-        permutation
-            .edges()
-            .act(&(EdgeCubelet(BL), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(BDL), CyclicGroup::identity())),
-    ))
+    ROUX_SECOND_PAIR.solve(&pair_signature(permutation, BL, BDL))
 }
 
 pub fn second_pair_action(permutation: CubePermutation3) -> Option<Action> {
@@ -391,11 +312,7 @@ pub fn second_pair_action(permutation: CubePermutation3) -> Option<Action> {
 }
 
 pub fn second_edge(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_SECOND_EDGE.solve(
-        &permutation
-            .edges()
-            .act(&(EdgeCubelet(DR), CyclicGroup::identity())),
-    )
+    ROUX_SECOND_EDGE.solve(&edge_signature(permutation, DR))
 }
 
 pub fn second_edge_action(permutation: CubePermutation3) -> Option<Action> {
@@ -404,14 +321,7 @@ pub fn second_edge_action(permutation: CubePermutation3) -> Option<Action> {
 }
 
 pub fn third_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_THIRD_PAIR.solve(&(
-        permutation
-            .edges()
-            .act(&(EdgeCubelet(FR), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(FDR), CyclicGroup::identity())),
-    ))
+    ROUX_THIRD_PAIR.solve(&pair_signature(permutation, FR, FDR))
 }
 
 pub fn third_pair_action(permutation: CubePermutation3) -> Option<Action> {
@@ -419,14 +329,7 @@ pub fn third_pair_action(permutation: CubePermutation3) -> Option<Action> {
 }
 
 pub fn fourth_pair(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    ROUX_FOURTH_PAIR.solve(&(
-        permutation
-            .edges()
-            .act(&(EdgeCubelet(BR), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(BDR), CyclicGroup::identity())),
-    ))
+    ROUX_FOURTH_PAIR.solve(&pair_signature(permutation, BR, BDR))
 }
 
 pub fn fourth_pair_action(permutation: CubePermutation3) -> Option<Action> {
@@ -434,21 +337,16 @@ pub fn fourth_pair_action(permutation: CubePermutation3) -> Option<Action> {
         .map(|seq| move_sequence_to_intuitive_action("Fourth pair", seq.clone()))
 }
 
-pub fn cmll(permutation: CubePermutation3) -> Option<MoveSequence> {
-    let cmll = CMLL.solve(&[
-        permutation
-            .corners()
-            .act(&(CornerCubelet(FUL), CyclicGroup::identity())),
+fn cmll_signature(permutation: CubePermutation3) -> RouxCmllSignature {
+    [FUL, FUR, BUR, BUL].map(|corner| {
         permutation
             .corners()
-            .act(&(CornerCubelet(FUR), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(BUR), CyclicGroup::identity())),
-        permutation
-            .corners()
-            .act(&(CornerCubelet(BUL), CyclicGroup::identity())),
-    ]);
+            .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+    })
+}
+
+pub fn cmll(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let cmll = CMLL.solve(&cmll_signature(permutation));
     cmll.map(|cmll| {
         let mut cmll = cmll.clone();
         // Remove any trailing AUF move.
@@ -463,43 +361,22 @@ pub fn cmll_action(permutation: CubePermutation3) -> Option<Action> {
     cmll(permutation).map(|seq| move_sequence_to_intuitive_action("CMLL", seq))
 }
 
-pub fn eo(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+fn eo_signature(permutation: CubePermutation3) -> RouxEoSignature {
     let axis_swapped = matches!(
         permutation.centres().act(&CentreCubelet(FaceType::U)).0,
         FaceType::F | FaceType::B
     );
 
-    EO.solve(&(
-        [
-            // Unact is used to get edge orientation: we don't care which edge is in this position,
-            // just how it is oriented relative to where the edge should belong.
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(UF), CyclicGroup::identity()))
-                .1,
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(UB), CyclicGroup::identity()))
-                .1,
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(DB), CyclicGroup::identity()))
-                .1,
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(DF), CyclicGroup::identity()))
-                .1,
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(UL), CyclicGroup::identity()))
-                .1,
-            permutation
-                .edges()
-                .unact(&(EdgeCubelet(UR), CyclicGroup::identity()))
-                .1,
-        ],
+    (
+        // We don't care which edge is in each position,
+        // just how it is oriented relative to where the edge should belong.
+        [UF, UB, DB, DF, UL, UR].map(|edge| permutation.edges().orientation_at(&EdgeCubelet(edge))),
         axis_swapped,
-    ))
+    )
+}
+
+pub fn eo(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    EO.solve(&eo_signature(permutation))
 }
 
 pub fn eo_action(permutation: CubePermutation3) -> Option<Action> {
@@ -507,23 +384,23 @@ pub fn eo_action(permutation: CubePermutation3) -> Option<Action> {
         .map(|seq| move_sequence_to_intuitive_action("Orientation of last six edges", seq.clone()))
 }
 
-pub fn lr(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    LR.solve(&(
-        [
-            permutation
-                .edges()
-                .act(&(EdgeCubelet(UL), CyclicGroup::identity()))
-                .0,
+fn lr_signature(permutation: CubePermutation3) -> RouxLrSignature {
+    (
+        [UL, UR].map(|edge| {
             permutation
                 .edges()
-                .act(&(EdgeCubelet(UR), CyclicGroup::identity()))
-                .0,
-        ],
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .0
+        }),
         permutation
             .corners()
             .act(&(CornerCubelet(FUL), CyclicGroup::identity()))
             .0,
-    ))
+    )
+}
+
+pub fn lr(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    LR.solve(&lr_signature(permutation))
 }
 
 pub fn lr_action(permutation: CubePermutation3) -> Option<Action> {
@@ -583,79 +460,342 @@ pub fn eolr_action(permutation: CubePermutation3) -> Option<Action> {
     eolr(permutation).map(|seq| move_sequence_to_intuitive_action("EOLR", seq.clone()))
 }
 
-pub fn l4e(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
-    L4E.solve(&(
-        [
-            permutation
-                .edges()
-                .act(&(EdgeCubelet(UF), CyclicGroup::identity()))
-                .0,
-            permutation
-                .edges()
-                .act(&(EdgeCubelet(UB), CyclicGroup::identity()))
-                .0,
-            permutation
-                .edges()
-                .act(&(EdgeCubelet(DB), CyclicGroup::identity()))
-                .0,
+/// The sequence graph that the last four edges are solved with.
+/// Some of its transitions are made of several moves, so it should be searched with a metric such as
+/// [metric::stm] that counts each of those moves, rather than counting each transition as one move.
+fn l4e_graph() -> SequenceGraph<RouxL4eSignature> {
+    // There must be an even number of U2 mnves,
+    // otherwise LR is messed up.
+    // However, the L4E are actually unsolvable wih an odd amount of U2 moves, since
+    // a 2-cycle of edges is impossible excluding U2 moves!
+    // So somewhat surprisingly, this signature is sufficient.
+    let gen_set = ["M", "U2", "E2 M' E2", "E2 M E2"]
+        .into_iter()
+        .map(|x| x.parse::<MoveSequence>().unwrap())
+        .collect::<Vec<_>>();
+
+    SequenceGraph::new("roux_l4e", gen_set, l4e_signature)
+}
+
+fn l4e_signature(permutation: CubePermutation3) -> RouxL4eSignature {
+    (
+        [UF, UB, DB, DF].map(|edge| {
             permutation
                 .edges()
-                .act(&(EdgeCubelet(DF), CyclicGroup::identity()))
-                .0,
-        ],
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .0
+        }),
         permutation.centres().act(&CentreCubelet(FaceType::F)),
-    ))
+    )
+}
+
+pub fn l4e(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    L4E.solve(&l4e_signature(permutation))
 }
 
 pub fn l4e_action(permutation: CubePermutation3) -> Option<Action> {
     l4e(permutation).map(|seq| move_sequence_to_intuitive_action("Last four edges", seq.clone()))
 }
 
-pub fn solve(mut permutation: CubePermutation3) -> Option<Action> {
-    let mut steps = Vec::new();
+fn lse_signature(permutation: CubePermutation3) -> RouxLseSignature {
+    (
+        [UF, UB, DB, DF, UL, UR].map(|edge| {
+            permutation
+                .edges()
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+        }),
+        permutation.centres().act(&CentreCubelet(FaceType::F)),
+        permutation
+            .corners()
+            .act(&(CornerCubelet(FUL), CyclicGroup::identity()))
+            .0,
+    )
+}
 
-    // Can't use impl FnOnce or anything, so just use fn.
-    let mut add_step = |func: fn(CubePermutation3) -> Option<Action>| -> Option<()> {
-        let step = func(permutation)?;
-        permutation =
-            CubePermutation3::from_move_sequence(step.steps.move_sequence()).op(permutation);
-        steps.push(step);
-        Some(())
-    };
+/// Solves the last six edges and the M slice centres at once, as an alternative to [eo], [lr], and [l4e].
+/// The first two blocks and the corners of the last layer must already be solved.
+pub fn lse(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    LSE.solve(&lse_signature(permutation))
+}
+
+pub fn lse_action(permutation: CubePermutation3) -> Option<Action> {
+    lse(permutation).map(|seq| move_sequence_to_intuitive_action("Last six edges", seq.clone()))
+}
 
-    add_step(first_edge_action);
-    add_step(first_pair_action);
-    add_step(second_pair_action);
-    add_step(second_edge_action);
-    add_step(third_pair_action);
-    add_step(fourth_pair_action);
-    add_step(cmll_action);
-    add_step(eo_action);
-    add_step(lr_action);
-    add_step(l4e_action);
+/// The steps of the Roux method, in the order they are performed.
+const STEPS: [Step; 10] = [
+    (
+        "First edge",
+        first_edge_action,
+        Some(|p| format!("{:?}", edge_signature(p, DL))),
+    ),
+    (
+        "First pair",
+        first_pair_action,
+        Some(|p| format!("{:?}", pair_signature(p, FL, FDL))),
+    ),
+    (
+        "Second pair",
+        second_pair_action,
+        Some(|p| format!("{:?}", pair_signature(p, BL, BDL))),
+    ),
+    (
+        "Second edge",
+        second_edge_action,
+        Some(|p| format!("{:?}", edge_signature(p, DR))),
+    ),
+    (
+        "Third pair",
+        third_pair_action,
+        Some(|p| format!("{:?}", pair_signature(p, FR, FDR))),
+    ),
+    (
+        "Fourth pair",
+        fourth_pair_action,
+        Some(|p| format!("{:?}", pair_signature(p, BR, BDR))),
+    ),
+    (
+        "CMLL",
+        cmll_action,
+        Some(|p| format!("{:?}", cmll_signature(p))),
+    ),
+    (
+        "Orientation of last six edges",
+        eo_action,
+        Some(|p| format!("{:?}", eo_signature(p))),
+    ),
+    (
+        "UL and UR edges",
+        lr_action,
+        Some(|p| format!("{:?}", lr_signature(p))),
+    ),
+    (
+        "Last four edges",
+        l4e_action,
+        Some(|p| format!("{:?}", l4e_signature(p))),
+    ),
+];
 
+/// The Roux method: blocks on the left and right, then the corners of the last layer, then the last six edges.
+pub struct Roux;
+
+impl Method for Roux {
+    fn name(&self) -> &'static str {
+        "Roux method"
+    }
+
+    fn steps(&self) -> &'static [Step] {
+        &STEPS
+    }
+}
+
+pub fn solve(permutation: CubePermutation3) -> Option<Action> {
+    run_method(&Roux, permutation)
+}
+
+/// Solves the cube using the Roux method, then checks that the solution really does solve the cube.
+pub fn solve_verified(permutation: CubePermutation3) -> Result<Action, Error> {
+    let action = try_run_method(&Roux, permutation)?;
+    if CubePermutation3::from_move_sequence(action.steps.move_sequence())
+        .map(|solution| solution.op(permutation))
+        == Some(CubePermutation3::identity())
+    {
+        Ok(action)
+    } else if permutation.is_solvable() {
+        Err(Error::Unsolvable)
+    } else {
+        Err(Error::InvalidState)
+    }
+}
+
+/// Solves the cube using the Roux method, building the first block on whichever side gives the shortest solution.
+/// The cube may be held in any orientation.
+///
+/// The solution begins by rotating the cube so that the first block is on the left,
+/// and ends by rotating it back to the way it was held.
+pub fn solve_colour_neutral(permutation: CubePermutation3) -> Option<Action> {
+    let rotations = CubePermutation3::rotations();
+
+    // Find the rotation that brings the centres back to the standard orientation.
+    let (standard_rotation, permutation) = rotations.iter().find_map(|&(_, rotation)| {
+        let permutation = rotation.op(permutation);
+        if *permutation.centres() == CentrePermutation::identity() {
+            Some((rotation, permutation))
+        } else {
+            None
+        }
+    })?;
+
+    // Try each orientation, by solving the cube as it looks from that orientation.
+    let ((rotation_seq, rotation), solution) = rotations
+        .iter()
+        .filter_map(|(seq, rotation)| {
+            let rotated = rotation.op(permutation).op(rotation.inverse());
+            solve(rotated).map(|solution| ((seq, rotation), solution))
+        })
+        .min_by_key(|(_, solution)| metric::stm(&solution.steps.move_sequence()))?;
+
+    // Combine the two rotations at the start into a single rotation.
+    let start = rotation.op(standard_rotation);
+    let inspection = rotations
+        .iter()
+        .find(|(_, rotation)| *rotation == start)
+        .expect("rotations form a group")
+        .0
+        .clone();
+    let end = rotation_seq.inverse();
+
+    let mut steps = Vec::new();
+    if !inspection.moves.is_empty() {
+        steps.push(move_sequence_to_intuitive_action("Inspection", inspection));
+    }
+    if let ActionSteps::Sequence { actions } = solution.steps {
+        steps.extend(actions);
+    }
+    if !end.moves.is_empty() {
+        steps.push(move_sequence_to_intuitive_action("Final rotation", end));
+    }
     Some(Action {
         reason: ActionReason::Solve,
-        description: Some("Roux method".to_string()),
+        description: Some("Roux method, colour neutral".to_string()),
         steps: ActionSteps::Sequence { actions: steps },
     })
 }
 
+/// Reflects every move of the action with [Move::mirror_lr].
+fn mirror_action(action: Action) -> Action {
+    Action {
+        steps: mirror_steps(action.steps),
+        ..action
+    }
+}
+
+fn mirror_steps(steps: ActionSteps) -> ActionSteps {
+    // Every move of a Roux solution turns layers of a 3x3x3 cube.
+    let mirror = |mv: Move| mv.mirror_lr(3).expect("move turns layers of a 3x3x3 cube");
+    match steps {
+        ActionSteps::Move { mv } => ActionSteps::Move { mv: mirror(mv) },
+        ActionSteps::CancelledMove { mv } => ActionSteps::CancelledMove { mv: mirror(mv) },
+        ActionSteps::Sequence { actions } => ActionSteps::Sequence {
+            actions: actions.into_iter().map(mirror_action).collect(),
+        },
+        ActionSteps::Conjugate { setup, body } => ActionSteps::Conjugate {
+            setup: Box::new(mirror_steps(*setup)),
+            body: Box::new(mirror_steps(*body)),
+        },
+        ActionSteps::Commutator { a, b } => ActionSteps::Commutator {
+            a: Box::new(mirror_steps(*a)),
+            b: Box::new(mirror_steps(*b)),
+        },
+        ActionSteps::SwitchToInverse => ActionSteps::SwitchToInverse,
+    }
+}
+
+/// Solves the cube using the Roux method, building the first block on the right instead of the left.
+/// This is how left-handed solvers usually perform the method.
+///
+/// The cube is reflected, solved as usual, and the solution is reflected back.
+pub fn solve_mirrored(permutation: CubePermutation3) -> Option<Action> {
+    solve(permutation.mirror_lr()).map(mirror_action)
+}
+
+/// Parses the given scramble, and solves the resulting cube with [solve_verified].
+pub fn solve_scramble(scramble: &str) -> Result<Action, Error> {
+    let scramble_sequence = scramble.parse::<MoveSequence>()?;
+    let mut permutation = CubePermutation3::identity();
+    for mv in scramble_sequence.moves {
+        let mv_permutation =
+            CubePermutation3::try_from_move(mv).ok_or(Error::DepthOutOfRange { mv, size: 3 })?;
+        permutation = mv_permutation.op(permutation);
+    }
+    solve_verified(permutation)
+}
+
+/// Reads the given facelet string, and solves the resulting cube with [solve_verified].
+/// See [Cube::from_facelets] for the format.
+/// If the stickers do not form a cube that can be solved, [Error::InvalidState] is returned.
+pub fn solve_facelets(facelets: &str) -> Result<Action, Error> {
+    let permutation = Cube::from_facelets(facelets)?
+        .to_permutation()
+        .filter(CubePermutation3::is_solvable)
+        .ok_or(Error::InvalidState)?;
+    solve_verified(permutation)
+}
+
+/// Parses the given scramble and solves it with the Roux method, for use from JavaScript.
+/// Throws an error if the scramble could not be parsed or solved.
+///
+/// The solution is returned as a tree of actions. Each action is an object of the form
+/// ```ts
+/// interface Action {
+///     // Why this action was performed.
+///     reason: "solve" | "shuffle" | "step" | "intuitive";
+///     // The name of the step of the solve, such as "First pair", if `reason` is "step".
+///     stepName?: string;
+///     // More information about this action, if there is any.
+///     description: string | null;
+///     // Every move performed by this action, in Singmaster notation, separated by spaces.
+///     moves: string;
+///     // The smaller actions that make up this action, unless it is a single move.
+///     actions?: Action[];
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn solve_roux(scramble: &str) -> Result<JsValue, JsValue> {
+    solve_scramble(scramble)
+        .map(|action| action.to_js())
+        .map_err(|error| js_sys::Error::new(&error.to_string()).into())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        group::{CyclicGroup, GroupAction, Magma},
-        permute::{CubePermutation3, EdgeCubelet},
+        cube::CornerType,
+        group::{CyclicGroup, Enumerable, GroupAction, Magma},
+        permute::{
+            CentrePermutation, CornerPermutation, CubePermutation3, EdgeCubelet, EdgePermutation,
+        },
     };
 
     use super::*;
 
+    #[cfg(feature = "tables")]
+    #[test]
+    fn tables() {
+        let bytes = ROUX_FIRST_EDGE.to_bytes();
+        let first_edge = SequenceSolver::<RouxEdgeSignature>::from_bytes(&bytes).unwrap();
+        for edge in EdgeCubelet::enumerate() {
+            for orientation in 0..2 {
+                let signature = (edge, CyclicGroup::new(orientation));
+                assert_eq!(
+                    first_edge.solve(&signature),
+                    ROUX_FIRST_EDGE.solve(&signature)
+                );
+            }
+        }
+
+        let bytes = ROUX_FIRST_PAIR.to_bytes();
+        let first_pair = SequenceSolver::<RouxPairSignature>::from_bytes(&bytes).unwrap();
+        for edge in EdgeCubelet::enumerate() {
+            for corner in CornerCubelet::enumerate() {
+                let signature = ((edge, CyclicGroup::new(1)), (corner, CyclicGroup::new(2)));
+                assert_eq!(
+                    first_pair.solve(&signature),
+                    ROUX_FIRST_PAIR.solve(&signature)
+                );
+            }
+        }
+
+        assert!(SequenceSolver::<RouxPairSignature>::from_bytes(&bytes[..3]).is_err());
+    }
+
     #[test]
     fn test_edge_insert() {
         // Solve the DF edge piece (oriented badly) into the DL slot.
         let solution = ROUX_FIRST_EDGE.solve(&(EdgeCubelet(DF), CyclicGroup::new(1)));
         assert_eq!(
             CubePermutation3::from_move_sequence(solution.unwrap().clone())
+                .unwrap()
                 .edges()
                 .act(&(EdgeCubelet(DF), CyclicGroup::new(1))),
             (EdgeCubelet(DL), CyclicGroup::new(0))
@@ -665,6 +805,7 @@ mod tests {
         let solution = ROUX_FIRST_EDGE.solve(&(EdgeCubelet(UB), CyclicGroup::new(0)));
         assert_eq!(
             CubePermutation3::from_move_sequence(solution.unwrap().clone())
+                .unwrap()
                 .edges()
                 .act(&(EdgeCubelet(UB), CyclicGroup::new(0))),
             (EdgeCubelet(DL), CyclicGroup::new(0))
@@ -679,12 +820,507 @@ mod tests {
                 .parse()
                 .unwrap();
 
-        let permutation = CubePermutation3::from_move_sequence(scramble);
+        let permutation = CubePermutation3::from_move_sequence(scramble).unwrap();
         let solution = solve(permutation).unwrap();
         println!("Solution: {:#?}", solution);
         let final_permutation =
-            CubePermutation3::from_move_sequence(solution.steps.move_sequence()).op(permutation);
+            CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                .unwrap()
+                .op(permutation);
 
         assert_eq!(final_permutation, CubePermutation3::identity());
     }
+
+    #[test]
+    fn last_six_edges() {
+        for scramble in [
+            "B R2 U2 F R' U' B2 F U R2 U2 L' D' R2 D L R' F' R F2 B2 U D' R L2",
+            "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F",
+            "D2 F' R2 U B L' D R2 F2 U' B' L2",
+        ] {
+            let mut permutation =
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+            // Perform every step up to and including CMLL.
+            for (_, func, _) in &STEPS[..7] {
+                let action = func(permutation).unwrap();
+                permutation = CubePermutation3::from_move_sequence(action.steps.move_sequence())
+                    .unwrap()
+                    .op(permutation);
+            }
+            let solution = lse(permutation).unwrap();
+            assert_eq!(
+                CubePermutation3::from_move_sequence(solution.clone())
+                    .unwrap()
+                    .op(permutation),
+                CubePermutation3::identity(),
+                "{}",
+                scramble
+            );
+        }
+    }
+
+    #[test]
+    fn custom_block() {
+        // Build a first block on the right, from the DR edge then the BR pair, instead of on the left.
+        let edge = edge_stage(
+            "custom_edge",
+            gen_set(&["F", "R", "U", "B", "L", "D", "M"]),
+            DR,
+        );
+        let pair = pair_stage("custom_pair", gen_set(&["F", "L", "U", "B", "M"]), BR, BDR);
+        for scramble in [
+            "B R2 U2 F R' U' B2 F U R2 U2 L' D' R2 D L R' F' R F2 B2 U D' R L2",
+            "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F",
+        ] {
+            let mut permutation =
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+            let solution = edge.solve(&edge_signature(permutation, DR)).unwrap();
+            permutation = CubePermutation3::from_move_sequence(solution.clone())
+                .unwrap()
+                .op(permutation);
+            let solution = pair.solve(&pair_signature(permutation, BR, BDR)).unwrap();
+            permutation = CubePermutation3::from_move_sequence(solution.clone())
+                .unwrap()
+                .op(permutation);
+            assert_eq!(
+                edge_signature(permutation, DR),
+                (EdgeCubelet(DR), CyclicGroup::identity())
+            );
+            assert_eq!(
+                pair_signature(permutation, BR, BDR),
+                pair_signature(CubePermutation3::identity(), BR, BDR)
+            );
+        }
+    }
+
+    #[test]
+    fn l4e_slice_turn_metric() {
+        // Each conjugated slice turn counts as three moves, not as a single transition.
+        assert_eq!(metric::stm(&"E2 M' E2".parse().unwrap()), 3);
+
+        let graph = l4e_graph();
+        let target = l4e_signature(CubePermutation3::identity());
+        for signature in graph.signatures() {
+            assert_eq!(
+                L4E.solve(signature).map(metric::stm),
+                graph
+                    .solve_single(signature, &target, metric::stm)
+                    .map(|solution| metric::stm(&solution)),
+                "{:?}",
+                signature
+            );
+        }
+    }
+
+    #[test]
+    fn method_stages() {
+        let scramble: MoveSequence =
+            "B R2 U2 F R' U' B2 F U R2 U2 L' D' R2 D L R' F' R F2 B2 U D' R L2"
+                .parse()
+                .unwrap();
+        let permutation = CubePermutation3::from_move_sequence(scramble).unwrap();
+        let solution = solve(permutation).unwrap();
+        assert_eq!(
+            CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                .unwrap()
+                .op(permutation),
+            CubePermutation3::identity()
+        );
+        assert_eq!(solution.description.as_deref(), Some("Roux method"));
+        // Running the stages one at a time by hand gives the same solution.
+        let mut current = permutation;
+        let mut moves = MoveSequence { moves: Vec::new() };
+        for stage in Roux.stages() {
+            let seq = stage(current).unwrap().steps.move_sequence();
+            current = CubePermutation3::from_move_sequence(seq.clone())
+                .unwrap()
+                .op(current);
+            moves = seq.op(moves);
+        }
+        assert_eq!(
+            solution.steps.move_sequence().to_string(),
+            moves.to_string()
+        );
+        assert_eq!(Roux.stages().len(), STEPS.len());
+    }
+
+    #[test]
+    fn reconstruction() {
+        let scramble: MoveSequence = "R U2 F' L D B2 R' U F2 D' L2 B U' R2 F".parse().unwrap();
+        let solution = solve(CubePermutation3::from_move_sequence(scramble).unwrap()).unwrap();
+        let reconstruction = solution.to_reconstruction(metric::stm);
+
+        let lines = reconstruction.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), STEPS.len());
+        for (line, (step, _, _)) in lines.iter().zip(STEPS) {
+            assert!(line.starts_with(&format!("{}: ", step)), "{}", line);
+        }
+        // The last line ends with the total move count.
+        let total = solution.move_count(metric::stm);
+        assert!(lines[lines.len() - 1].ends_with(&format!("/{})", total)));
+    }
+
+    #[test]
+    fn eo_signatures() {
+        let flipped = |alg: &str| {
+            let (orientations, _) =
+                eo_signature(CubePermutation3::from_move_sequence(alg.parse().unwrap()).unwrap());
+            orientations.map(|r| r != CyclicGroup::identity())
+        };
+        assert_eq!(flipped("U2"), [false; 6]);
+        // A quarter turn of M flips the four edges it moves.
+        assert_eq!(flipped("M"), [true, true, true, true, false, false]);
+        // The flipped edges at UF and UB are then moved to UL and UR.
+        assert_eq!(flipped("M U"), [false, false, true, true, true, true]);
+
+        // This agrees with reading the orientation of each position by undoing the permutation.
+        for alg in ["M", "M U M'", "R U M' U' r'", "F R U R' U' F'"] {
+            let permutation = CubePermutation3::from_move_sequence(alg.parse().unwrap()).unwrap();
+            assert_eq!(
+                eo_signature(permutation).0,
+                [UF, UB, DB, DF, UL, UR].map(|edge| permutation
+                    .edges()
+                    .unact(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                    .1),
+                "{}",
+                alg
+            );
+        }
+    }
+
+    #[test]
+    fn solve_errors() {
+        assert_eq!(
+            solve_scramble("R U X").unwrap_err(),
+            Error::Parse("could not parse move 3: 'X' is not a face or slice".to_string())
+        );
+        assert_eq!(
+            solve_facelets("UUU").unwrap_err(),
+            Error::Parse("expected 54 facelets, but found 3".to_string())
+        );
+        // Swapping the U sticker of UF with the R sticker of UR leaves an edge with two U stickers.
+        let solved = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+        let mut two_u_stickers = solved.to_string();
+        two_u_stickers.replace_range(7..8, "R");
+        two_u_stickers.replace_range(10..11, "U");
+        assert_eq!(
+            solve_facelets(&two_u_stickers).unwrap_err(),
+            Error::InvalidState
+        );
+        // Swapping both stickers of UF flips the edge, which cannot be done by any sequence of moves.
+        let mut flipped_edge = solved.to_string();
+        flipped_edge.replace_range(7..8, "F");
+        flipped_edge.replace_range(19..20, "U");
+        assert_eq!(
+            solve_facelets(&flipped_edge).unwrap_err(),
+            Error::InvalidState
+        );
+        assert!(solve_facelets(solved).is_ok());
+
+        // A single twisted corner can never be solved, so there is no CMLL case for it.
+        let twisted_corner = CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            EdgePermutation::identity(),
+            CornerPermutation::new_unchecked(CornerCubelet::enumerate().map(|c| {
+                if c == CornerCubelet(CornerType::FUR) {
+                    (c, CyclicGroup::new(1))
+                } else {
+                    (c, CyclicGroup::identity())
+                }
+            })),
+        );
+        let error = solve_verified(twisted_corner).unwrap_err();
+        // The error names the step, and the twisted corner can be seen in the signature.
+        let signature = format!(
+            "{:?}",
+            [
+                (CornerCubelet(CornerType::FUL), CyclicGroup::<3>::new(0)),
+                (CornerCubelet(CornerType::FUR), CyclicGroup::new(1)),
+                (CornerCubelet(CornerType::BUR), CyclicGroup::new(0)),
+                (CornerCubelet(CornerType::BUL), CyclicGroup::new(0)),
+            ]
+        );
+        assert_eq!(
+            error,
+            Error::StepFailed {
+                step: "CMLL",
+                signature: Some(signature.clone()),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "could not complete the step \"CMLL\" from the signature {}",
+                signature
+            )
+        );
+
+        // A single flipped edge is only noticed when the last six edges are oriented.
+        let flipped_edge = CubePermutation3::new_unchecked(
+            CentrePermutation::identity(),
+            EdgePermutation::new_unchecked(EdgeCubelet::enumerate().map(|e| {
+                if e == EdgeCubelet(UF) {
+                    (e, CyclicGroup::new(1))
+                } else {
+                    (e, CyclicGroup::identity())
+                }
+            })),
+            CornerPermutation::identity(),
+        );
+        match solve_verified(flipped_edge).unwrap_err() {
+            Error::StepFailed {
+                step,
+                signature: Some(_),
+            } => assert_eq!(step, "Orientation of last six edges"),
+            error => panic!("unexpected error {}", error),
+        }
+
+        // Swapping the U and D centres is a reflection of the cube, but no step of the method tracks the U centre.
+        let swapped_centres = CubePermutation3::new_unchecked(
+            CentrePermutation::new_unchecked([
+                CentreCubelet(FaceType::F),
+                CentreCubelet(FaceType::R),
+                CentreCubelet(FaceType::D),
+                CentreCubelet(FaceType::B),
+                CentreCubelet(FaceType::L),
+                CentreCubelet(FaceType::U),
+            ]),
+            EdgePermutation::identity(),
+            CornerPermutation::identity(),
+        );
+        let error = solve_verified(swapped_centres).unwrap_err();
+        assert_eq!(error, Error::InvalidState);
+        assert_eq!(
+            error.to_string(),
+            "the cube state is not a valid arrangement of pieces"
+        );
+
+        assert!(solve_scramble("R U R' U'").is_ok());
+    }
+
+    #[test]
+    fn eo_graph_coverage() {
+        let graph = SequenceGraph::new("roux_eo", gen_set(&["U", "M"]), eo_signature);
+        let solver = graph.search(([CyclicGroup::identity(); 6], false), metric::stm);
+        // An even number of the six edges are misoriented, and the centres may be on either axis.
+        assert_eq!(graph.len(), 32 * 2);
+        assert_eq!(solver.len(), graph.len());
+        for signature in graph.signatures() {
+            assert!(solver.contains(signature));
+        }
+        let mut odd = [CyclicGroup::identity(); 6];
+        odd[0] = CyclicGroup::new(1);
+        assert!(!solver.contains(&(odd, false)));
+    }
+
+    #[test]
+    fn step_breakdown() {
+        let scramble: MoveSequence = "F2 U' R2 B D' L2 F R' U2 B' D R2 L U F' D2"
+            .parse()
+            .unwrap();
+        let action = solve(CubePermutation3::from_move_sequence(scramble).unwrap()).unwrap();
+        let breakdown = action.step_breakdown();
+        assert_eq!(
+            breakdown
+                .iter()
+                .map(|(name, _, _)| *name)
+                .collect::<Vec<_>>(),
+            STEPS.map(|(name, _, _)| name)
+        );
+        assert_eq!(
+            breakdown.iter().map(|(_, htm, _)| htm).sum::<u64>(),
+            action.move_count(metric::htm)
+        );
+        assert_eq!(
+            breakdown.iter().map(|(_, _, stm)| stm).sum::<u64>(),
+            action.move_count(metric::stm)
+        );
+        assert!(breakdown.iter().all(|(_, htm, stm)| htm >= stm));
+    }
+
+    #[test]
+    fn colour_neutral() {
+        for (scramble, held) in [
+            ("R U2 F' L D2 B R' U F2 D' L2 B2 U R2 F", "x y"),
+            ("D2 F' R U2 L B2 D' R2 U F L' B U2 R D2 F2", "z'"),
+            ("F2 U' R2 B D' L2 F R' U2 B' D R2 L U F' D2", "y2"),
+        ] {
+            let permutation =
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+            // The same scramble, with the cube held in a different orientation.
+            let held = CubePermutation3::from_move_sequence(held.parse().unwrap())
+                .unwrap()
+                .op(permutation);
+            let solution = solve_colour_neutral(held).unwrap();
+            assert_eq!(
+                CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                    .unwrap()
+                    .op(held),
+                CubePermutation3::identity(),
+                "{}",
+                scramble
+            );
+            // The chosen orientation is no worse than solving in the standard orientation.
+            assert!(
+                solution.move_count(metric::stm)
+                    <= solve(permutation).unwrap().move_count(metric::stm),
+                "{}",
+                scramble
+            );
+        }
+    }
+
+    #[test]
+    fn mirrored() {
+        let scramble: MoveSequence = "D2 F' R U2 L B2 D' R2 U F L' B U2 R D2 F2".parse().unwrap();
+        let permutation = CubePermutation3::from_move_sequence(scramble).unwrap();
+        let solution = solve_mirrored(permutation).unwrap();
+        assert_eq!(
+            CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                .unwrap()
+                .op(permutation),
+            CubePermutation3::identity()
+        );
+
+        // The first block is built on the right, so the first edge is the DR edge.
+        if let ActionSteps::Sequence { actions } = &solution.steps {
+            let first_edge = CubePermutation3::from_move_sequence(actions[0].steps.move_sequence())
+                .unwrap()
+                .op(permutation);
+            assert_eq!(
+                first_edge
+                    .edges()
+                    .act(&(EdgeCubelet(DR), CyclicGroup::identity())),
+                (EdgeCubelet(DR), CyclicGroup::identity())
+            );
+        }
+    }
+
+    #[test]
+    fn cmll_skip() {
+        // These scrambles only turn the M slice and the U layer, so the corners are already solved up to AUF.
+        // The solve fails at the first step that cannot be completed, so CMLL must be able to do nothing.
+        for scramble in ["M' U M", "U M2 U' M", "M U2 M' U'"] {
+            let permutation =
+                CubePermutation3::from_move_sequence(scramble.parse().unwrap()).unwrap();
+            let cmll = cmll(permutation).unwrap();
+            assert!(
+                cmll.moves.iter().all(|mv| mv.axis == Axis::UD),
+                "{}: {}",
+                scramble,
+                cmll
+            );
+            assert!(solve_verified(permutation).is_ok(), "{}", scramble);
+        }
+    }
+
+    #[test]
+    fn cmll_l_cases() {
+        // In the L cases, two diagonally opposite corners are oriented and the other two are twisted.
+        // Performing the inverse of each algorithm creates its case, which CMLL must solve up to AUF.
+        let u = CubePermutation3::from_move("U".parse().unwrap());
+        let aufs =
+            [0, 1, 2, 3].map(|turns| (0..turns).fold(CubePermutation3::identity(), |g, _| u.op(g)));
+        for alg in [
+            "F R U' R' U' R U R' F'",
+            "F R' F' R U R U' R'",
+            "R U2 R D R' U2 R D' R2'",
+            "R' U2 R' D' R U2 R' D R2",
+            "R U2 R' U' R U R' U' R U R' U' R U' R'",
+            "R U2 R2' F R F' R U2 R'",
+        ] {
+            let case = CubePermutation3::from_move_sequence(
+                alg.parse::<MoveSequence>().unwrap().inverse(),
+            )
+            .unwrap();
+            let solution = cmll(case).unwrap_or_else(|| panic!("no CMLL for {}", alg));
+            let solved = CubePermutation3::from_move_sequence(solution)
+                .unwrap()
+                .op(case);
+            assert!(
+                aufs.iter().any(|auf| cmll_signature(auf.op(solved))
+                    == cmll_signature(CubePermutation3::identity())),
+                "{}",
+                alg
+            );
+        }
+    }
+
+    #[test]
+    fn cmll_alternatives() {
+        // Alternatives with wide moves in place of some R moves have the same effect on the corners.
+        let pairs = [
+            ("R U R' U R U2 R'", "r U R' U R U2 r'"),
+            ("R U R' U' R' F R F'", "r U R' U' r' F R F'"),
+        ]
+        .map(|(alg, alternative)| {
+            (
+                alg.parse::<MoveSequence>().unwrap(),
+                alternative.parse::<MoveSequence>().unwrap(),
+            )
+        });
+        let solver = AlgorithmicSolver::new(
+            "cmll_alternatives",
+            pairs
+                .iter()
+                .flat_map(|(alg, alternative)| [alg.clone(), alternative.clone()])
+                .collect(),
+            vec!["U".parse().unwrap()],
+            vec!["U".parse().unwrap()],
+            cmll_signature,
+            metric::etm,
+        );
+
+        // The algorithms create their cases, so they are solved by their inverses.
+        for (alg, alternative) in pairs {
+            let case = cmll_signature(CubePermutation3::from_move_sequence(alg.clone()).unwrap());
+            assert_eq!(
+                solver.solve_all(&case),
+                [alg.inverse(), alternative.inverse()]
+            );
+            assert_eq!(solver.solve(&case), Some(&alg.inverse()));
+        }
+    }
+
+    #[test]
+    fn cmll_cases() {
+        // Every arrangement of the U layer corners with orientations summing to zero has a CMLL case.
+        let u_corners = [
+            CornerType::FUR,
+            CornerType::FUL,
+            CornerType::BUR,
+            CornerType::BUL,
+        ];
+        let mut cases = 0;
+        for a in 0..4 {
+            for b in 0..4 {
+                for c in 0..4 {
+                    if a == b || b == c || a == c {
+                        continue;
+                    }
+                    let d = 6 - a - b - c;
+                    for twists in 0..27 {
+                        let twists = [twists % 3, twists / 3 % 3, twists / 9];
+                        let last_twist = 6 - twists.iter().sum::<u8>();
+                        let mut corners =
+                            CornerCubelet::enumerate().map(|c| (c, CyclicGroup::identity()));
+                        for (i, target) in [a, b, c, d].into_iter().enumerate() {
+                            corners[u_corners[i] as usize] = (
+                                CornerCubelet(u_corners[target]),
+                                CyclicGroup::new(*twists.get(i).unwrap_or(&last_twist)),
+                            );
+                        }
+                        let permutation = CubePermutation3::new_unchecked(
+                            CentrePermutation::identity(),
+                            EdgePermutation::identity(),
+                            CornerPermutation::new_unchecked(corners),
+                        );
+                        assert!(cmll(permutation).is_some(), "{}", permutation);
+                        cases += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(cases, 24 * 27);
+    }
 }
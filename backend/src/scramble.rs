@@ -0,0 +1,108 @@
+use rand::{rngs::StdRng, SeedableRng};
+use wasm_bindgen::prelude::*;
+
+use crate::{cube::MoveSequence, group::InverseSemigroup, permute::CubePermutation3};
+
+/// Returns a permutation of a 3x3x3 cube chosen uniformly at random from all permutations
+/// that can be reached from the solved state. The centres are not moved.
+pub fn random_state() -> CubePermutation3 {
+    CubePermutation3::random_solvable(&mut rand::thread_rng())
+}
+
+/// Returns a move sequence that scrambles a solved cube into a uniformly random state,
+/// in the style of official WCA scrambles.
+///
+/// The sequence is the inverse of a two-phase solution to the random state,
+/// so it uses only face turns, and is not necessarily optimal. See [scramble_to].
+pub fn random_state_scramble() -> MoveSequence {
+    scramble_to(random_state())
+}
+
+/// Returns a random-state scramble in Singmaster notation, chosen using a generator seeded with `seed`.
+/// The same seed always gives the same scramble.
+///
+/// As in `random_state_scramble`, the scramble is the inverse of a two-phase solution,
+/// so it is usually at most 24 moves long.
+#[wasm_bindgen]
+pub fn generate_scramble(seed: u32) -> String {
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    scramble_to(CubePermutation3::random_solvable(&mut rng)).to_string()
+}
+
+/// The longest scramble that the two-phase solver looks for.
+const MAX_SCRAMBLE_LENGTH: usize = 24;
+
+/// How long the two-phase solver may search for a scramble before falling back to the Roux method.
+const SCRAMBLE_TIMEOUT_MS: u64 = 10_000;
+
+/// Returns a move sequence that turns a solved cube into the given state.
+///
+/// The sequence is the inverse of a two-phase solution of at most [MAX_SCRAMBLE_LENGTH] moves.
+/// If no such solution is found within [SCRAMBLE_TIMEOUT_MS] milliseconds,
+/// the inverse of a Roux solution is used instead, which is much longer and may contain slice moves.
+fn scramble_to(permutation: CubePermutation3) -> MoveSequence {
+    let solution = crate::two_phase::solve(permutation, MAX_SCRAMBLE_LENGTH, SCRAMBLE_TIMEOUT_MS)
+        .unwrap_or_else(|| {
+            crate::roux::solve_verified(permutation)
+                .expect("random states should always be solvable")
+                .steps
+                .move_sequence()
+        });
+    solution.inverse().canonicalise()
+}
+
+/// Returns a sequence of `length` moves, each chosen uniformly at random from `moves`.
+/// This gives test cases that are a known number of moves from the solved state.
+#[cfg(test)]
+pub(crate) fn random_moves<R: rand::Rng + ?Sized>(
+    rng: &mut R,
+    moves: &[crate::cube::Move],
+    length: usize,
+) -> MoveSequence {
+    use rand::seq::SliceRandom;
+    MoveSequence {
+        moves: (0..length).map(|_| *moves.choose(rng).unwrap()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_states() {
+        for _ in 0..10 {
+            let state = random_state();
+            assert!(state.is_solvable());
+            let scramble = scramble_to(state);
+            assert!(scramble.len() <= MAX_SCRAMBLE_LENGTH);
+            assert_eq!(
+                CubePermutation3::from_move_sequence(scramble).unwrap(),
+                state
+            );
+        }
+
+        // The Roux solver verifies that it really solved the cube.
+        let scramble = random_state_scramble();
+        assert!(!scramble.moves.is_empty());
+        let state = CubePermutation3::from_move_sequence(scramble).unwrap();
+        assert!(crate::roux::solve_verified(state).is_ok());
+    }
+
+    #[test]
+    fn seeded_scrambles() {
+        for seed in [0, 1323, u32::MAX] {
+            let scramble = generate_scramble(seed);
+            assert_eq!(generate_scramble(seed), scramble);
+
+            let moves = scramble.parse::<MoveSequence>().unwrap();
+            assert!(!moves.is_empty());
+            let mut rng = StdRng::seed_from_u64(seed as u64);
+            assert_eq!(
+                CubePermutation3::from_move_sequence(moves).unwrap(),
+                CubePermutation3::random_solvable(&mut rng)
+            );
+        }
+        assert_ne!(generate_scramble(0), generate_scramble(1));
+    }
+}
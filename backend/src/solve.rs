@@ -1,4 +1,10 @@
-use crate::{cube::MoveSequence, permute::CubePermutation3, Move, MoveSequenceConv};
+use crate::{
+    cube::{MoveSequence, RotationType},
+    error::Error,
+    group::{InverseSemigroup, Magma},
+    permute::CubePermutation3,
+    Move, MoveSequenceConv,
+};
 use wasm_bindgen::prelude::*;
 use web_sys::{Document, Element};
 
@@ -26,29 +32,368 @@ pub enum ActionReason {
     Intuitive,
 }
 
-/// TODO: Add conjugate, commutator, and algorithmic action steps.
+/// TODO: Add algorithmic action steps.
 #[derive(Debug)]
 pub enum ActionSteps {
-    /// TODO: Moves can be cancelled into other moves.
-    /// We should be able to mark moves as "cancelled" so that
-    /// they appear but do not ever get performed or contribute to move count.
+    /// Perform this move.
+    /// Neighbouring moves of the same layers may later be merged into it; see [Action::collapse_cancellations].
     Move { mv: Move },
+    /// A move that is cancelled out by a neighbouring move, for example when one step ends with `R`
+    /// and the next begins with `R'`.
+    /// It is still displayed, but it is never performed and does not contribute to the move count.
+    /// See [Action::collapse_cancellations].
+    CancelledMove { mv: Move },
     /// Perform this sequence of actions.
     Sequence { actions: Vec<Action> },
+    /// Perform the setup, then the body, then undo the setup.
+    /// This is written `[setup: body]`.
+    Conjugate {
+        setup: Box<ActionSteps>,
+        body: Box<ActionSteps>,
+    },
+    /// Perform `a`, then `b`, then undo `a`, then undo `b`.
+    /// This is written `[a, b]`.
+    Commutator {
+        a: Box<ActionSteps>,
+        b: Box<ActionSteps>,
+    },
+    /// Switches between solving the scramble and solving its inverse, as in NISS.
+    /// Moves after an odd number of switches are made on the inverse scramble.
+    /// They act like premoves performed before the scramble,
+    /// so they appear inverted and in reverse order at the end of the solution.
+    SwitchToInverse,
 }
 
 impl ActionSteps {
+    /// Pushes every move and cancelled move in these steps onto `moves`, in order.
+    /// Conjugates and commutators are replaced with `None`, since their moves cannot be cancelled individually.
+    /// Switches to the inverse scramble are also replaced with `None`, since moves on either side of them are not adjacent.
+    fn collect_moves<'a>(&'a mut self, moves: &mut Vec<Option<&'a mut ActionSteps>>) {
+        match self {
+            ActionSteps::Move { .. } | ActionSteps::CancelledMove { .. } => moves.push(Some(self)),
+            ActionSteps::Sequence { actions } => {
+                for action in actions {
+                    action.steps.collect_moves(moves);
+                }
+            }
+            ActionSteps::Conjugate { .. }
+            | ActionSteps::Commutator { .. }
+            | ActionSteps::SwitchToInverse => moves.push(None),
+        }
+    }
+
+    /// Pushes the moves of these steps onto `normal` or `inverse`, depending on which scramble they are made on.
+    /// `on_inverse` is toggled by each [ActionSteps::SwitchToInverse].
+    fn collect_niss_moves(
+        &self,
+        on_inverse: &mut bool,
+        normal: &mut Vec<Move>,
+        inverse: &mut Vec<Move>,
+    ) {
+        match self {
+            ActionSteps::SwitchToInverse => *on_inverse = !*on_inverse,
+            ActionSteps::Sequence { actions } => {
+                for action in actions {
+                    action.steps.collect_niss_moves(on_inverse, normal, inverse);
+                }
+            }
+            steps => {
+                let moves = steps.move_sequence().moves;
+                if *on_inverse {
+                    inverse.extend(moves);
+                } else {
+                    normal.extend(moves);
+                }
+            }
+        }
+    }
+
+    /// The moves that these steps perform on the original scramble.
+    /// Moves made on the inverse scramble are inverted and moved to the end.
     pub fn move_sequence(&self) -> MoveSequence {
         match self {
             ActionSteps::Move { mv } => MoveSequence { moves: vec![*mv] },
-            ActionSteps::Sequence { actions } => MoveSequence {
-                moves: actions
+            ActionSteps::CancelledMove { .. } | ActionSteps::SwitchToInverse => {
+                MoveSequence { moves: Vec::new() }
+            }
+            ActionSteps::Sequence { .. } => {
+                let mut normal = Vec::new();
+                let mut inverse = Vec::new();
+                self.collect_niss_moves(&mut false, &mut normal, &mut inverse);
+                MoveSequence {
+                    moves: [normal, MoveSequence { moves: inverse }.inverse().moves].concat(),
+                }
+            }
+            ActionSteps::Conjugate { setup, body } => {
+                let setup = setup.move_sequence();
+                MoveSequence {
+                    moves: [
+                        setup.moves.clone(),
+                        body.move_sequence().moves,
+                        setup.inverse().moves,
+                    ]
+                    .concat(),
+                }
+            }
+            ActionSteps::Commutator { a, b } => {
+                let a = a.move_sequence();
+                let b = b.move_sequence();
+                MoveSequence {
+                    moves: [
+                        a.moves.clone(),
+                        b.moves.clone(),
+                        a.inverse().moves,
+                        b.inverse().moves,
+                    ]
+                    .concat(),
+                }
+            }
+        }
+    }
+
+    /// Writes the moves of these steps on a single line, writing conjugates and commutators in bracket notation.
+    /// Cancelled moves are never performed, so they are left out.
+    fn to_inline_string(&self) -> String {
+        match self {
+            ActionSteps::Move { mv } => mv.to_string(),
+            ActionSteps::CancelledMove { .. } => String::new(),
+            ActionSteps::Sequence { actions } => actions
+                .iter()
+                .map(|action| action.steps.to_inline_string())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+            ActionSteps::SwitchToInverse => "(NISS)".to_string(),
+            ActionSteps::Conjugate { setup, body } => format!(
+                "[{}: {}]",
+                setup.to_inline_string(),
+                body.to_inline_string()
+            ),
+            ActionSteps::Commutator { a, b } => {
+                format!("[{}, {}]", a.to_inline_string(), b.to_inline_string())
+            }
+        }
+    }
+}
+
+impl Action {
+    /// Converts this action into a plain JavaScript object, so that it can be rendered by the front end.
+    /// See [crate::roux::solve_roux] for the shape of the object.
+    pub fn to_js(&self) -> JsValue {
+        let object = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            js_sys::Reflect::set(&object, &JsValue::from_str(key), &value)
+                .expect("plain objects accept any property");
+        };
+
+        let reason = match &self.reason {
+            ActionReason::Solve => "solve",
+            ActionReason::Shuffle => "shuffle",
+            ActionReason::SolveStep { step_name } => {
+                set("stepName", JsValue::from_str(step_name));
+                "step"
+            }
+            ActionReason::Intuitive => "intuitive",
+        };
+        set("reason", JsValue::from_str(reason));
+        set(
+            "description",
+            self.description
+                .as_deref()
+                .map_or(JsValue::NULL, JsValue::from_str),
+        );
+        set(
+            "moves",
+            JsValue::from_str(&self.steps.move_sequence().to_string()),
+        );
+        if let ActionSteps::Sequence { actions } = &self.steps {
+            set(
+                "actions",
+                actions
                     .iter()
-                    .map(|act| act.steps.move_sequence().moves)
-                    .flatten()
-                    .collect(),
-            },
+                    .map(Action::to_js)
+                    .collect::<js_sys::Array>()
+                    .into(),
+            );
+        }
+
+        object.into()
+    }
+
+    /// Counts the moves performed by this action, in the given metric.
+    /// See [crate::metric] for the available metrics.
+    pub fn move_count(&self, metric: impl Fn(&MoveSequence) -> u64) -> u64 {
+        metric(&self.steps.move_sequence())
+    }
+
+    /// Lists the named steps of a solve, in order, with the number of moves each one took
+    /// in the half turn metric and in the slice turn metric, as `(name, htm, stm)`.
+    /// Steps nested inside other steps are counted as part of the outermost step.
+    /// Moves that are not part of any named step are not listed.
+    pub fn step_breakdown(&self) -> Vec<(&'static str, u64, u64)> {
+        let mut breakdown = Vec::new();
+        self.add_step_breakdown(&mut breakdown);
+        breakdown
+    }
+
+    /// Writes this action as text for sharing, with one line for each of its steps, such as `First edge: R U' M2 (3/3)`.
+    /// Each line ends with the number of moves in that step and the running total, counted in the given metric.
+    /// A step that is itself made of named steps is followed by those steps, indented by two more spaces.
+    /// Consecutive moves that are not part of any named step are written together on a line with no name.
+    pub fn to_reconstruction(&self, metric: impl Fn(&MoveSequence) -> u64) -> String {
+        let mut lines = Vec::new();
+        match &self.steps {
+            ActionSteps::Sequence { actions } if actions.iter().any(Action::is_named) => {
+                add_reconstruction_lines(actions, &metric, 0, &mut 0, &mut lines)
+            }
+            _ => {
+                add_reconstruction_lines(std::slice::from_ref(self), &metric, 0, &mut 0, &mut lines)
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Whether this action has a name of its own in a reconstruction.
+    fn is_named(&self) -> bool {
+        self.reconstruction_label().is_some()
+    }
+
+    /// The name of this action in a reconstruction, or `None` if it is just some moves.
+    fn reconstruction_label(&self) -> Option<String> {
+        match (&self.reason, &self.description) {
+            (ActionReason::SolveStep { step_name }, Some(description)) => {
+                Some(format!("{} ({})", step_name, description))
+            }
+            (ActionReason::SolveStep { step_name }, None) => Some(step_name.to_string()),
+            (_, Some(description)) => Some(description.clone()),
+            (ActionReason::Solve, None) => Some("Solve".to_string()),
+            (ActionReason::Shuffle, None) => Some("Scramble".to_string()),
+            (ActionReason::Intuitive, None) => None,
+        }
+    }
+
+    /// Merges each pair of adjacent moves that turn the same layers, even across the boundaries between steps.
+    /// The first move is marked as cancelled, and the second is replaced with their combined turn,
+    /// so `U' U2` becomes `U` and `U U` becomes `U2`. If the two moves undo each other, both are cancelled.
+    /// Cancelling a pair can make another pair adjacent, so `R U U' R'` is cancelled entirely.
+    /// Moves inside conjugates and commutators are left alone.
+    pub fn collapse_cancellations(&mut self) {
+        let mut moves = Vec::new();
+        self.steps.collect_moves(&mut moves);
+
+        // The moves that have not yet been cancelled, in order.
+        let mut uncancelled: Vec<(Move, &mut ActionSteps)> = Vec::new();
+        for steps in moves {
+            let steps = match steps {
+                Some(steps) => steps,
+                None => {
+                    uncancelled.clear();
+                    continue;
+                }
+            };
+            let mv = match steps {
+                ActionSteps::Move { mv } => *mv,
+                _ => continue,
+            };
+            match uncancelled.last() {
+                Some((previous, _))
+                    if previous.axis == mv.axis
+                        && previous.start_depth == mv.start_depth
+                        && previous.end_depth == mv.end_depth =>
+                {
+                    let (previous, previous_steps) = uncancelled.pop().unwrap();
+                    *previous_steps = ActionSteps::CancelledMove { mv: previous };
+                    match RotationType::from_rotations(
+                        previous.rotation_type.rotations() + mv.rotation_type.rotations(),
+                    ) {
+                        Some(rotation_type) => {
+                            let merged =
+                                Move::new(mv.axis, rotation_type, mv.start_depth, mv.end_depth);
+                            *steps = ActionSteps::Move { mv: merged };
+                            uncancelled.push((merged, steps));
+                        }
+                        None => *steps = ActionSteps::CancelledMove { mv },
+                    }
+                }
+                _ => uncancelled.push((mv, steps)),
+            }
+        }
+    }
+
+    fn add_step_breakdown(&self, breakdown: &mut Vec<(&'static str, u64, u64)>) {
+        match (&self.reason, &self.steps) {
+            (ActionReason::SolveStep { step_name }, _) => {
+                breakdown.push((
+                    step_name,
+                    self.move_count(crate::metric::htm),
+                    self.move_count(crate::metric::stm),
+                ));
+            }
+            (_, ActionSteps::Sequence { actions }) => {
+                for action in actions {
+                    action.add_step_breakdown(breakdown);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Adds a line to a reconstruction for each of the given actions, as described in [Action::to_reconstruction].
+/// `total` is the running move count before these actions, and is updated to include them.
+fn add_reconstruction_lines(
+    actions: &[Action],
+    metric: &impl Fn(&MoveSequence) -> u64,
+    depth: usize,
+    total: &mut u64,
+    lines: &mut Vec<String>,
+) {
+    let indent = "  ".repeat(depth);
+    let mut i = 0;
+    while i < actions.len() {
+        let label = actions[i].reconstruction_label();
+        // Unnamed actions are collated onto a single line.
+        let end = if label.is_some() {
+            i + 1
+        } else {
+            actions[i..]
+                .iter()
+                .position(Action::is_named)
+                .map_or(actions.len(), |len| i + len)
+        };
+        let (moves, count) =
+            actions[i..end]
+                .iter()
+                .fold((Vec::new(), 0), |(mut moves, count), action| {
+                    let text = action.steps.to_inline_string();
+                    if !text.is_empty() {
+                        moves.push(text);
+                    }
+                    (moves, count + action.move_count(metric))
+                });
+        let start = *total;
+        *total += count;
+
+        let mut line = indent.clone();
+        if let Some(label) = &label {
+            line += label;
+            line += ":";
+            if !moves.is_empty() {
+                line += " ";
+            }
         }
+        line += &moves.join(" ");
+        line += &format!(" ({}/{})", count, total);
+        lines.push(line);
+
+        // Only named actions are listed with their own steps.
+        if let (Some(_), ActionSteps::Sequence { actions: children }) = (&label, &actions[i].steps)
+        {
+            if children.iter().any(Action::is_named) {
+                add_reconstruction_lines(children, metric, depth + 1, &mut { start }, lines);
+            }
+        }
+        i = end;
     }
 }
 
@@ -70,6 +415,64 @@ pub fn move_sequence_to_intuitive_action(step_name: &'static str, seq: MoveSeque
     }
 }
 
+/// A step of a method: its name, the stage that performs it,
+/// and optionally a function that describes the signature the stage looks up, for reporting errors.
+pub type Step = (
+    &'static str,
+    fn(CubePermutation3) -> Option<Action>,
+    Option<fn(CubePermutation3) -> String>,
+);
+
+/// A method for solving the cube in a fixed sequence of stages, such as the Roux method.
+pub trait Method {
+    /// The name of the method, used to describe a full solve.
+    fn name(&self) -> &'static str;
+
+    /// The steps of the method, in the order they are performed.
+    fn steps(&self) -> &'static [Step];
+
+    /// The stages of the method, in the order they are performed.
+    /// Each stage is given the cube as left by the previous stages, and returns `None` if it cannot be completed.
+    fn stages(&self) -> Vec<fn(CubePermutation3) -> Option<Action>> {
+        self.steps().iter().map(|&(_, stage, _)| stage).collect()
+    }
+}
+
+/// Solves the cube by performing each step of the method in turn,
+/// applying the moves of each step to the cube before starting the next.
+/// Reports the first step that could not be completed.
+pub fn try_run_method(
+    method: &impl Method,
+    mut permutation: CubePermutation3,
+) -> Result<Action, Error> {
+    let mut actions = Vec::new();
+    for &(step, stage, signature) in method.steps() {
+        let (action, next) = stage(permutation)
+            .and_then(|action| {
+                let performed = CubePermutation3::from_move_sequence(action.steps.move_sequence())?;
+                Some((action, performed.op(permutation)))
+            })
+            .ok_or_else(|| Error::StepFailed {
+                step,
+                signature: signature.map(|signature| signature(permutation)),
+            })?;
+        permutation = next;
+        actions.push(action);
+    }
+
+    Ok(Action {
+        reason: ActionReason::Solve,
+        description: Some(method.name().to_string()),
+        steps: ActionSteps::Sequence { actions },
+    })
+}
+
+/// Solves the cube by performing each step of the method in turn.
+/// Returns `None` if any step could not be completed.
+pub fn run_method(method: &impl Method, permutation: CubePermutation3) -> Option<Action> {
+    try_run_method(method, permutation).ok()
+}
+
 #[wasm_bindgen]
 #[allow(dead_code)]
 pub fn action_to_div() -> MoveSequenceConv {
@@ -81,7 +484,8 @@ pub fn action_to_div() -> MoveSequenceConv {
         .parse::<MoveSequence>()
         .unwrap();
     let action =
-        crate::roux::solve(CubePermutation3::from_move_sequence(scramble.clone())).unwrap();
+        crate::roux::solve(CubePermutation3::from_move_sequence(scramble.clone()).unwrap())
+            .unwrap();
 
     // Clear the history div.
     let range = document.create_range().unwrap();
@@ -105,14 +509,50 @@ pub fn action_to_div() -> MoveSequenceConv {
         },
         &document,
         &history,
+        &mut NissState::default(),
     )
     .unwrap();
-    add_action_to_div(action, &document, &history).unwrap();
+    let mut niss = NissState::default();
+    add_action_to_div(action, &document, &history, &mut niss).unwrap();
+    if !niss.inverse_moves.is_empty() {
+        let val = document.create_element("p").unwrap();
+        val.set_text_content(Some("Undo the moves made on the inverse: "));
+        add_inverse_moves(&niss, &mut append_span(&document, &val)).unwrap();
+        history.append_child(&val).unwrap();
+    }
 
     seq.into()
 }
 
-fn add_action_to_div(action: Action, document: &Document, div: &Element) -> Result<(), JsValue> {
+/// Tracks whether the moves being rendered are made on the inverse scramble,
+/// toggled by each [ActionSteps::SwitchToInverse].
+#[derive(Default)]
+struct NissState {
+    on_inverse: bool,
+    /// The moves made on the inverse scramble so far, in the order they were made.
+    inverse_moves: Vec<Move>,
+}
+
+/// Returns a function that appends a span with the given text and class name to the element.
+fn append_span<'a>(
+    document: &'a Document,
+    div: &'a Element,
+) -> impl FnMut(&str, &'static str) -> Result<(), JsValue> + 'a {
+    move |text, class| {
+        let span = document.create_element("span")?;
+        span.set_text_content(Some(text));
+        span.set_class_name(class);
+        div.append_child(&span)?;
+        Ok(())
+    }
+}
+
+fn add_action_to_div(
+    action: Action,
+    document: &Document,
+    div: &Element,
+    niss: &mut NissState,
+) -> Result<(), JsValue> {
     let reason = match &action.reason {
         ActionReason::Solve => Some("Solve the cube".to_string()),
         ActionReason::Shuffle => Some("Shuffle the cube".to_string()),
@@ -135,12 +575,12 @@ fn add_action_to_div(action: Action, document: &Document, div: &Element) -> Resu
     div.append_child(&val)?;
 
     match action.steps {
-        ActionSteps::Move { mv } => {
-            let span = document.create_element("span")?;
-            // Zero-width space
-            span.set_text_content(Some(&format!("{}\u{200b}", mv)));
-            span.set_class_name("history-move");
-            div.append_child(&span)?;
+        ActionSteps::Move { .. }
+        | ActionSteps::CancelledMove { .. }
+        | ActionSteps::Conjugate { .. }
+        | ActionSteps::Commutator { .. }
+        | ActionSteps::SwitchToInverse => {
+            add_steps_inline(&action.steps, niss, &mut append_span(document, div))?;
         }
         ActionSteps::Sequence { actions } => {
             let list = document.create_element(match &action.reason {
@@ -150,42 +590,37 @@ fn add_action_to_div(action: Action, document: &Document, div: &Element) -> Resu
             // For each action that's just a move with no description, collate them into this list.
             let mut collated_moves = Vec::new();
             for sub_action in actions {
-                match &sub_action {
+                match sub_action {
                     Action {
                         reason: _,
                         description: None,
-                        steps: ActionSteps::Move { mv },
+                        steps:
+                            steps @ (ActionSteps::Move { .. } | ActionSteps::CancelledMove { .. }),
                     } => {
-                        collated_moves.push(*mv);
+                        collated_moves.push(steps);
                     }
-                    _ => {
+                    sub_action => {
                         // It's not just a simple move.
                         // Add it as a bullet point.
                         // But first, add the collated moves.
                         if !collated_moves.is_empty() {
                             let li = document.create_element("li")?;
-                            for mv in std::mem::take(&mut collated_moves) {
-                                let span = document.create_element("span")?;
-                                span.set_text_content(Some(&format!("{}\u{200b}", mv)));
-                                span.set_class_name("history-move");
-                                li.append_child(&span)?;
+                            for steps in std::mem::take(&mut collated_moves) {
+                                add_steps_inline(&steps, niss, &mut append_span(document, &li))?;
                             }
                             list.append_child(&li)?;
                         }
 
                         let li = document.create_element("li")?;
-                        add_action_to_div(sub_action, document, &li)?;
+                        add_action_to_div(sub_action, document, &li, niss)?;
                         list.append_child(&li)?;
                     }
                 }
             }
             if !collated_moves.is_empty() {
                 let li = document.create_element("li")?;
-                for mv in std::mem::take(&mut collated_moves) {
-                    let span = document.create_element("span")?;
-                    span.set_text_content(Some(&format!("{}\u{200b}", mv)));
-                    span.set_class_name("history-move");
-                    li.append_child(&span)?;
+                for steps in std::mem::take(&mut collated_moves) {
+                    add_steps_inline(&steps, niss, &mut append_span(document, &li))?;
                 }
                 list.append_child(&li)?;
             }
@@ -195,3 +630,330 @@ fn add_action_to_div(action: Action, document: &Document, div: &Element) -> Resu
 
     Ok(())
 }
+
+/// Adds the moves of the given steps on a single line using `add_span`,
+/// writing conjugates and commutators in bracket notation.
+/// The front end steps through the `history-move` elements in order, so the moves implied by the brackets
+/// are still added, but hidden.
+/// Moves made on the inverse scramble are not `history-move`s; they are recorded in `niss`
+/// and performed later by [add_inverse_moves].
+fn add_steps_inline(
+    steps: &ActionSteps,
+    niss: &mut NissState,
+    add_span: &mut dyn FnMut(&str, &'static str) -> Result<(), JsValue>,
+) -> Result<(), JsValue> {
+    fn add_implied_moves(
+        seq: MoveSequence,
+        niss: &mut NissState,
+        add_span: &mut dyn FnMut(&str, &'static str) -> Result<(), JsValue>,
+    ) -> Result<(), JsValue> {
+        for mv in seq.moves {
+            if niss.on_inverse {
+                niss.inverse_moves.push(mv);
+                add_span(&mv.to_string(), "history-inverse-move history-implied-move")?;
+            } else {
+                add_span(&mv.to_string(), "history-move history-implied-move")?;
+            }
+        }
+        Ok(())
+    }
+
+    match steps {
+        ActionSteps::Move { mv } => {
+            // Zero-width space
+            let text = format!("{}\u{200b}", mv);
+            if niss.on_inverse {
+                niss.inverse_moves.push(*mv);
+                add_span(&text, "history-inverse-move")?;
+            } else {
+                add_span(&text, "history-move")?;
+            }
+        }
+        ActionSteps::CancelledMove { mv } => {
+            // This is not a `history-move`, since it is never performed.
+            add_span(&format!("{}\u{200b}", mv), "history-cancelled-move")?;
+        }
+        ActionSteps::Sequence { actions } => {
+            for action in actions {
+                add_steps_inline(&action.steps, niss, add_span)?;
+            }
+        }
+        ActionSteps::SwitchToInverse => {
+            niss.on_inverse = !niss.on_inverse;
+            add_span("(NISS)", "history-bracket")?;
+        }
+        ActionSteps::Conjugate { setup, body } => {
+            add_span("[", "history-bracket")?;
+            add_steps_inline(setup, niss, add_span)?;
+            add_span(": ", "history-bracket")?;
+            add_steps_inline(body, niss, add_span)?;
+            add_span("]", "history-bracket")?;
+            add_implied_moves(setup.move_sequence().inverse(), niss, add_span)?;
+        }
+        ActionSteps::Commutator { a, b } => {
+            add_span("[", "history-bracket")?;
+            add_steps_inline(a, niss, add_span)?;
+            add_span(", ", "history-bracket")?;
+            add_steps_inline(b, niss, add_span)?;
+            add_span("]", "history-bracket")?;
+            add_implied_moves(
+                MoveSequence {
+                    moves: [
+                        a.move_sequence().inverse().moves,
+                        b.move_sequence().inverse().moves,
+                    ]
+                    .concat(),
+                },
+                niss,
+                add_span,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the moves made on the inverse scramble, inverted, as `history-move`s.
+/// Performed after all other moves, this matches [ActionSteps::move_sequence].
+fn add_inverse_moves(
+    niss: &NissState,
+    add_span: &mut dyn FnMut(&str, &'static str) -> Result<(), JsValue>,
+) -> Result<(), JsValue> {
+    let moves = MoveSequence {
+        moves: niss.inverse_moves.clone(),
+    };
+    for mv in moves.inverse().moves {
+        add_span(&format!("{}\u{200b}", mv), "history-move")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group::Unital;
+
+    use super::*;
+
+    fn steps(seq: &str) -> Box<ActionSteps> {
+        Box::new(move_sequence_to_intuitive_action("", seq.parse().unwrap()).steps)
+    }
+
+    fn permutation(seq: &str) -> CubePermutation3 {
+        CubePermutation3::from_move_sequence(seq.parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn cancellations() {
+        let mut action = Action {
+            reason: ActionReason::Solve,
+            description: None,
+            steps: ActionSteps::Sequence {
+                actions: vec![
+                    move_sequence_to_intuitive_action(
+                        "Second pair",
+                        "U R U' R' F".parse().unwrap(),
+                    ),
+                    move_sequence_to_intuitive_action("Second edge", "F' R U2 M".parse().unwrap()),
+                ],
+            },
+        };
+        let permutation =
+            CubePermutation3::from_move_sequence(action.steps.move_sequence()).unwrap();
+        assert_eq!(action.move_count(crate::metric::stm), 9);
+
+        action.collapse_cancellations();
+        assert_eq!(action.move_count(crate::metric::stm), 4);
+        assert_eq!(action.steps.move_sequence().to_string(), "U R U M");
+        assert_eq!(
+            CubePermutation3::from_move_sequence(action.steps.move_sequence()).unwrap(),
+            permutation
+        );
+        assert_eq!(
+            action.step_breakdown(),
+            [("Second pair", 2, 2), ("Second edge", 3, 2)]
+        );
+
+        // Turns of the same layers are merged modulo four, but turns of different layers are not.
+        for (seq, collapsed) in [
+            ("U U", "U2"),
+            ("U' U2", "U"),
+            ("U2 U2'", ""),
+            ("R U U U R'", "R U' R'"),
+            ("R Rw R'", "R Rw R'"),
+        ] {
+            let mut action = move_sequence_to_intuitive_action("Step", seq.parse().unwrap());
+            action.collapse_cancellations();
+            assert_eq!(
+                action.steps.move_sequence().to_string(),
+                collapsed,
+                "{}",
+                seq
+            );
+        }
+    }
+
+    #[test]
+    fn conjugates_and_commutators() {
+        let commutator = ActionSteps::Commutator {
+            a: steps("R"),
+            b: steps("U"),
+        };
+        assert_eq!(commutator.move_sequence().to_string(), "R U R' U'");
+        assert_eq!(
+            CubePermutation3::from_move_sequence(commutator.move_sequence()).unwrap(),
+            permutation("R U R' U'")
+        );
+
+        let conjugate = ActionSteps::Conjugate {
+            setup: steps("F"),
+            body: Box::new(commutator),
+        };
+        assert_eq!(
+            CubePermutation3::from_move_sequence(conjugate.move_sequence()).unwrap(),
+            permutation("F R U R' U' F'")
+        );
+
+        // Turns of opposite faces commute, so their commutator does nothing.
+        let commutator = ActionSteps::Commutator {
+            a: steps("R"),
+            b: steps("L'"),
+        };
+        assert_eq!(
+            CubePermutation3::from_move_sequence(commutator.move_sequence()).unwrap(),
+            CubePermutation3::identity()
+        );
+    }
+
+    #[test]
+    fn reconstruction() {
+        let action = Action {
+            reason: ActionReason::Solve,
+            description: Some("Example method".to_string()),
+            steps: ActionSteps::Sequence {
+                actions: vec![
+                    move_sequence_to_intuitive_action("First edge", "R U' M2".parse().unwrap()),
+                    Action {
+                        reason: ActionReason::Intuitive,
+                        description: None,
+                        steps: ActionSteps::Move {
+                            mv: "F".parse().unwrap(),
+                        },
+                    },
+                    Action {
+                        reason: ActionReason::SolveStep { step_name: "Block" },
+                        description: None,
+                        steps: ActionSteps::Sequence {
+                            actions: vec![
+                                move_sequence_to_intuitive_action("Pair", "U R".parse().unwrap()),
+                                move_sequence_to_intuitive_action("Insert", "R'".parse().unwrap()),
+                            ],
+                        },
+                    },
+                    Action {
+                        reason: ActionReason::SolveStep {
+                            step_name: "Last layer",
+                        },
+                        description: Some("Sexy move".to_string()),
+                        steps: ActionSteps::Commutator {
+                            a: steps("R"),
+                            b: steps("U"),
+                        },
+                    },
+                ],
+            },
+        };
+        assert_eq!(
+            action.to_reconstruction(crate::metric::stm),
+            "First edge: R U' M2 (3/3)\n\
+             F (1/4)\n\
+             Block: U R R' (3/7)\n  \
+             Pair: U R (2/6)\n  \
+             Insert: R' (1/7)\n\
+             Last layer (Sexy move): [R, U] (4/11)"
+        );
+
+        // A single move is a reconstruction of its own.
+        let action = move_sequence_to_intuitive_action("Cross", "D2".parse().unwrap());
+        assert_eq!(
+            action.to_reconstruction(crate::metric::stm),
+            "Cross: D2 (1/1)"
+        );
+    }
+
+    #[test]
+    fn niss() {
+        let scramble = "R U F";
+        // On the normal scramble, `F'` solves one move. On the inverse scramble `F' U' R'`,
+        // `R` solves another, leaving `U` to solve on the normal scramble with the premove `R'`.
+        let steps = ActionSteps::Sequence {
+            actions: vec![
+                move_sequence_to_intuitive_action("Normal", "F'".parse().unwrap()),
+                Action {
+                    reason: ActionReason::Intuitive,
+                    description: None,
+                    steps: ActionSteps::SwitchToInverse,
+                },
+                move_sequence_to_intuitive_action("Inverse", "R".parse().unwrap()),
+                Action {
+                    reason: ActionReason::Intuitive,
+                    description: None,
+                    steps: ActionSteps::SwitchToInverse,
+                },
+                move_sequence_to_intuitive_action("Normal", "U'".parse().unwrap()),
+            ],
+        };
+        let solution = steps.move_sequence();
+        assert_eq!(solution.to_string(), "F' U' R'");
+        assert_eq!(
+            CubePermutation3::from_move_sequence(solution)
+                .unwrap()
+                .op(permutation(scramble)),
+            CubePermutation3::identity()
+        );
+    }
+
+    #[test]
+    fn niss_rendering() {
+        let switch = || Action {
+            reason: ActionReason::Intuitive,
+            description: None,
+            steps: ActionSteps::SwitchToInverse,
+        };
+        let steps = ActionSteps::Sequence {
+            actions: vec![
+                move_sequence_to_intuitive_action("Normal", "F' D".parse().unwrap()),
+                switch(),
+                move_sequence_to_intuitive_action("Inverse", "R B".parse().unwrap()),
+                Action {
+                    reason: ActionReason::Intuitive,
+                    description: None,
+                    steps: ActionSteps::Conjugate {
+                        setup: steps("L"),
+                        body: steps("U"),
+                    },
+                },
+                switch(),
+                move_sequence_to_intuitive_action("Normal", "U'".parse().unwrap()),
+            ],
+        };
+
+        let mut niss = NissState::default();
+        let mut played = Vec::new();
+        let mut add_span = |text: &str, class: &'static str| {
+            if class.split(' ').any(|class| class == "history-move") {
+                played.push(text.trim_end_matches('\u{200b}').to_string());
+            }
+            Ok(())
+        };
+        add_steps_inline(&steps, &mut niss, &mut add_span).unwrap();
+        add_inverse_moves(&niss, &mut add_span).unwrap();
+
+        let expected = steps
+            .move_sequence()
+            .moves
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(played, expected);
+    }
+}
@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::{
+    coordinate::{self, is_redundant_after, pair_distances, CoordinateTable},
+    cube::{Move, MoveSequence},
+    group::{Magma, Unital},
+    permute::{CentrePermutation, CubePermutation3},
+};
+
+/// Computes a coordinate of a cube state, as one of the functions in [coordinate].
+type CoordinateFunction = fn(&CubePermutation3) -> usize;
+
+/// One phase of the two-phase algorithm.
+/// A phase tracks three coordinates of the cube, and is complete when all three are solved.
+/// The third coordinate is paired with each of the others to estimate the number of moves remaining.
+struct Phase {
+    moves: Vec<Move>,
+    coordinate_functions: [CoordinateFunction; 3],
+    coordinates: [CoordinateTable; 3],
+    /// The distances to the solved state for the pairs `(0, 2)` and `(1, 2)` of coordinates.
+    distances: [Vec<u8>; 2],
+}
+
+impl Phase {
+    fn new(moves: &[&str], coordinates: [(usize, CoordinateFunction); 3]) -> Self {
+        let moves = moves
+            .iter()
+            .map(|mv| mv.parse::<Move>().unwrap())
+            .collect::<Vec<_>>();
+        let turns = moves
+            .iter()
+            .map(|&mv| CubePermutation3::from_move(mv))
+            .collect::<Vec<_>>();
+        let tables = coordinates.map(|(size, f)| CoordinateTable::new(size, &turns, f));
+        let distances = [
+            pair_distances(&tables[0], &tables[2]),
+            pair_distances(&tables[1], &tables[2]),
+        ];
+        Self {
+            moves,
+            coordinate_functions: coordinates.map(|(_, f)| f),
+            coordinates: tables,
+            distances,
+        }
+    }
+
+    fn coordinates(&self, permutation: &CubePermutation3) -> [usize; 3] {
+        self.coordinate_functions.map(|f| f(permutation))
+    }
+
+    /// The coordinates after performing the `i`th move of this phase.
+    fn transition(&self, coordinates: [usize; 3], i: usize) -> [usize; 3] {
+        let mut result = coordinates;
+        for (c, table) in result.iter_mut().zip(&self.coordinates) {
+            *c = table.transitions[*c][i];
+        }
+        result
+    }
+
+    /// A lower bound on the number of moves needed to complete this phase.
+    /// This is zero exactly when the phase is complete.
+    fn lower_bound(&self, [a, b, c]: [usize; 3]) -> usize {
+        let size = self.coordinates[2].transitions.len();
+        self.distances[0][a * size + c].max(self.distances[1][b * size + c]) as usize
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Phase 1 brings the cube into the subgroup generated by `U`, `D`, `R2`, `L2`, `F2`, `B2`,
+    /// where every piece is oriented and the slice edges are in the slice between U and D.
+    static ref PHASE_1: Phase = Phase::new(
+        &["U", "U2", "U'", "D", "D2", "D'", "R", "R2", "R'", "L", "L2", "L'", "F", "F2", "F'", "B", "B2", "B'"],
+        [
+            (2187, coordinate::corner_orientation),
+            (2048, coordinate::edge_orientation),
+            (495, coordinate::ud_slice),
+        ],
+    );
+
+    /// Phase 2 solves the cube using only moves from that subgroup.
+    static ref PHASE_2: Phase = Phase::new(
+        &["U", "U2", "U'", "D", "D2", "D'", "R2", "L2", "F2", "B2"],
+        [
+            (40320, coordinate::corner_permutation),
+            (40320, coordinate::ud_edge_permutation),
+            (24, coordinate::ud_slice_permutation),
+        ],
+    );
+}
+
+/// Builds the lookup tables used by [`solve`], which otherwise happens the first time it is called.
+/// This takes a few seconds, so it is useful to do it ahead of time.
+pub fn precompute_tables() {
+    lazy_static::initialize(&PHASE_1);
+    lazy_static::initialize(&PHASE_2);
+}
+
+/// Solves the cube using Kociemba's two-phase algorithm, returning a solution of at most `max_length` face turns.
+///
+/// The first solution found within the bound is returned, so it is not necessarily optimal.
+/// Returns `None` if there is no solution within the bound, if no solution was found within `timeout_ms` milliseconds,
+/// or if the cube cannot be solved without rotating it.
+/// Almost every cube can be solved in 21 moves within a few seconds.
+///
+/// The lookup tables are built the first time this function is called, unless [`precompute_tables`] was called.
+pub fn solve(
+    permutation: CubePermutation3,
+    max_length: usize,
+    timeout_ms: u64,
+) -> Option<MoveSequence> {
+    if *permutation.centres() != CentrePermutation::identity() {
+        return None;
+    }
+
+    let mut search = Search {
+        permutation,
+        max_length,
+        deadline: Instant::now() + Duration::from_millis(timeout_ms),
+        moves: Vec::new(),
+    };
+    let coordinates = PHASE_1.coordinates(&permutation);
+    for depth in 0..=max_length {
+        match search.phase_1(coordinates, depth) {
+            Some(true) => {
+                return Some(MoveSequence {
+                    moves: search.moves,
+                })
+            }
+            Some(false) => {}
+            None => return None,
+        }
+    }
+    None
+}
+
+struct Search {
+    permutation: CubePermutation3,
+    max_length: usize,
+    deadline: Instant,
+    /// The moves of the solution so far.
+    moves: Vec<Move>,
+}
+
+impl Search {
+    /// Searches for phase 1 solutions with exactly `remaining` more moves, and tries to complete each of them.
+    /// Returns `Some(true)` if a solution was found, and `None` if the search timed out.
+    fn phase_1(&mut self, coordinates: [usize; 3], remaining: usize) -> Option<bool> {
+        if Instant::now() > self.deadline {
+            return None;
+        }
+        if PHASE_1.lower_bound(coordinates) > remaining {
+            return Some(false);
+        }
+        if remaining == 0 {
+            return self.start_phase_2();
+        }
+
+        for (i, &mv) in PHASE_1.moves.iter().enumerate() {
+            if let Some(&previous) = self.moves.last() {
+                if is_redundant_after(previous, mv) {
+                    continue;
+                }
+            }
+            // If the last move of phase 1 is also a phase 2 move,
+            // the cube was already in the phase 2 subgroup one move earlier, so this was searched before.
+            if remaining == 1 && PHASE_2.moves.contains(&mv) {
+                continue;
+            }
+
+            self.moves.push(mv);
+            let result = self.phase_1(PHASE_1.transition(coordinates, i), remaining - 1);
+            if result != Some(false) {
+                return result;
+            }
+            self.moves.pop();
+        }
+        Some(false)
+    }
+
+    /// Tries to complete the current phase 1 solution within the maximum length.
+    /// Returns `Some(true)` if a solution was found, and `None` if the search timed out.
+    fn start_phase_2(&mut self) -> Option<bool> {
+        let permutation = self
+            .moves
+            .iter()
+            .fold(self.permutation, |permutation, &mv| {
+                CubePermutation3::from_move(mv).op(permutation)
+            });
+        let coordinates = PHASE_2.coordinates(&permutation);
+        let phase_1_length = self.moves.len();
+        for depth in 0..=self.max_length - phase_1_length {
+            if self.phase_2(coordinates, depth)? {
+                return Some(true);
+            }
+        }
+        Some(false)
+    }
+
+    /// Searches for phase 2 solutions with exactly `remaining` more moves.
+    /// Returns `Some(true)` if a solution was found, and `None` if the search timed out.
+    fn phase_2(&mut self, coordinates: [usize; 3], remaining: usize) -> Option<bool> {
+        if Instant::now() > self.deadline {
+            return None;
+        }
+        let lower_bound = PHASE_2.lower_bound(coordinates);
+        if lower_bound > remaining {
+            return Some(false);
+        }
+        if remaining == 0 {
+            return Some(true);
+        }
+
+        for (i, &mv) in PHASE_2.moves.iter().enumerate() {
+            if let Some(&previous) = self.moves.last() {
+                if is_redundant_after(previous, mv) {
+                    continue;
+                }
+            }
+            self.moves.push(mv);
+            let result = self.phase_2(PHASE_2.transition(coordinates, i), remaining - 1);
+            if result != Some(false) {
+                return result;
+            }
+            self.moves.pop();
+        }
+        Some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::scramble::random_moves;
+
+    fn assert_solves(permutation: CubePermutation3, solution: &MoveSequence) {
+        assert_eq!(
+            CubePermutation3::from_move_sequence(solution.clone()).unwrap().op(permutation),
+            CubePermutation3::identity()
+        );
+    }
+
+    #[test]
+    fn random_scrambles() {
+        precompute_tables();
+        let mut rng = StdRng::seed_from_u64(2468);
+        for _ in 0..3 {
+            let permutation = CubePermutation3::random_solvable(&mut rng);
+            let solution = solve(permutation, 30, 60_000).unwrap();
+            assert_solves(permutation, &solution);
+            assert!(solution.moves.len() <= 30);
+        }
+    }
+
+    #[test]
+    fn short_scrambles() {
+        let mut rng = StdRng::seed_from_u64(97531);
+        for length in 0..7 {
+            let scramble = random_moves(&mut rng, &PHASE_1.moves, length);
+            let permutation = CubePermutation3::from_move_sequence(scramble).unwrap();
+            let solution = solve(permutation, length, 60_000).unwrap();
+            assert_solves(permutation, &solution);
+            assert!(solution.moves.len() <= length);
+        }
+
+        let permutation = CubePermutation3::from_move_sequence("R U R' U'".parse().unwrap()).unwrap();
+        assert_eq!(solve(permutation, 3, 60_000), None);
+        let permutation = CubePermutation3::from_move_sequence("R x".parse().unwrap()).unwrap();
+        assert_eq!(solve(permutation, 20, 60_000), None);
+
+        // The search gives up once the time runs out, even in the middle of phase 2.
+        let permutation = CubePermutation3::random_solvable(&mut rng);
+        assert_eq!(solve(permutation, 30, 0), None);
+    }
+}
@@ -1,7 +1,11 @@
 // A macro to provide `println!(..)`-style syntax for `console.log` logging.
+// Outside of WASM (for instance, when running tests natively), this just prints to stdout.
 macro_rules! log {
     ( $( $t:tt )* ) => {
+        #[cfg(target_arch = "wasm32")]
         web_sys::console::log_1(&format!( $( $t )* ).into());
+        #[cfg(not(target_arch = "wasm32"))]
+        println!( $( $t )* );
     }
 }
 pub(crate) use log;
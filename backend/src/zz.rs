@@ -0,0 +1,241 @@
+use crate::{
+    cfop::{edges_signature, pair_signature, U_CORNERS, U_EDGES},
+    cube::{
+        EdgeType, MoveSequence,
+        {CornerType::*, EdgeType::*},
+    },
+    group::{CyclicGroup, Enumerable, GroupAction, Magma, Unital},
+    intuitive::{SequenceGraph, SequenceSolver},
+    metric,
+    permute::{CornerCubelet, CubePermutation3, EdgeCubelet},
+    petrus,
+    solve::{move_sequence_to_intuitive_action, run_method, Action, Method, Step},
+};
+
+type ZzEoSignature = [CyclicGroup<2>; 12];
+type ZzLineSignature = [(EdgeCubelet, CyclicGroup<2>); 2];
+type ZzEdgeSignature = (EdgeCubelet, CyclicGroup<2>);
+type ZzPairSignature = (
+    (EdgeCubelet, CyclicGroup<2>),
+    (CornerCubelet, CyclicGroup<3>),
+);
+type ZzLastLayerSignature = ([EdgeCubelet; 4], [(CornerCubelet, CyclicGroup<3>); 4]);
+
+fn eo_signature(cube: CubePermutation3) -> ZzEoSignature {
+    // As in OLL, only the orientation of the piece in each position matters.
+    EdgeType::enumerate().map(|edge| {
+        cube.edges()
+            .unact(&(EdgeCubelet(edge), CyclicGroup::identity()))
+            .1
+    })
+}
+
+fn last_layer_signature(cube: CubePermutation3) -> ZzLastLayerSignature {
+    // Every edge is already oriented, so only the positions of the edges are needed.
+    (
+        U_EDGES.map(|edge| {
+            cube.edges()
+                .act(&(EdgeCubelet(edge), CyclicGroup::identity()))
+                .0
+        }),
+        U_CORNERS.map(|corner| {
+            cube.corners()
+                .act(&(CornerCubelet(corner), CyclicGroup::identity()))
+        }),
+    )
+}
+
+lazy_static::lazy_static! {
+    static ref ZZ_EO: SequenceSolver<ZzEoSignature> = {
+        let gen_set = vec!["F", "R", "U", "B", "L", "D"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_eo", gen_set, eo_signature);
+        graph.search(eo_signature(CubePermutation3::identity()), metric::etm)
+    };
+
+    /// Half turns of F and B do not flip any edges, so the line is solved without undoing edge orientation.
+    static ref ZZ_LINE: SequenceSolver<ZzLineSignature> = {
+        let gen_set = vec!["R", "U", "L", "D", "F2", "B2"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_line", gen_set, |cube| {
+            edges_signature(cube, [DF, DB])
+        });
+        graph.search(edges_signature(CubePermutation3::identity(), [DF, DB]), metric::etm)
+    };
+
+    /// From here on, only R, U, and L are turned, so the line and the edge orientation are preserved.
+    static ref ZZ_DL_EDGE: SequenceSolver<ZzEdgeSignature> = {
+        let gen_set = vec!["L", "U", "R"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_dl", gen_set, |cube| {
+            cube.edges()
+                .act(&(EdgeCubelet(DL), CyclicGroup::identity()))
+        });
+        graph.search((EdgeCubelet(DL), CyclicGroup::identity()), metric::etm)
+    };
+
+    static ref ZZ_FRONT_LEFT_PAIR: SequenceSolver<ZzPairSignature> = {
+        let gen_set = vec!["U", "R", "L U L'", "L U2 L'", "L U' L'", "L' U L", "L' U2 L", "L' U' L"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_fl", gen_set, |cube| pair_signature(cube, FL, FDL));
+        graph.search(pair_signature(CubePermutation3::identity(), FL, FDL), metric::etm)
+    };
+
+    static ref ZZ_BACK_LEFT_PAIR: SequenceSolver<ZzPairSignature> = {
+        let gen_set = vec!["U", "R", "L U L'", "L U2 L'", "L U' L'"]
+            .into_iter()
+            .map(|x| x.parse::<MoveSequence>().unwrap())
+            .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_bl", gen_set, |cube| pair_signature(cube, BL, BDL));
+        graph.search(pair_signature(CubePermutation3::identity(), BL, BDL), metric::etm)
+    };
+
+    /// The last layer is solved with algorithms that do not turn F or B:
+    /// the Sune and Antisune twist corners, the U permutation cycles edges,
+    /// and the Niklas cycles corners.
+    static ref ZZ_LAST_LAYER: SequenceSolver<ZzLastLayerSignature> = {
+        let gen_set = vec![
+            "U",
+            "R U R' U R U2 R'",
+            "R U2 R' U' R U' R'",
+            "R U' R U R U R U' R' U' R2",
+            "L' U R U' L U R' U'",
+        ]
+        .into_iter()
+        .map(|x| x.parse::<MoveSequence>().unwrap())
+        .collect::<Vec<_>>();
+
+        let graph = SequenceGraph::new("zz_ll", gen_set, last_layer_signature);
+        graph.search(last_layer_signature(CubePermutation3::identity()), metric::etm)
+    };
+}
+
+/// Orients every edge, then solves the DF and DB edges without flipping any edges.
+pub fn eo_line(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let eo = ZZ_EO.solve(&eo_signature(permutation))?;
+    let permutation = CubePermutation3::from_move_sequence(eo.clone())?.op(permutation);
+    let line = ZZ_LINE.solve(&edges_signature(permutation, [DF, DB]))?;
+    Some(line.clone().op(eo.clone()))
+}
+
+pub fn eo_line_action(permutation: CubePermutation3) -> Option<Action> {
+    eo_line(permutation).map(|seq| move_sequence_to_intuitive_action("EOLine", seq))
+}
+
+/// Solves the 1x2x3 block on the left, made of the DL edge and the front and back left pairs.
+pub fn left_block(permutation: CubePermutation3) -> Option<MoveSequence> {
+    let edge = ZZ_DL_EDGE.solve(
+        &permutation
+            .edges()
+            .act(&(EdgeCubelet(DL), CyclicGroup::identity())),
+    )?;
+    let permutation = CubePermutation3::from_move_sequence(edge.clone())?.op(permutation);
+    let front = ZZ_FRONT_LEFT_PAIR.solve(&pair_signature(permutation, FL, FDL))?;
+    let permutation = CubePermutation3::from_move_sequence(front.clone())?.op(permutation);
+    let back = ZZ_BACK_LEFT_PAIR.solve(&pair_signature(permutation, BL, BDL))?;
+    Some(back.clone().op(front.clone()).op(edge.clone()))
+}
+
+pub fn left_block_action(permutation: CubePermutation3) -> Option<Action> {
+    left_block(permutation).map(|seq| move_sequence_to_intuitive_action("Left block", seq))
+}
+
+/// Solves the 1x2x3 block on the right with R and U, in the same way as the end of the Petrus first two layers.
+pub fn right_block(permutation: CubePermutation3) -> Option<MoveSequence> {
+    petrus::first_two_layers(permutation)
+}
+
+pub fn right_block_action(permutation: CubePermutation3) -> Option<Action> {
+    right_block(permutation).map(|seq| move_sequence_to_intuitive_action("Right block", seq))
+}
+
+/// Solves the last layer, given that its edges are oriented.
+pub fn last_layer(permutation: CubePermutation3) -> Option<&'static MoveSequence> {
+    ZZ_LAST_LAYER.solve(&last_layer_signature(permutation))
+}
+
+pub fn last_layer_action(permutation: CubePermutation3) -> Option<Action> {
+    last_layer(permutation).map(|seq| move_sequence_to_intuitive_action("Last layer", seq.clone()))
+}
+
+/// The steps of the ZZ method, in the order they are performed.
+const STEPS: [Step; 4] = [
+    ("EOLine", eo_line_action, None),
+    ("Left block", left_block_action, None),
+    ("Right block", right_block_action, None),
+    ("Last layer", last_layer_action, None),
+];
+
+/// The ZZ method: edge orientation and a line on the bottom, then blocks on either side, then the last layer.
+pub struct Zz;
+
+impl Method for Zz {
+    fn name(&self) -> &'static str {
+        "ZZ method"
+    }
+
+    fn steps(&self) -> &'static [Step] {
+        &STEPS
+    }
+}
+
+pub fn solve(permutation: CubePermutation3) -> Option<Action> {
+    run_method(&Zz, permutation)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        cube::{Axis, RotationType},
+        solve::ActionSteps,
+    };
+
+    use super::*;
+
+    #[test]
+    fn zz_solve() {
+        let mut rng = StdRng::seed_from_u64(86420);
+        for _ in 0..5 {
+            let permutation = CubePermutation3::random_solvable(&mut rng);
+            let solution = solve(permutation).unwrap();
+            let final_permutation =
+                CubePermutation3::from_move_sequence(solution.steps.move_sequence())
+                    .unwrap()
+                    .op(permutation);
+            assert_eq!(
+                final_permutation,
+                CubePermutation3::identity(),
+                "{}",
+                permutation
+            );
+
+            // After the EOLine, F and B are never turned by a quarter turn, which would flip edges.
+            match solution.steps {
+                ActionSteps::Sequence { actions } => {
+                    assert_eq!(actions.len(), STEPS.len());
+                    for action in &actions[1..] {
+                        assert!(action.steps.move_sequence().moves.iter().all(|mv| {
+                            mv.axis != Axis::FB || mv.rotation_type == RotationType::Double
+                        }));
+                    }
+                }
+                _ => panic!("expected a sequence of steps"),
+            }
+        }
+    }
+}
@@ -11,3 +11,32 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn solve_roux() {
+    let get = |value: &wasm_bindgen::JsValue, key: &str| {
+        js_sys::Reflect::get(value, &key.into()).unwrap()
+    };
+
+    let solution = autocuber::solve_roux("R U R' U'").unwrap();
+    assert_eq!(get(&solution, "reason"), "solve");
+    assert_eq!(get(&solution, "description"), "Roux method");
+    let steps = js_sys::Array::from(&get(&solution, "actions"));
+    assert!(steps.length() > 0);
+    let first_step = steps.get(0);
+    assert_eq!(get(&first_step, "reason"), "step");
+    assert_eq!(get(&first_step, "stepName"), "First edge");
+
+    assert!(autocuber::solve_roux("R X").is_err());
+}
+
+#[wasm_bindgen_test]
+fn wasm_cube() {
+    let mut cube = autocuber::WasmCube::new();
+    let solved = cube.facelets();
+    cube.apply_str("R U R' U'").unwrap();
+    assert_ne!(cube.facelets(), solved);
+    assert!(cube.apply_str("R X").is_err());
+    cube.apply_str("U R U' R'").unwrap();
+    assert_eq!(cube.facelets(), solved);
+}